@@ -0,0 +1,109 @@
+//! Benchmarks for persisting many tracked entities
+//!
+//! They are the baseline for performance-motivated changes. Save one before a change with
+//! `cargo bench -- --save-baseline main` and compare against it after with
+//! `cargo bench -- --baseline main`.
+
+use std::fs;
+use std::hint::black_box;
+use std::io::BufWriter;
+
+use bevy::prelude::*;
+use bevy::window::WindowClosing;
+use bevy_where_was_i::{SaveFormat, SaveTrigger, SaveWhereWasI, WhereWasI, WhereWasIPlugin};
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+
+/// The number of tracked entities in the full save and load passes
+const ENTITIES: usize = 10_000;
+
+const DIRECTORY: &str = "target/benches/persistence";
+
+fn transform(index: usize) -> Transform {
+    Transform::from_xyz(index as f32, 0.5, -(index as f32))
+        .with_rotation(Quat::from_rotation_y(index as f32 * 0.01))
+        .with_scale(Vec3::splat(1.5))
+}
+
+/// An app with [`ENTITIES`] tracked entities that hasn't run its startup schedules yet
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_message::<WindowClosing>()
+        .add_plugins(WhereWasIPlugin {
+            skip_unchanged: false,
+            ..WhereWasIPlugin::from_directory(DIRECTORY)
+        });
+    for index in 0..ENTITIES {
+        app.world_mut().spawn((
+            WhereWasI::from_name(format!("entity_{index}")),
+            transform(index),
+        ));
+    }
+    app
+}
+
+fn save(app: &mut App) {
+    app.world_mut().write_message(SaveWhereWasI {
+        trigger: SaveTrigger::Manual,
+        window: None,
+    });
+    app.update();
+}
+
+fn serialize(c: &mut Criterion) {
+    for format in [SaveFormat::Text, SaveFormat::Binary] {
+        c.bench_function(&format!("serialize {format:?}"), |b| {
+            b.iter(|| {
+                let mut writer = BufWriter::new(Vec::new());
+                format
+                    .serialize(&mut writer, black_box(&transform(7)))
+                    .unwrap();
+                writer.into_inner().unwrap()
+            })
+        });
+    }
+}
+
+fn deserialize(c: &mut Criterion) {
+    for format in [SaveFormat::Text, SaveFormat::Binary] {
+        let mut writer = BufWriter::new(Vec::new());
+        format.serialize(&mut writer, &transform(7)).unwrap();
+        let bytes = writer.into_inner().unwrap();
+        c.bench_function(&format!("deserialize {format:?}"), |b| {
+            b.iter(|| format.deserialize(black_box(bytes.as_slice())).unwrap())
+        });
+    }
+}
+
+fn save_pass(c: &mut Criterion) {
+    let _ = fs::remove_dir_all(DIRECTORY);
+    let mut app = app();
+    app.update();
+
+    let mut group = c.benchmark_group("passes");
+    group.sample_size(10);
+    group.bench_function(format!("save {ENTITIES} entities"), |b| {
+        b.iter(|| save(&mut app))
+    });
+    group.finish();
+}
+
+fn load_pass(c: &mut Criterion) {
+    let _ = fs::remove_dir_all(DIRECTORY);
+    let mut saved = app();
+    saved.update();
+    save(&mut saved);
+
+    let mut group = c.benchmark_group("passes");
+    group.sample_size(10);
+    group.bench_function(format!("load {ENTITIES} entities"), |b| {
+        // The first update reads the savefiles and restores the entities
+        b.iter_batched(app, |mut app| app.update(), BatchSize::LargeInput)
+    });
+    group.finish();
+
+    fs::remove_dir_all(DIRECTORY).unwrap();
+}
+
+criterion_group!(benches, serialize, deserialize, save_pass, load_pass);
+criterion_main!(benches);
@@ -0,0 +1,115 @@
+//! Derive macros for [bevy-where-was-i](https://github.com/evroon/bevy-where-was-i), enabled with
+//! its `derive` feature
+#![warn(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, parse_macro_input};
+
+/// Implement `Persist` for a struct with named fields, saving every field under its name
+///
+/// The type of every field has to implement `PersistValue`. Fields marked with `#[persist(skip)]`
+/// are neither saved nor restored. `#[persist(version = 2)]` on the struct sets `Persist::VERSION`.
+#[proc_macro_derive(Persist, attributes(persist))]
+pub fn derive_persist(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match persist(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Whether `field` is marked with `#[persist(skip)]`
+fn skipped(field: &syn::Field) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("persist"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `skip`"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+/// The version set with `#[persist(version = ...)]` on the struct, if any
+fn version(input: &DeriveInput) -> syn::Result<Option<syn::LitInt>> {
+    let mut version = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("persist"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("version") {
+                version = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `version`"))
+            }
+        })?;
+    }
+    Ok(version)
+}
+
+fn persist(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "Persist can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "Persist can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut idents = Vec::new();
+    for field in &fields.named {
+        if !skipped(field)? {
+            idents.extend(field.ident.clone());
+        }
+    }
+    let names = idents
+        .iter()
+        .map(|ident| ident.to_string())
+        .collect::<Vec<_>>();
+
+    let version = version(input)?
+        .map(|version| quote!(const VERSION: u32 = #version;))
+        .unwrap_or_default();
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::bevy_where_was_i::Persist for #name #ty_generics #where_clause {
+            #version
+
+            fn write_fields(&self, fields: &mut ::bevy_where_was_i::PersistedFields) {
+                #(fields.insert(#names, &self.#idents);)*
+            }
+
+            fn read_fields(
+                &mut self,
+                fields: &::bevy_where_was_i::PersistedFields,
+            ) -> ::core::result::Result<(), ::bevy_where_was_i::WhereWasIParseError> {
+                #(
+                    if let ::core::option::Option::Some(value) = fields.get(#names)? {
+                        self.#idents = value;
+                    }
+                )*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
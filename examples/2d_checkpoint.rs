@@ -0,0 +1,188 @@
+use bevy::DefaultPlugins;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy_where_was_i::{WhereWasI, WhereWasIPlugin, WhereWasIRestored};
+
+const GRAVITY: f32 = -1500.0;
+const SPEED: f32 = 300.0;
+const JUMP: f32 = 700.0;
+const PLAYER_SIZE: Vec2 = Vec2::new(32.0, 48.0);
+/// The player falls out of the level below this height and starts again at the checkpoint
+const FALL_LIMIT: f32 = -600.0;
+
+/// The platforms of the level, by their center and size
+const PLATFORMS: [(Vec2, Vec2); 5] = [
+    (Vec2::new(0.0, -100.0), Vec2::new(600.0, 40.0)),
+    (Vec2::new(450.0, 0.0), Vec2::new(200.0, 30.0)),
+    (Vec2::new(750.0, 100.0), Vec2::new(200.0, 30.0)),
+    (Vec2::new(1100.0, 0.0), Vec2::new(300.0, 30.0)),
+    (Vec2::new(1550.0, -100.0), Vec2::new(400.0, 40.0)),
+];
+/// The flags the player can reach, the first one is where a new game starts
+const FLAGS: [Vec2; 3] = [
+    Vec2::new(-200.0, -48.0),
+    Vec2::new(750.0, 147.0),
+    Vec2::new(1650.0, -48.0),
+];
+
+#[derive(Component, Default)]
+struct Player {
+    velocity: Vec2,
+    grounded: bool,
+}
+
+/// Marks the last flag the player reached
+#[derive(Component)]
+struct Checkpoint;
+
+/// Example for a 2D platformer that continues from the last checkpoint.
+///
+/// The checkpoint is saved as soon as the player reaches a flag, and the player starts from it at
+/// the next launch. The zoom of the orthographic camera, which is the scale of its `Transform`, is
+/// restored as well.
+///
+/// Move with the arrow keys or A and D, jump with space and zoom with the mouse wheel.
+fn main() {
+    App::new()
+        .add_plugins(WhereWasIPlugin {
+            directory: "./assets/saves/2d_checkpoint".into(),
+            ..default()
+        })
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, (setup_camera, setup_level))
+        .add_systems(
+            Update,
+            (
+                move_player,
+                reach_checkpoint,
+                fall_out_of_level,
+                follow_player,
+            )
+                .chain(),
+        )
+        .add_systems(Update, zoom_camera)
+        .run();
+}
+
+fn setup_camera(mut commands: Commands<'_, '_>) {
+    commands.spawn((Camera2d, WhereWasI::camera()));
+}
+
+fn setup_level(mut commands: Commands<'_, '_>) {
+    for (center, size) in PLATFORMS {
+        commands.spawn((
+            Sprite::from_color(Color::srgb(0.3, 0.5, 0.3), size),
+            Transform::from_translation(center.extend(0.0)),
+        ));
+    }
+    for flag in FLAGS {
+        commands.spawn((
+            Sprite::from_color(Color::srgb(0.5, 0.5, 0.5), Vec2::new(12.0, 64.0)),
+            Transform::from_translation(flag.extend(0.0)),
+        ));
+    }
+
+    let start = FLAGS[0].extend(1.0);
+    commands.spawn((
+        Player::default(),
+        Sprite::from_color(Color::srgb_u8(124, 144, 255), PLAYER_SIZE),
+        Transform::from_translation(start),
+    ));
+    // Saved right away whenever the player reaches another flag, not only when the game closes
+    commands
+        .spawn((
+            Checkpoint,
+            WhereWasI::from_name("checkpoint").save_on_change(),
+            Sprite::from_color(Color::srgb(1.0, 0.8, 0.2), Vec2::new(12.0, 64.0)),
+            Transform::from_translation(start),
+        ))
+        .observe(
+            |restored: On<WhereWasIRestored>, player: Single<&mut Transform, With<Player>>| {
+                player.into_inner().translation = restored.restored.translation;
+            },
+        );
+}
+
+fn move_player(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    player: Single<(&mut Player, &mut Transform)>,
+) {
+    let (mut player, mut transform) = player.into_inner();
+    let delta = time.delta_secs();
+
+    let mut direction = 0.0;
+    if keys.any_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) {
+        direction -= 1.0;
+    }
+    if keys.any_pressed([KeyCode::ArrowRight, KeyCode::KeyD]) {
+        direction += 1.0;
+    }
+    player.velocity.x = direction * SPEED;
+    if player.grounded && keys.just_pressed(KeyCode::Space) {
+        player.velocity.y = JUMP;
+    }
+    player.velocity.y += GRAVITY * delta;
+
+    let previous_bottom = transform.translation.y - PLAYER_SIZE.y / 2.0;
+    transform.translation += (player.velocity * delta).extend(0.0);
+    let bottom = transform.translation.y - PLAYER_SIZE.y / 2.0;
+
+    // Land on top of platforms, but jump through them from below
+    player.grounded = false;
+    for (center, size) in PLATFORMS {
+        let top = center.y + size.y / 2.0;
+        let above = (transform.translation.x - center.x).abs() < (size.x + PLAYER_SIZE.x) / 2.0;
+        if above && player.velocity.y <= 0.0 && previous_bottom >= top && bottom < top {
+            transform.translation.y = top + PLAYER_SIZE.y / 2.0;
+            player.velocity.y = 0.0;
+            player.grounded = true;
+        }
+    }
+}
+
+/// Move the checkpoint to the flag the player touches
+fn reach_checkpoint(
+    player: Single<&Transform, With<Player>>,
+    checkpoint: Single<&mut Transform, (With<Checkpoint>, Without<Player>)>,
+) {
+    let mut checkpoint = checkpoint.into_inner();
+    for flag in FLAGS {
+        let reached = flag.distance(player.translation.truncate()) < 40.0;
+        if reached && checkpoint.translation.truncate() != flag {
+            checkpoint.translation = flag.extend(1.0);
+            info!("Reached the checkpoint at {}", flag);
+        }
+    }
+}
+
+fn fall_out_of_level(
+    player: Single<(&mut Player, &mut Transform)>,
+    checkpoint: Single<&Transform, (With<Checkpoint>, Without<Player>)>,
+) {
+    let (mut player, mut transform) = player.into_inner();
+    if transform.translation.y < FALL_LIMIT {
+        transform.translation = checkpoint.translation;
+        player.velocity = Vec2::ZERO;
+    }
+}
+
+fn follow_player(
+    player: Single<&Transform, With<Player>>,
+    camera: Single<&mut Transform, (With<Camera2d>, Without<Player>)>,
+) {
+    let mut camera = camera.into_inner();
+    camera.translation.x = player.translation.x;
+    camera.translation.y = player.translation.y;
+}
+
+fn zoom_camera(
+    mut scrolls: MessageReader<MouseWheel>,
+    camera: Single<&mut Transform, With<Camera2d>>,
+) {
+    let mut camera = camera.into_inner();
+    for scroll in scrolls.read() {
+        let zoom = (camera.scale.x * (1.0 - 0.1 * scroll.y)).clamp(0.25, 4.0);
+        camera.scale = Vec3::new(zoom, zoom, 1.0);
+    }
+}
@@ -13,6 +13,7 @@ fn main() {
         .add_plugins(PanOrbitCameraPlugin)
         .add_plugins(WhereWasIPlugin {
             directory: "./assets/saves/3d_scene".into(),
+            ..default()
         })
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, setup_camera)
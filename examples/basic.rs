@@ -9,6 +9,7 @@ fn main() {
     App::new()
         .add_plugins(WhereWasIPlugin {
             directory: "./assets/saves/basic".into(),
+            ..default()
         })
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, setup_camera)
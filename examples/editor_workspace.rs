@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass, egui};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+use bevy_where_was_i::{
+    Persist, SaveTrigger, SaveWhereWasI, WhereWasI, WhereWasIAppExt, WhereWasIPlugin,
+};
+
+/// The names of the cameras that can look at the scene
+const CAMERAS: [&str; 2] = ["perspective", "top"];
+
+/// The layout of the editor panels, and which camera looks at the scene
+#[derive(Resource, Persist)]
+struct Workspace {
+    outliner_width: f32,
+    properties_width: f32,
+    show_properties: bool,
+    camera: String,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self {
+            outliner_width: 200.0,
+            properties_width: 250.0,
+            show_properties: true,
+            camera: CAMERAS[0].into(),
+        }
+    }
+}
+
+/// Example for an editor that restores its whole workspace: the size and position of its window,
+/// the layout of its egui panels and the cameras it looks at the scene with.
+///
+/// Resize and move the window, drag the edges of the panels, switch cameras and orbit them, then
+/// close the window and run the example again.
+///
+/// `cargo run --example editor_workspace --features inspector,derive`
+fn main() {
+    App::new()
+        .add_plugins(PanOrbitCameraPlugin)
+        .add_plugins(WhereWasIPlugin {
+            directory: "./assets/saves/editor_workspace".into(),
+            primary_window: Some("editor".into()),
+            ..default()
+        })
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin::default())
+        .init_resource::<Workspace>()
+        .persist_resource::<Workspace>("workspace")
+        .add_systems(Startup, (setup_cameras, setup_scene))
+        .add_systems(Update, activate_camera)
+        .add_systems(EguiPrimaryContextPass, panels)
+        .run();
+}
+
+fn setup_cameras(mut commands: Commands<'_, '_>) {
+    commands.spawn((
+        Camera3d::default(),
+        PanOrbitCamera::default(),
+        WhereWasI::from_name(CAMERAS[0])
+            .or_default(Transform::from_xyz(5.0, 5.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y)),
+    ));
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            is_active: false,
+            ..default()
+        },
+        PanOrbitCamera::default(),
+        WhereWasI::from_name(CAMERAS[1])
+            .or_default(Transform::from_xyz(0.0, 10.0, 0.01).looking_at(Vec3::ZERO, Vec3::Y)),
+    ));
+}
+
+fn setup_scene(
+    mut commands: Commands<'_, '_>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(Circle::new(4.0))),
+        MeshMaterial3d(materials.add(Color::WHITE)),
+        Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+    ));
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255))),
+        Transform::from_xyz(0.0, 0.5, 0.0),
+    ));
+    commands.spawn((
+        PointLight {
+            shadow_maps_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0),
+    ));
+}
+
+/// Render with the camera that is selected in the workspace, which is restored after startup
+fn activate_camera(workspace: Res<Workspace>, mut cameras: Query<(&WhereWasI, &mut Camera)>) {
+    if !workspace.is_changed() {
+        return;
+    }
+    for (tracked, mut camera) in &mut cameras {
+        camera.is_active = tracked.name() == workspace.camera;
+    }
+}
+
+/// Show the outliner and properties panels, and remember how wide they were
+fn panels(
+    mut contexts: EguiContexts<'_, '_>,
+    mut workspace: ResMut<Workspace>,
+    mut requests: MessageWriter<SaveWhereWasI>,
+    cameras: Query<(&WhereWasI, &Transform), With<Camera>>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    let mut viewport = egui::Ui::new(
+        ctx.clone(),
+        "viewport".into(),
+        egui::UiBuilder::new()
+            .layer_id(egui::LayerId::background())
+            .max_rect(ctx.viewport_rect()),
+    );
+
+    let outliner = egui::Panel::left("outliner")
+        .default_size(workspace.outliner_width)
+        .show_inside(&mut viewport, |ui| {
+            ui.heading("Cameras");
+            for name in CAMERAS {
+                if ui.radio(workspace.camera == name, name).clicked() {
+                    workspace.camera = name.into();
+                }
+            }
+            ui.separator();
+            ui.checkbox(&mut workspace.show_properties, "Show properties");
+            if ui.button("Save workspace").clicked() {
+                requests.write(SaveWhereWasI {
+                    trigger: SaveTrigger::Manual,
+                    window: None,
+                });
+            }
+        });
+    // Resizing a panel shouldn't count as switching cameras
+    let width = outliner.response.rect.width();
+    workspace.bypass_change_detection().outliner_width = width;
+
+    if !workspace.show_properties {
+        return Ok(());
+    }
+    let properties = egui::Panel::right("properties")
+        .default_size(workspace.properties_width)
+        .show_inside(&mut viewport, |ui| {
+            ui.heading("Properties");
+            let Some((_, transform)) = cameras
+                .iter()
+                .find(|(tracked, _)| tracked.name() == workspace.camera)
+            else {
+                return;
+            };
+            ui.label(format!("Camera: {}", workspace.camera));
+            ui.label(format!("Translation: {:.2}", transform.translation));
+            ui.label(format!("Rotation: {:.2}", transform.rotation));
+        });
+    let width = properties.response.rect.width();
+    workspace.bypass_change_detection().properties_width = width;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use bevy::window::WindowClosing;
+use bevy_where_was_i::{Persist, WhereWasI, WhereWasIAppExt, WhereWasIFirstRun, WhereWasIPlugin};
+
+/// The stats of the player, saved next to its `Transform`
+#[derive(Component, Persist, Debug)]
+#[persist(version = 1)]
+struct PlayerStats {
+    name: String,
+    level: u32,
+    experience: u64,
+    health: f32,
+    coins: u32,
+    /// Only lasts until the player is hit, so it isn't saved
+    #[persist(skip)]
+    invulnerable: bool,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        Self {
+            name: "Ferris".into(),
+            level: 1,
+            experience: 0,
+            health: 100.0,
+            coins: 0,
+            invulnerable: true,
+        }
+    }
+}
+
+/// Example for a savegame that stores the stats of the player, a component of your own, next to
+/// its position.
+///
+/// Every run plays a short session without a window and exits, which saves the game. Run it a few
+/// times to see the player walk on, collect coins and level up:
+/// `cargo run --example savegame --features derive`
+fn main() -> AppExit {
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_message::<WindowClosing>()
+        .add_plugins(WhereWasIPlugin::from_directory("./assets/saves/savegame"))
+        .persist_component::<PlayerStats>("stats")
+        // Savegames of the first version called the coins gold
+        .migrate_persisted::<PlayerStats>(0, |fields| fields.rename("gold", "coins"))
+        .add_systems(Startup, spawn_player)
+        .add_systems(Update, play)
+        .run()
+}
+
+fn spawn_player(mut commands: Commands<'_, '_>) {
+    commands.spawn((WhereWasI::from_name("player"), PlayerStats::default()));
+}
+
+/// Play a short session: walk on, collect some coins and gain experience, then quit
+fn play(
+    first_run: Res<WhereWasIFirstRun>,
+    player: Single<(&mut Transform, &mut PlayerStats)>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let (mut transform, mut stats) = player.into_inner();
+    if first_run.0 {
+        println!("Starting a new game as {}", stats.name);
+    } else {
+        println!(
+            "Welcome back, {}! Level {} with {} coins and {} health at {}",
+            stats.name, stats.level, stats.coins, stats.health, transform.translation
+        );
+    }
+
+    transform.translation.x += 10.0;
+    stats.coins += 3;
+    stats.health -= 5.0;
+    stats.invulnerable = false;
+    stats.experience += 40;
+    if stats.experience >= 100 * u64::from(stats.level) {
+        stats.experience = 0;
+        stats.level += 1;
+        stats.health = 100.0;
+        println!("Reached level {}", stats.level);
+    }
+
+    println!("Saving {:?}", *stats);
+    exit.write(AppExit::Success);
+}
@@ -0,0 +1,163 @@
+use std::env;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::window::WindowClosing;
+use bevy_where_was_i::{
+    SaveTrigger, SaveWhereWasI, WhereWasI, WhereWasILoadCompleted, WhereWasIPlugin,
+    WhereWasISaveCompleted,
+};
+
+/// The number of tracked cubes, unless another number is passed as an argument
+#[derive(Resource)]
+struct Cubes(usize);
+
+/// Example with thousands of tracked cubes that orbit around the center of the scene.
+///
+/// The cubes are autosaved every few seconds on a background task, with the savefiles spread over
+/// several concurrent batches, and continue from where they were when the example is restarted.
+/// The time every save and restore took is printed.
+///
+/// `cargo run --release --example stress -- 10000`
+///
+/// Pass `--headless` to save and restore the cubes without a window, print how long that took and
+/// exit. Run it twice, the second run also restores what the first one saved:
+/// `cargo run --release --example stress -- 10000 --headless`
+fn main() -> AppExit {
+    let cubes = env::args()
+        .skip(1)
+        .find_map(|argument| argument.parse().ok())
+        .unwrap_or(10_000);
+    let headless = env::args().any(|argument| argument == "--headless");
+
+    let mut app = App::new();
+    app.add_plugins(WhereWasIPlugin {
+        autosave: (!headless).then_some(Duration::from_secs(5)),
+        save_parallelism: 4,
+        ..WhereWasIPlugin::from_directory("./assets/saves/stress")
+    })
+    .insert_resource(Cubes(cubes))
+    .add_systems(Update, report_save);
+
+    if headless {
+        app.add_plugins(MinimalPlugins)
+            .add_message::<WindowClosing>()
+            .add_systems(Startup, spawn_entities)
+            .add_systems(Update, (move_and_save, exit_after_save));
+    } else {
+        app.add_plugins(DefaultPlugins)
+            .add_systems(Startup, (setup_camera, spawn_cubes))
+            .add_systems(Update, (orbit_cubes, report_load));
+    }
+
+    app.run()
+}
+
+/// The position of the cube with this index in a square grid around the center of the scene
+fn grid_position(index: usize, cubes: usize) -> Vec3 {
+    let side = (cubes as f32).sqrt().ceil() as usize;
+    let offset = (side as f32 - 1.0) / 2.0;
+    Vec3::new(
+        (index % side) as f32 - offset,
+        0.0,
+        (index / side) as f32 - offset,
+    ) * 1.5
+}
+
+fn setup_camera(mut commands: Commands<'_, '_>, cubes: Res<Cubes>) {
+    let distance = (cubes.0 as f32).sqrt() * 1.5;
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, distance, distance).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(1.0, 2.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+fn spawn_cubes(
+    mut commands: Commands<'_, '_>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cubes: Res<Cubes>,
+) {
+    let mesh = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
+    let material = materials.add(Color::srgb_u8(124, 144, 255));
+
+    for index in 0..cubes.0 {
+        commands.spawn((
+            WhereWasI::from_name(format!("cube_{index}")),
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(grid_position(index, cubes.0)),
+        ));
+    }
+}
+
+/// Move the cubes a little every frame, so they continue from where they were restored
+fn orbit_cubes(time: Res<Time>, mut cubes: Query<&mut Transform, With<WhereWasI>>) {
+    let orbit = Quat::from_rotation_y(0.1 * time.delta_secs());
+    for mut transform in &mut cubes {
+        transform.rotate_around(Vec3::ZERO, orbit);
+        transform.rotate_local_x(time.delta_secs());
+    }
+}
+
+fn spawn_entities(mut commands: Commands<'_, '_>, cubes: Res<Cubes>) {
+    for index in 0..cubes.0 {
+        commands.spawn((
+            WhereWasI::from_name(format!("cube_{index}")),
+            Transform::from_translation(grid_position(index, cubes.0)),
+        ));
+    }
+}
+
+fn report_load(mut loaded: MessageReader<WhereWasILoadCompleted>) {
+    for load in loaded.read() {
+        println!(
+            "Restored {} cubes in {:?}, {} failed",
+            load.restored.len(),
+            load.duration,
+            load.failed.len()
+        );
+    }
+}
+
+/// Move every cube once they were restored and save them all
+fn move_and_save(
+    loaded: MessageReader<WhereWasILoadCompleted>,
+    mut requests: MessageWriter<SaveWhereWasI>,
+    mut transforms: Query<&mut Transform, With<WhereWasI>>,
+) {
+    if loaded.is_empty() {
+        return;
+    }
+    report_load(loaded);
+
+    for mut transform in &mut transforms {
+        transform.translation.y += 1.0;
+    }
+    requests.write(SaveWhereWasI {
+        trigger: SaveTrigger::Manual,
+        window: None,
+    });
+}
+
+fn report_save(mut saved: MessageReader<WhereWasISaveCompleted>) {
+    for save in saved.read() {
+        println!(
+            "Saved {} cubes ({} bytes) in {:?}, {} failed",
+            save.saved.len(),
+            save.bytes,
+            save.duration,
+            save.failed.len()
+        );
+    }
+}
+
+fn exit_after_save(saved: MessageReader<WhereWasISaveCompleted>, mut exit: MessageWriter<AppExit>) {
+    if !saved.is_empty() {
+        exit.write(AppExit::Success);
+    }
+}
@@ -0,0 +1,347 @@
+use std::marker::PhantomData;
+
+use crate::prelude::*;
+use bevy_ecs::component::Mutable;
+use bevy_ecs::query::QueryFilter;
+
+use crate::persist::{
+    Persist, PersistMigrations, Persistable, PersistedComponent, PersistedCustom, PersistedFields,
+    PersistedResource, restore_component, restore_custom, restore_resource, save_component,
+    save_custom, save_resource,
+};
+use crate::rules::{
+    PersistedFilter, PersistedMarker, PersistedNames, track_marker, track_matching, track_named,
+};
+use crate::{
+    SchemaEntry, SchemaKind, WhereWasILoaded, WhereWasISchema, load_state, save_on_exit,
+    save_state, saving_on_exit,
+};
+
+/// Extends [`App`] with ways to hook into [`WhereWasIPlugin`](crate::WhereWasIPlugin)
+pub trait WhereWasIAppExt {
+    /// Call `sync` for every restored entity with a `C` component, right after it was restored
+    ///
+    /// Camera controllers and other components that keep their own pose would otherwise overwrite
+    /// the restored [`Transform`] with their stale state in the next frame. `sync` receives the
+    /// entity, the restored [`Transform`] and the component to reinitialize from it.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::{WhereWasIAppExt, WhereWasIPlugin};
+    ///
+    /// #[derive(Component)]
+    /// struct OrbitCamera {
+    ///     focus: Vec3,
+    ///     radius: f32,
+    /// }
+    ///
+    /// App::new()
+    ///     .add_plugins(WhereWasIPlugin::default())
+    ///     .after_restore(|_entity, transform, orbit: &mut OrbitCamera| {
+    ///         orbit.radius = transform.translation.distance(orbit.focus);
+    ///     });
+    /// ```
+    fn after_restore<C: Component<Mutability = Mutable>>(
+        &mut self,
+        sync: fn(Entity, &Transform, &mut C),
+    ) -> &mut Self;
+
+    /// Track every entity whose [`Name`] is `name` as if it had `WhereWasI::from_name(name)`
+    ///
+    /// This configures persistence from the setup code of the application, for entities that are
+    /// spawned by code that can't be changed, such as a third-party crate. Entities that already
+    /// have a [`WhereWasI`](crate::WhereWasI) component keep it.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::{WhereWasIAppExt, WhereWasIPlugin};
+    ///
+    /// App::new()
+    ///     .add_plugins(WhereWasIPlugin::default())
+    ///     .persist_transform("camera");
+    /// ```
+    fn persist_transform(&mut self, name: impl Into<String>) -> &mut Self;
+
+    /// Track every entity with the marker component `M` as if it had `WhereWasI::from_name(name)`
+    ///
+    /// This spares threading [`WhereWasI`](crate::WhereWasI) through every spawn site of an
+    /// existing marker. Registering the same marker again replaces its name.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::{WhereWasIAppExt, WhereWasIPlugin};
+    ///
+    /// #[derive(Component)]
+    /// struct MainCamera;
+    ///
+    /// App::new()
+    ///     .add_plugins(WhereWasIPlugin::default())
+    ///     .persist_marker::<MainCamera>("camera");
+    /// ```
+    fn persist_marker<M: Component>(&mut self, name: impl Into<String>) -> &mut Self;
+
+    /// Track every entity that matches the query filter `F` and has a [`Name`], under that name
+    ///
+    /// This opts a whole category of entities into persistence at once, for example all lights in
+    /// a large scene. Every matching entity needs a unique [`Name`], see
+    /// [`WhereWasIPlugin::name_collisions`](crate::WhereWasIPlugin::name_collisions).
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::{WhereWasIAppExt, WhereWasIPlugin};
+    ///
+    /// #[derive(Component)]
+    /// struct Lamp;
+    ///
+    /// App::new()
+    ///     .add_plugins(WhereWasIPlugin::default())
+    ///     .persist_matching::<With<Lamp>>();
+    /// ```
+    fn persist_matching<F: QueryFilter + 'static>(&mut self) -> &mut Self;
+
+    /// Save the resource `R` as `name` whenever all entities are saved, and restore it at startup
+    ///
+    /// The resource has to exist at startup to be restored. Registering the same resource again
+    /// replaces its name.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::{Persist, WhereWasIAppExt, WhereWasIPlugin};
+    /// # use bevy_where_was_i::{PersistedFields, WhereWasIParseError};
+    ///
+    /// #[derive(Resource, Default)]
+    /// struct Workspace {
+    ///     zoom: f32,
+    /// }
+    /// # impl Persist for Workspace {
+    /// #     fn write_fields(&self, fields: &mut PersistedFields) {}
+    /// #     fn read_fields(&mut self, fields: &PersistedFields) -> Result<(), WhereWasIParseError> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    ///
+    /// App::new()
+    ///     .add_plugins(WhereWasIPlugin::default())
+    ///     .init_resource::<Workspace>()
+    ///     .persist_resource::<Workspace>("workspace");
+    /// ```
+    fn persist_resource<R: Resource<Mutability = Mutable> + Persist>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self;
+
+    /// Save the component `C` of every tracked entity whenever all entities are saved, and restore
+    /// it when it is added to a tracked entity
+    ///
+    /// The component is saved as `<entity>.<name>`, where `<entity>` is the name the entity is
+    /// saved under. Registering the same component again replaces its name.
+    fn persist_component<C: Component<Mutability = Mutable> + Persist>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self;
+
+    /// Save the resource `R` as `name` with its own [`Persistable`] implementation whenever all
+    /// entities are saved, and insert it from the savefile at startup
+    ///
+    /// The savefile is written to the save directory of the plugin and failures result in a
+    /// [`WhereWasISaveFailed`](crate::WhereWasISaveFailed) message, as for transforms.
+    /// Registering the same resource again replaces its name.
+    fn persist_custom<R: Resource + Persistable>(&mut self, name: impl Into<String>) -> &mut Self;
+
+    /// Migrate the fields of `T` saved by version `from` of the type to version `from + 1`
+    ///
+    /// When a file written by an older [`Persist::VERSION`] is read, the migrations from its
+    /// version up to the current one run in order before the fields are read. Versions without a
+    /// migration are read as they are, so only changes that would otherwise lose data, such as a
+    /// renamed field, need one.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::{Persist, PersistedFields, WhereWasIAppExt, WhereWasIPlugin};
+    /// # use bevy_where_was_i::WhereWasIParseError;
+    ///
+    /// #[derive(Resource, Default)]
+    /// struct Workspace {
+    ///     zoom: f32,
+    /// }
+    /// # impl Persist for Workspace {
+    /// #     const VERSION: u32 = 1;
+    /// #     fn write_fields(&self, fields: &mut PersistedFields) {}
+    /// #     fn read_fields(&mut self, fields: &PersistedFields) -> Result<(), WhereWasIParseError> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    ///
+    /// App::new()
+    ///     .add_plugins(WhereWasIPlugin::default())
+    ///     .init_resource::<Workspace>()
+    ///     .persist_resource::<Workspace>("workspace")
+    ///     .migrate_persisted::<Workspace>(0, |fields| fields.rename("scale", "zoom"));
+    /// ```
+    fn migrate_persisted<T: Persist + 'static>(
+        &mut self,
+        from: u32,
+        migration: fn(&mut PersistedFields),
+    ) -> &mut Self;
+}
+
+impl WhereWasIAppExt for App {
+    fn after_restore<C: Component<Mutability = Mutable>>(
+        &mut self,
+        sync: fn(Entity, &Transform, &mut C),
+    ) -> &mut Self {
+        // Entities restored in `PostStartup` are picked up in the `PreUpdate` of the same frame
+        let system = move |mut loaded: MessageReader<WhereWasILoaded>,
+                           mut synced: Query<&mut C>| {
+            for loaded in loaded.read() {
+                if let Ok(mut component) = synced.get_mut(loaded.entity) {
+                    sync(loaded.entity, &loaded.restored, &mut component);
+                }
+            }
+        };
+        self.add_systems(PreUpdate, system.after(load_state))
+    }
+
+    fn persist_transform(&mut self, name: impl Into<String>) -> &mut Self {
+        if !self.world().contains_resource::<PersistedNames>() {
+            self.init_resource::<PersistedNames>()
+                .add_systems(PostStartup, track_named.before(load_state))
+                .add_systems(PreUpdate, track_named.before(load_state));
+        }
+        self.world_mut()
+            .resource_mut::<PersistedNames>()
+            .0
+            .insert(name.into());
+        self
+    }
+
+    fn persist_marker<M: Component>(&mut self, name: impl Into<String>) -> &mut Self {
+        if !self.world().contains_resource::<PersistedMarker<M>>() {
+            self.add_systems(PostStartup, track_marker::<M>.before(load_state))
+                .add_systems(PreUpdate, track_marker::<M>.before(load_state));
+        }
+        self.insert_resource(PersistedMarker::<M>::new(name.into()))
+    }
+
+    fn persist_matching<F: QueryFilter + 'static>(&mut self) -> &mut Self {
+        if self.world().contains_resource::<PersistedFilter<F>>() {
+            return self;
+        }
+        self.insert_resource(PersistedFilter::<F>(PhantomData))
+            .add_systems(PostStartup, track_matching::<F>.before(load_state))
+            .add_systems(PreUpdate, track_matching::<F>.before(load_state))
+    }
+
+    fn persist_resource<R: Resource<Mutability = Mutable> + Persist>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self {
+        if !self.world().contains_resource::<PersistedResource<R>>() {
+            self.add_systems(PostStartup, restore_resource::<R>)
+                .add_systems(Update, save_resource::<R>.after(save_state))
+                .add_systems(
+                    Last,
+                    save_resource::<R>
+                        .after(save_on_exit)
+                        .run_if(saving_on_exit),
+                );
+        }
+        let name = name.into();
+        register_schema::<R>(self, SchemaKind::Resource, &name, Some(R::VERSION));
+        self.insert_resource(PersistedResource::<R>::new(name))
+    }
+
+    fn persist_component<C: Component<Mutability = Mutable> + Persist>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> &mut Self {
+        if !self.world().contains_resource::<PersistedComponent<C>>() {
+            self.add_systems(PreUpdate, restore_component::<C>.after(load_state))
+                .add_systems(Update, save_component::<C>.after(save_state))
+                .add_systems(
+                    Last,
+                    save_component::<C>
+                        .after(save_on_exit)
+                        .run_if(saving_on_exit),
+                );
+        }
+        let name = name.into();
+        register_schema::<C>(self, SchemaKind::Component, &name, Some(C::VERSION));
+        self.insert_resource(PersistedComponent::<C>::new(name))
+    }
+
+    fn persist_custom<R: Resource + Persistable>(&mut self, name: impl Into<String>) -> &mut Self {
+        if !self.world().contains_resource::<PersistedCustom<R>>() {
+            self.add_systems(PostStartup, restore_custom::<R>)
+                .add_systems(Update, save_custom::<R>.after(save_state))
+                .add_systems(
+                    Last,
+                    save_custom::<R>.after(save_on_exit).run_if(saving_on_exit),
+                );
+        }
+        let name = name.into();
+        register_schema::<R>(self, SchemaKind::Custom, &name, None);
+        self.insert_resource(PersistedCustom::<R>::new(name))
+    }
+
+    fn migrate_persisted<T: Persist + 'static>(
+        &mut self,
+        from: u32,
+        migration: fn(&mut PersistedFields),
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<PersistMigrations<T>>()
+            .migrations
+            .insert(from, migration);
+        self
+    }
+}
+
+/// Add the type `T` to the [`WhereWasISchema`] of `app`
+fn register_schema<T>(app: &mut App, kind: SchemaKind, name: &str, version: Option<u32>) {
+    app.world_mut()
+        .get_resource_or_init::<WhereWasISchema>()
+        .register(SchemaEntry {
+            kind,
+            name: name.into(),
+            version,
+            type_name: std::any::type_name::<T>().into(),
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{WhereWasI, read_saves};
+
+    #[derive(Component, Default)]
+    struct Orbit {
+        radius: f32,
+        synced: usize,
+    }
+
+    #[test]
+    fn test_after_restore() {
+        let mut app = test_app(test_config("assets/tests"));
+        app.add_systems(PreStartup, read_saves)
+            .add_systems(PostStartup, load_state)
+            .add_systems(PreUpdate, load_state)
+            .after_restore(|_, transform, orbit: &mut Orbit| {
+                orbit.radius = transform.translation.length();
+                orbit.synced += 1;
+            });
+
+        let camera = app
+            .world_mut()
+            .spawn((WhereWasI::camera(), Orbit::default()))
+            .id();
+        app.update();
+        app.update();
+
+        let orbit = app.world().get::<Orbit>(camera).unwrap();
+        let transform = app.world().get::<Transform>(camera).unwrap();
+        assert_eq!(orbit.radius, transform.translation.length());
+        assert_eq!(orbit.synced, 1);
+    }
+}
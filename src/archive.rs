@@ -0,0 +1,168 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::state_path;
+use crate::timestamp::{format_timestamp, parse_timestamp};
+
+/// Keeps a timestamped copy of every savefile in an `archive` subdirectory before it is overwritten
+///
+/// Archived copies are named after the time they were originally saved, for example
+/// `archive/camera.20250131T235959Z.state`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArchivePolicy {
+    /// The maximum number of archived copies per savefile, the oldest are deleted first
+    pub max_count: Option<usize>,
+    /// Archived copies older than this are deleted
+    pub max_age: Option<Duration>,
+}
+
+/// Copy the current savefile for `name` into the archive and prune it according to `policy`
+pub(crate) fn archive_state(
+    directory: &Path,
+    name: &str,
+    policy: &ArchivePolicy,
+) -> io::Result<()> {
+    let path = state_path(directory, name)?;
+    let Ok(metadata) = fs::metadata(&path) else {
+        // Nothing to archive yet
+        return Ok(());
+    };
+
+    let saved_at = metadata.modified().unwrap_or_else(|_| SystemTime::now());
+    let archive = directory.join("archive");
+    fs::create_dir_all(&archive)?;
+    fs::copy(
+        &path,
+        archive.join(format!("{name}.{}.state", format_timestamp(saved_at))),
+    )?;
+
+    prune_archive(&archive, name, policy)
+}
+
+/// The archived copies of the savefile for `name`, sorted from oldest to newest
+fn archived(archive: &Path, name: &str) -> io::Result<Vec<(SystemTime, PathBuf)>> {
+    let mut archived = Vec::new();
+
+    for entry in fs::read_dir(archive)?.flatten() {
+        let file_name = entry.file_name();
+        let Some((archived_name, timestamp)) = file_name
+            .to_str()
+            .and_then(|file_name| file_name.strip_suffix(".state"))
+            .and_then(|stem| stem.rsplit_once('.'))
+        else {
+            continue;
+        };
+
+        if archived_name == name
+            && let Some(saved_at) = parse_timestamp(timestamp)
+        {
+            archived.push((saved_at, entry.path()));
+        }
+    }
+
+    archived.sort();
+    Ok(archived)
+}
+
+/// Delete archived copies of the savefile for `name` that exceed `policy`
+fn prune_archive(archive: &Path, name: &str, policy: &ArchivePolicy) -> io::Result<()> {
+    let archived = archived(archive, name)?;
+    let excess = policy
+        .max_count
+        .map_or(0, |max_count| archived.len().saturating_sub(max_count));
+    let now = SystemTime::now();
+
+    for (index, (saved_at, path)) in archived.into_iter().enumerate() {
+        let expired = policy
+            .max_age
+            .is_some_and(|max_age| now.duration_since(saved_at).is_ok_and(|age| age > max_age));
+
+        if index < excess || expired {
+            fs::remove_file(path)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_names(archive: &Path) -> Vec<String> {
+        let mut names = fs::read_dir(archive)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_archive_state() {
+        let directory = Path::new("assets/tests/archive_state");
+        fs::create_dir_all(directory).unwrap();
+        fs::copy("assets/tests/camera.state", directory.join("camera.state")).unwrap();
+
+        archive_state(directory, "camera", &ArchivePolicy::default()).unwrap();
+        archive_state(directory, "missing", &ArchivePolicy::default()).unwrap();
+
+        let archived = archived(&directory.join("archive"), "camera").unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(
+            fs::read(&archived[0].1).unwrap(),
+            include_bytes!("../assets/tests/camera.state")
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_prune_archive() {
+        let archive = Path::new("assets/tests/prune_archive");
+        fs::create_dir_all(archive).unwrap();
+        for file_name in [
+            "camera.19700101T000000Z.state",
+            "camera.20200101T000000Z.state",
+            "camera.20250101T000000Z.state",
+            "camera.v2.19700101T000000Z.state",
+        ] {
+            fs::write(archive.join(file_name), "").unwrap();
+        }
+
+        let policy = ArchivePolicy {
+            max_count: Some(2),
+            max_age: None,
+        };
+        prune_archive(archive, "camera", &policy).unwrap();
+        assert_eq!(
+            file_names(archive),
+            vec![
+                "camera.20200101T000000Z.state",
+                "camera.20250101T000000Z.state",
+                "camera.v2.19700101T000000Z.state",
+            ]
+        );
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap();
+        let policy = ArchivePolicy {
+            max_count: None,
+            // Everything saved before 2022 is expired
+            max_age: Some(now - Duration::from_secs(1_640_995_200)),
+        };
+        prune_archive(archive, "camera", &policy).unwrap();
+        assert_eq!(
+            file_names(archive),
+            vec![
+                "camera.20250101T000000Z.state",
+                "camera.v2.19700101T000000Z.state",
+            ]
+        );
+
+        fs::remove_dir_all(archive).unwrap();
+    }
+}
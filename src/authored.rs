@@ -0,0 +1,126 @@
+use crate::prelude::*;
+use bevy_ecs::lifecycle::HookContext;
+use bevy_ecs::world::DeferredWorld;
+
+use crate::WhereWasI;
+
+/// A component with the [`Transform`] a tracked entity was authored with
+///
+/// It is recorded as soon as the [`WhereWasI`] component is added, before a savefile is restored,
+/// from the pose passed to [`WhereWasI::or_default`] or from the [`Transform`] of the entity
+/// otherwise. [`ResetWhereWasI`](crate::ResetWhereWasI) reverts to it and
+/// [`TransformDiff`](crate::TransformDiff) reports it.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasI, WhereWasIAuthored};
+///
+/// fn moved_since_spawn(cameras: Query<(&Transform, &WhereWasIAuthored), With<WhereWasI>>) -> bool {
+///     cameras
+///         .iter()
+///         .any(|(transform, authored)| transform.translation != authored.0.translation)
+/// }
+/// ```
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+pub struct WhereWasIAuthored(pub Transform);
+
+/// What happens to the [`Transform`] of an entity that isn't restored from a savefile, see
+/// [`WhereWasIPlugin::restore_policy`](crate::WhereWasIPlugin::restore_policy)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RestorePolicy {
+    /// Keep its current [`Transform`], or apply the pose passed to [`WhereWasI::or_default`]
+    #[default]
+    KeepCurrent,
+    /// Revert it to its [`WhereWasIAuthored`] transform, in case it moved since [`WhereWasI`] was
+    /// added
+    PreferAuthoredIfNoSave,
+}
+
+/// Record the [`WhereWasIAuthored`] transform of an entity when [`WhereWasI`] is added to it
+pub(crate) fn record_authored(mut world: DeferredWorld, context: HookContext) {
+    let Some(where_was_i) = world.get::<WhereWasI>(context.entity) else {
+        return;
+    };
+    let Some(authored) = where_was_i
+        .default
+        .or_else(|| world.get::<Transform>(context.entity).copied())
+    else {
+        return;
+    };
+    world
+        .commands()
+        .entity(context.entity)
+        .insert(WhereWasIAuthored(authored));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_state;
+    use crate::tests::{test_app, test_config};
+    use crate::{ResetWhereWasI, WhereWasIConfig};
+
+    fn moved_before_load(restore_policy: RestorePolicy) -> Transform {
+        let mut app = test_app(WhereWasIConfig {
+            restore_policy,
+            ..test_config("assets/tests/authored")
+        });
+        app.add_systems(PreUpdate, load_state);
+        let entity = app
+            .world_mut()
+            .spawn((WhereWasI::camera(), Transform::from_xyz(1.0, 2.0, 3.0)))
+            .id();
+        app.world_mut().flush();
+        assert_eq!(
+            app.world().get::<WhereWasIAuthored>(entity),
+            Some(&WhereWasIAuthored(Transform::from_xyz(1.0, 2.0, 3.0)))
+        );
+
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation
+            .x = 5.0;
+        app.update();
+        *app.world().get::<Transform>(entity).unwrap()
+    }
+
+    #[test]
+    fn test_restore_policy() {
+        assert_eq!(
+            moved_before_load(RestorePolicy::KeepCurrent),
+            Transform::from_xyz(5.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            moved_before_load(RestorePolicy::PreferAuthoredIfNoSave),
+            Transform::from_xyz(1.0, 2.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn test_reset_to_authored() {
+        let mut app = test_app(test_config("assets/tests/reset_to_authored"));
+        app.add_systems(Update, crate::reset_state);
+        let entity = app
+            .world_mut()
+            .spawn((
+                WhereWasI::camera().or_default(Transform::from_xyz(0.0, 1.0, 0.0)),
+                Transform::from_xyz(4.0, 4.0, 4.0),
+            ))
+            .id();
+        app.world_mut().flush();
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation
+            .y = 8.0;
+
+        app.world_mut().write_message(ResetWhereWasI { entity });
+        app.update();
+        assert_eq!(
+            *app.world().get::<Transform>(entity).unwrap(),
+            Transform::from_xyz(0.0, 1.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,497 @@
+use std::collections::HashMap;
+use std::io;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_platform::time::Instant;
+use bevy_tasks::futures::check_ready;
+use bevy_tasks::{AsyncComputeTaskPool, IoTaskPool, Task, TaskPool};
+
+use crate::diagnostics::{SaveMeasurement, SaveMetrics};
+use crate::logging::log_summary;
+use crate::names::resolve_names;
+use crate::session::write_session;
+use crate::throttle::{RequestedSave, SaveThrottle};
+use crate::{
+    SaveRecord, SaveTrigger, SaveWhereWasI, SessionMetadata, WhereWasI, WhereWasIConfig,
+    WhereWasIContentHash, WhereWasIPersisted, WhereWasISaveFailed, WhereWasISaves, write_state,
+};
+
+/// A [`Message`] with a summary of a save that has finished, both for autosaves and for the save
+/// when the window closes
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasISaveCompleted;
+///
+/// fn log_saves(mut completed: MessageReader<WhereWasISaveCompleted>) {
+///     for save in completed.read() {
+///         info!("Saved {} entities", save.saved.len());
+///     }
+/// }
+/// ```
+#[derive(Message, Clone, Debug)]
+pub struct WhereWasISaveCompleted {
+    /// What caused the save
+    pub trigger: SaveTrigger,
+    /// The names of the entities whose savefile was written
+    pub saved: Vec<String>,
+    /// The names of the entities that could not be saved, each of them is also reported with a
+    /// [`WhereWasISaveFailed`] message
+    pub failed: Vec<String>,
+    /// The number of bytes written
+    pub bytes: u64,
+    /// How long the save took
+    pub duration: Duration,
+}
+
+/// The outcome of writing the savefile of one entity
+pub(crate) struct SaveOutcome {
+    entity: Entity,
+    name: String,
+    transform: Transform,
+    result: io::Result<u64>,
+}
+
+/// An entity to save, with its resolved name and current [`Transform`]
+#[derive(Clone, Debug)]
+pub(crate) struct SaveEntry {
+    pub(crate) entity: Entity,
+    pub(crate) name: String,
+    pub(crate) transform: Transform,
+    /// The [`WhereWasIGroup`](crate::WhereWasIGroup) of the entity, which decides where the
+    /// savefile is written
+    pub(crate) group: Option<String>,
+}
+
+/// The entities to save
+///
+/// With [`WhereWasIConfig::skip_unchanged`], entities whose [`Transform`] equals the record in
+/// `saves` are left out.
+pub(crate) fn save_entries<'a>(
+    tracked: impl Iterator<Item = (Entity, &'a WhereWasI, &'a Transform)>,
+    config: &WhereWasIConfig,
+    saves: &WhereWasISaves,
+) -> Vec<SaveEntry> {
+    let tracked = tracked.collect::<Vec<_>>();
+    let by_entity: HashMap<Entity, (&WhereWasI, Transform)> = tracked
+        .iter()
+        .map(|(entity, where_was_i, transform)| (*entity, (*where_was_i, **transform)))
+        .collect();
+
+    resolve_names(
+        tracked
+            .iter()
+            .filter(|(_, where_was_i, _)| !where_was_i.follows)
+            .map(|(entity, where_was_i, _)| (*entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    )
+    .into_iter()
+    .filter_map(|(entity, name)| {
+        let (where_was_i, transform) = by_entity.get(&entity)?;
+        Some(SaveEntry {
+            entity,
+            name,
+            transform: *transform,
+            group: where_was_i.group.clone(),
+        })
+    })
+    .filter(|entry| {
+        let unchanged = config.skip_unchanged
+            && saves
+                .get(&entry.name)
+                .is_some_and(|record| record.transform == entry.transform);
+        if unchanged {
+            debug!("Not saving {}, it hasn't changed", entry.name);
+        }
+        !unchanged
+    })
+    .collect()
+}
+
+/// Serialize and write the savefiles of `entries`, in the directory and format of their group
+///
+/// This only touches the disk, so it can run on another thread. With
+/// [`WhereWasIConfig::save_parallelism`] larger than one, the entries are split into that many
+/// batches that are written concurrently on the [`IoTaskPool`]. The outcomes are in the same order
+/// as `entries` either way.
+pub(crate) fn write_states(config: &WhereWasIConfig, entries: Vec<SaveEntry>) -> Vec<SaveOutcome> {
+    let write = |entry: SaveEntry| SaveOutcome {
+        result: write_state(
+            config.group(entry.group.as_deref()),
+            &entry.name,
+            &entry.transform,
+        ),
+        entity: entry.entity,
+        name: entry.name,
+        transform: entry.transform,
+    };
+
+    if config.save_parallelism <= 1 || entries.len() <= 1 {
+        return entries.into_iter().map(write).collect();
+    }
+
+    let batch_size = entries.len().div_ceil(config.save_parallelism);
+    let mut batches = Vec::new();
+    let mut entries = entries.into_iter().peekable();
+    while entries.peek().is_some() {
+        batches.push(entries.by_ref().take(batch_size).collect::<Vec<_>>());
+    }
+
+    IoTaskPool::get_or_init(TaskPool::default)
+        .scope(|scope| {
+            for batch in batches {
+                scope.spawn(async move { batch.into_iter().map(write).collect::<Vec<_>>() });
+            }
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Everything that is updated after a save has finished
+#[derive(SystemParam)]
+pub(crate) struct SaveSink<'w, 's> {
+    commands: Commands<'w, 's>,
+    config: Res<'w, WhereWasIConfig>,
+    saves: ResMut<'w, WhereWasISaves>,
+    metrics: ResMut<'w, SaveMetrics>,
+    failures: MessageWriter<'w, WhereWasISaveFailed>,
+    completed: MessageWriter<'w, WhereWasISaveCompleted>,
+    throttle: ResMut<'w, SaveThrottle>,
+    content_hash: Option<Res<'w, WhereWasIContentHash>>,
+}
+
+impl SaveSink<'_, '_> {
+    /// The configuration of the plugin
+    pub(crate) fn config(&self) -> &WhereWasIConfig {
+        &self.config
+    }
+
+    /// The records of the savefiles that were last saved or loaded
+    pub(crate) fn saves(&self) -> &WhereWasISaves {
+        &self.saves
+    }
+
+    /// The save to start now, combining `requested` with a save that was postponed
+    /// by the [`SaveRateLimit`](crate::SaveRateLimit)
+    pub(crate) fn admit(&mut self, requested: Option<RequestedSave>) -> Option<RequestedSave> {
+        self.throttle
+            .admit(requested, self.config.rate_limit.as_ref(), Instant::now())
+    }
+
+    /// Whether a save right now stays within the [`SaveRateLimit`](crate::SaveRateLimit)
+    pub(crate) fn within_rate_limit(&mut self) -> bool {
+        self.config
+            .rate_limit
+            .as_ref()
+            .is_none_or(|limit| self.throttle.allows(limit, Instant::now()))
+    }
+
+    /// Record the `outcomes` of a save caused by `trigger` that took `duration`
+    ///
+    /// Returns the entities whose savefile was written, with the [`Transform`] that was saved.
+    pub(crate) fn finish(
+        &mut self,
+        outcomes: Vec<SaveOutcome>,
+        duration: Duration,
+        trigger: SaveTrigger,
+    ) -> Vec<(Entity, Transform)> {
+        let mut written = Vec::new();
+        let mut saved = Vec::new();
+        let mut failed = Vec::new();
+        let mut bytes = 0;
+
+        for outcome in outcomes {
+            match outcome.result {
+                Ok(size) => {
+                    bytes += size;
+                    if self.config.dry_run {
+                        continue;
+                    }
+                    self.saves.insert(
+                        &outcome.name,
+                        SaveRecord {
+                            transform: outcome.transform,
+                            modified: Some(SystemTime::now()),
+                            trigger: Some(trigger),
+                        },
+                    );
+                    self.commands.trigger(WhereWasIPersisted {
+                        entity: outcome.entity,
+                        name: outcome.name.clone(),
+                        transform: outcome.transform,
+                    });
+                    written.push((outcome.entity, outcome.transform));
+                    saved.push(outcome.name);
+                }
+                Err(error) => {
+                    error!("Could not save {}: {}", outcome.name, error);
+                    failed.push(outcome.name.clone());
+                    self.failures.write(WhereWasISaveFailed {
+                        name: outcome.name,
+                        error,
+                    });
+                }
+            }
+        }
+
+        if !self.config.dry_run {
+            log_summary!(
+                self.config.summary_log_level,
+                names = ?saved,
+                directory = %self.config.directory.display(),
+                duration = ?duration,
+                "Saved {} transforms to: {}",
+                saved.len(),
+                self.config.directory.display()
+            );
+        }
+        self.throttle.record(Instant::now(), saved.len(), bytes);
+        self.metrics.last = Some(SaveMeasurement {
+            bytes,
+            duration,
+            saved_at: SystemTime::now(),
+        });
+
+        if !saved.is_empty() {
+            let metadata = SessionMetadata {
+                saved_at: SystemTime::now(),
+                entities: saved.len(),
+                app_version: self.config.app_version.clone(),
+                trigger: Some(trigger),
+                triggers: self.saves.triggers(),
+                saved: self.saves.saved_at(),
+                content_hash: self.content_hash.as_ref().map(|hash| hash.0.clone()),
+            };
+            if let Err(err) = write_session(&self.config.directory, &metadata) {
+                error!("Could not write session metadata: {}", err);
+            }
+        }
+
+        self.completed.write(WhereWasISaveCompleted {
+            trigger,
+            saved,
+            failed,
+            bytes,
+            duration,
+        });
+        written
+    }
+}
+
+/// The state of periodic saves, see [`WhereWasIPlugin::autosave`](crate::WhereWasIPlugin::autosave)
+#[derive(Resource)]
+pub(crate) struct Autosave {
+    timer: Timer,
+    task: Option<Task<(Vec<SaveOutcome>, Duration)>>,
+}
+
+impl Autosave {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval, TimerMode::Repeating),
+            task: None,
+        }
+    }
+
+    /// The time until the next autosave starts
+    #[cfg(feature = "debug-ui")]
+    pub(crate) fn remaining(&self) -> Duration {
+        self.timer.remaining()
+    }
+
+    /// Start writing `entries` on the [`AsyncComputeTaskPool`]
+    ///
+    /// Nothing happens while the previous autosave is still running.
+    pub(crate) fn start(&mut self, config: &WhereWasIConfig, entries: Vec<SaveEntry>) {
+        if self.task.is_some() {
+            debug!("Skipping autosave, the previous one is still running");
+            return;
+        }
+
+        let config = config.clone();
+        let started = Instant::now();
+        let task = AsyncComputeTaskPool::get_or_init(TaskPool::default).spawn(async move {
+            let outcomes = write_states(&config, entries);
+            (outcomes, started.elapsed())
+        });
+        self.task = Some(task);
+    }
+
+    /// Block until the autosave in flight, if any, has finished and return its outcomes and how
+    /// long it took
+    ///
+    /// Used before any other save, so an autosave can't overwrite it afterwards.
+    pub(crate) fn flush(&mut self) -> Option<(Vec<SaveOutcome>, Duration)> {
+        self.task.take().map(bevy_tasks::block_on)
+    }
+}
+
+/// Request an autosave when the timer finishes
+pub(crate) fn tick_autosave(
+    time: Res<Time>,
+    mut autosave: ResMut<Autosave>,
+    mut requests: MessageWriter<SaveWhereWasI>,
+) {
+    if autosave.timer.tick(time.delta()).just_finished() {
+        requests.write(SaveWhereWasI {
+            trigger: SaveTrigger::Autosave,
+            window: None,
+        });
+    }
+}
+
+/// Record the outcome of the autosave in flight once it has finished
+pub(crate) fn finish_autosave(mut autosave: ResMut<Autosave>, mut sink: SaveSink) {
+    let Some(task) = &mut autosave.task else {
+        return;
+    };
+    let Some((outcomes, duration)) = check_ready(task) else {
+        return;
+    };
+
+    autosave.task = None;
+    sink.finish(outcomes, duration, SaveTrigger::Autosave);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{read_state, save_state};
+
+    #[test]
+    fn test_write_states_in_parallel() {
+        let config = WhereWasIConfig {
+            save_parallelism: 3,
+            ..test_config("assets/tests/parallel_save")
+        };
+        let entries = (0..10)
+            .map(|index| SaveEntry {
+                entity: Entity::PLACEHOLDER,
+                name: format!("entity_{index}"),
+                transform: Transform::from_xyz(index as f32, 0.0, 0.0),
+                group: None,
+            })
+            .collect::<Vec<_>>();
+
+        let outcomes = write_states(&config, entries.clone());
+
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|outcome| outcome.name.clone())
+                .collect::<Vec<_>>(),
+            entries
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect::<Vec<_>>()
+        );
+        for entry in entries {
+            assert_eq!(
+                read_state(format!("assets/tests/parallel_save/{}.state", entry.name)).unwrap(),
+                entry.transform
+            );
+        }
+
+        fs::remove_dir_all("assets/tests/parallel_save").unwrap();
+    }
+
+    #[test]
+    fn test_autosave() {
+        let mut app = test_app(test_config("assets/tests/autosave"));
+        app.init_resource::<Time>()
+            .insert_resource(Autosave::new(Duration::from_secs(30)))
+            .add_systems(Update, (tick_autosave, save_state, finish_autosave).chain());
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0);
+        app.world_mut().spawn((WhereWasI::camera(), transform));
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(10));
+        app.update();
+        assert!(!fs::exists("assets/tests/autosave/camera.state").unwrap());
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(20));
+        app.update();
+        // The task may finish on another thread, so keep polling without starting a new one
+        while app.world().resource::<Autosave>().task.is_some() {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::ZERO);
+            app.update();
+        }
+
+        assert_eq!(
+            read_state("assets/tests/autosave/camera.state").unwrap(),
+            transform
+        );
+        assert!(app.world().resource::<WhereWasISaves>().contains("camera"));
+        let completed = app
+            .world()
+            .resource::<Messages<WhereWasISaveCompleted>>()
+            .iter_current_update_messages()
+            .map(|save| (save.trigger, save.saved.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            completed,
+            vec![(SaveTrigger::Autosave, vec!["camera".to_string()])]
+        );
+
+        fs::remove_dir_all("assets/tests/autosave").unwrap();
+    }
+
+    #[test]
+    fn test_save_after_autosave() {
+        let config = test_config("assets/tests/save_after_autosave");
+        let mut app = test_app(config.clone());
+        app.insert_resource(Autosave::new(Duration::from_secs(30)))
+            .add_systems(Update, save_state);
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0);
+        let entity = app.world_mut().spawn((WhereWasI::camera(), transform)).id();
+
+        app.world_mut().resource_mut::<Autosave>().start(
+            &config,
+            vec![SaveEntry {
+                entity,
+                name: "camera".into(),
+                transform,
+                group: None,
+            }],
+        );
+        app.world_mut().write_message(SaveWhereWasI::default());
+        app.update();
+
+        assert!(app.world().resource::<Autosave>().task.is_none());
+        assert_eq!(
+            app.world()
+                .resource::<WhereWasISaves>()
+                .get("camera")
+                .unwrap()
+                .trigger,
+            Some(SaveTrigger::Autosave)
+        );
+        // The camera didn't move since the autosave, so the manual save leaves it out
+        let completed = app
+            .world()
+            .resource::<Messages<WhereWasISaveCompleted>>()
+            .iter_current_update_messages()
+            .map(|save| (save.trigger, save.saved.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            completed,
+            vec![
+                (SaveTrigger::Autosave, vec!["camera".to_string()]),
+                (SaveTrigger::Manual, vec![]),
+            ]
+        );
+
+        fs::remove_dir_all("assets/tests/save_after_autosave").unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+//! Inspect and edit savefiles written by bevy-where-was-i
+//!
+//! ```text
+//! wwi show assets/saves/camera.state
+//! wwi validate assets/saves
+//! wwi set assets/saves/camera.state translation 0 5 -10
+//! wwi convert assets/saves --from text --to binary
+//! wwi schema assets/saves --output schema.manifest
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+use bevy_where_was_i::{
+    PersistedFields, SCHEMA_FILE, SaveFormat, SchemaKind, WhereWasISchema, convert, read_savefile,
+    write_savefile,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+
+#[derive(Parser)]
+#[command(name = "wwi", about = "Inspect and edit bevy-where-was-i savefiles")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the contents of savefiles in a readable form
+    Show {
+        /// Savefiles, or directories with savefiles
+        paths: Vec<PathBuf>,
+    },
+    /// Check that savefiles can be read, exits with an error if any can't
+    Validate {
+        /// Savefiles, or directories with savefiles
+        paths: Vec<PathBuf>,
+    },
+    /// Overwrite a field of a savefile
+    Set {
+        /// The savefile to edit
+        path: PathBuf,
+        /// The field to overwrite
+        field: Field,
+        /// The new values: x, y, z for translation and scale, x, y, z, w for rotation, or yaw,
+        /// pitch, roll in degrees for euler
+        #[arg(allow_negative_numbers = true)]
+        values: Vec<f32>,
+    },
+    /// Rewrite savefiles in another format
+    Convert {
+        /// A savefile, or a save directory to convert entirely
+        path: PathBuf,
+        /// The format the savefiles are in now
+        #[arg(long, default_value = "text")]
+        from: Format,
+        /// The format to convert the savefiles to
+        #[arg(long)]
+        to: Format,
+    },
+    /// Print the persisted types in the manifest of a save directory and check the versions of
+    /// their savefiles against it, exits with an error if any is newer than the manifest
+    Schema {
+        /// The save directory
+        directory: PathBuf,
+        /// Also write the manifest to this file, for example to commit it
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Binary,
+}
+
+impl From<Format> for SaveFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Text => SaveFormat::Text,
+            Format::Binary => SaveFormat::Binary,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Field {
+    Translation,
+    Rotation,
+    Euler,
+    Scale,
+}
+
+/// The savefiles in `paths`, where directories are expanded into the savefiles they contain
+fn savefiles(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut savefiles = Vec::new();
+
+    for path in paths {
+        let Ok(entries) = fs::read_dir(path) else {
+            savefiles.push(path.clone());
+            continue;
+        };
+
+        let mut found = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "state")
+            })
+            .collect::<Vec<_>>();
+        found.sort();
+        savefiles.extend(found);
+    }
+    savefiles
+}
+
+/// Format `transform` as indented lines
+fn pretty_print(transform: &Transform) -> String {
+    let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+    let [x, y, z] = transform.translation.to_array();
+    let [qx, qy, qz, qw] = transform.rotation.to_array();
+    let [sx, sy, sz] = transform.scale.to_array();
+
+    format!(
+        "  translation: {x}, {y}, {z}\n  rotation:    {qx}, {qy}, {qz}, {qw}\n  euler:       \
+         yaw {:.2}°, pitch {:.2}°, roll {:.2}°\n  scale:       {sx}, {sy}, {sz}",
+        yaw.to_degrees(),
+        pitch.to_degrees(),
+        roll.to_degrees()
+    )
+}
+
+/// Overwrite `field` of `transform` with `values`
+fn set_field(transform: &mut Transform, field: Field, values: &[f32]) -> Result<(), String> {
+    match (field, values) {
+        (Field::Translation, &[x, y, z]) => transform.translation = Vec3::new(x, y, z),
+        (Field::Scale, &[x, y, z]) => transform.scale = Vec3::new(x, y, z),
+        (Field::Rotation, &[x, y, z, w]) => {
+            transform.rotation = Quat::from_xyzw(x, y, z, w).normalize();
+        }
+        (Field::Euler, &[yaw, pitch, roll]) => {
+            transform.rotation = Quat::from_euler(
+                EulerRot::YXZ,
+                yaw.to_radians(),
+                pitch.to_radians(),
+                roll.to_radians(),
+            );
+        }
+        (Field::Rotation, _) => return Err(format!("Expected 4 values, got {}", values.len())),
+        _ => return Err(format!("Expected 3 values, got {}", values.len())),
+    }
+
+    if !transform.is_finite() {
+        return Err("Values must be finite".into());
+    }
+    Ok(())
+}
+
+/// Edit `field` of the savefile at `path`, keeping its format
+fn set(path: &Path, field: Field, values: &[f32]) -> Result<(), String> {
+    let format = SaveFormat::detect(path).map_err(|err| err.to_string())?;
+    let mut transform = read_savefile(path).map_err(|err| err.to_string())?;
+    set_field(&mut transform, field, values)?;
+    write_savefile(path, &transform, format).map_err(|err| err.to_string())
+}
+
+/// The savefiles of the persisted types in `schema` in `directory`, with the version they are
+/// expected to have
+fn persisted_files(directory: &Path, schema: &WhereWasISchema) -> Vec<(PathBuf, u32)> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(directory.join("persisted")) else {
+        return files;
+    };
+    let mut paths = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        // Components are saved as `<entity>.<name>`, resources as `<name>`
+        let expected = schema.entries().iter().find(|entry| match entry.kind {
+            SchemaKind::Resource => stem == entry.name,
+            SchemaKind::Component => stem.ends_with(&format!(".{}", entry.name)),
+            SchemaKind::Custom => false,
+        });
+        if let Some(version) = expected.and_then(|entry| entry.version) {
+            files.push((path, version));
+        }
+    }
+    files
+}
+
+/// Print the manifest in `directory` and check the savefiles of its types, returning whether all
+/// of them can be read by the application that wrote the manifest
+fn schema(directory: &Path, output: Option<&Path>) -> Result<bool, String> {
+    let schema = WhereWasISchema::read_manifest(directory.join(SCHEMA_FILE))
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("No {SCHEMA_FILE}, it is written when the application saves"))?;
+    for entry in schema.entries() {
+        println!("{entry}");
+    }
+    if let Some(output) = output {
+        schema
+            .write_manifest(output)
+            .map_err(|err| format!("{}: {}", output.display(), err))?;
+    }
+
+    let mut compatible = true;
+    for (path, expected) in persisted_files(directory, &schema) {
+        match PersistedFields::read(&path) {
+            Ok(fields) if fields.version() > expected => {
+                println!(
+                    "{}: version {} is newer than version {}",
+                    path.display(),
+                    fields.version(),
+                    expected
+                );
+                compatible = false;
+            }
+            Ok(fields) if fields.version() < expected => println!(
+                "{}: version {} will be migrated to version {}",
+                path.display(),
+                fields.version(),
+                expected
+            ),
+            Ok(_) => {}
+            Err(err) => {
+                println!("{}: {}", path.display(), err);
+                compatible = false;
+            }
+        }
+    }
+    Ok(compatible)
+}
+
+fn main() -> ExitCode {
+    let mut failed = false;
+
+    match Cli::parse().command {
+        Command::Show { paths } => {
+            for path in savefiles(&paths) {
+                match read_savefile(&path) {
+                    Ok(transform) => println!(
+                        "{} ({:?})\n{}",
+                        path.display(),
+                        SaveFormat::detect(&path).unwrap_or_default(),
+                        pretty_print(&transform)
+                    ),
+                    Err(err) => {
+                        eprintln!("{}: {}", path.display(), err);
+                        failed = true;
+                    }
+                }
+            }
+        }
+        Command::Validate { paths } => {
+            for path in savefiles(&paths) {
+                match read_savefile(&path) {
+                    Ok(_) => println!("{}: ok", path.display()),
+                    Err(err) => {
+                        println!("{}: {}", path.display(), err);
+                        failed = true;
+                    }
+                }
+            }
+        }
+        Command::Set {
+            path,
+            field,
+            values,
+        } => {
+            if let Err(err) = set(&path, field, &values) {
+                eprintln!("{}: {}", path.display(), err);
+                failed = true;
+            }
+        }
+        Command::Convert { path, from, to } => match convert(&path, from.into(), to.into()) {
+            Ok(converted) => println!("Converted {converted} savefile(s)"),
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                failed = true;
+            }
+        },
+        Command::Schema { directory, output } => match schema(&directory, output.as_deref()) {
+            Ok(compatible) => failed = !compatible,
+            Err(err) => {
+                eprintln!("{}: {}", directory.display(), err);
+                failed = true;
+            }
+        },
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_field() {
+        let mut transform = Transform::IDENTITY;
+
+        set_field(&mut transform, Field::Translation, &[1.0, 2.0, 3.0]).unwrap();
+        set_field(&mut transform, Field::Euler, &[90.0, 0.0, 0.0]).unwrap();
+
+        assert_eq!(transform.translation, Vec3::new(1.0, 2.0, 3.0));
+        assert!(
+            transform
+                .rotation
+                .abs_diff_eq(Quat::from_rotation_y(90f32.to_radians()), 1e-6)
+        );
+        assert!(set_field(&mut transform, Field::Rotation, &[1.0, 2.0, 3.0]).is_err());
+        assert!(set_field(&mut transform, Field::Scale, &[f32::NAN, 1.0, 1.0]).is_err());
+    }
+
+    #[test]
+    fn test_persisted_files() {
+        let directory = Path::new("assets/tests/wwi_schema");
+        fs::create_dir_all(directory.join("persisted")).unwrap();
+        for file in ["workspace", "camera.lens", "camera.other"] {
+            fs::write(directory.join(format!("persisted/{file}.persist")), "v0\n").unwrap();
+        }
+        let schema = WhereWasISchema::parse_manifest(
+            "v0\n\nresource workspace 2 game::Workspace\ncomponent lens 1 game::Lens\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            persisted_files(directory, &schema),
+            vec![
+                (directory.join("persisted/camera.lens.persist"), 1),
+                (directory.join("persisted/workspace.persist"), 2),
+            ]
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_savefiles() {
+        assert_eq!(
+            savefiles(&[PathBuf::from("assets/tests")])
+                .iter()
+                .filter(|path| path.ends_with("camera.state"))
+                .count(),
+            1
+        );
+    }
+}
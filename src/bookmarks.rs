@@ -0,0 +1,159 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+
+use crate::history::apply_restore;
+use crate::names::validate_name;
+use crate::validation::read_checked;
+use crate::{WhereWasIStore, state_path, write_transform};
+
+/// The directory in which the savefiles of `bookmark` are stored
+fn bookmark_directory(directory: &Path, bookmark: &str) -> io::Result<PathBuf> {
+    validate_name(bookmark).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    Ok(directory.join("bookmarks").join(bookmark))
+}
+
+impl WhereWasIStore<'_, '_> {
+    /// Save the current [`Transform`] of all tracked entities under a bookmark named `bookmark`
+    ///
+    /// Bookmarks are stored in the `bookmarks` subdirectory of the save directory, an existing
+    /// bookmark with the same name is replaced.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIStore;
+    ///
+    /// fn bookmark_overview(mut store: WhereWasIStore) {
+    ///     if let Err(err) = store.bookmark("overview") {
+    ///         error!("Could not save bookmark: {err}");
+    ///     }
+    /// }
+    /// ```
+    pub fn bookmark(&mut self, bookmark: &str) -> io::Result<()> {
+        let directory = bookmark_directory(&self.config.directory, bookmark)?;
+        if fs::exists(&directory)? {
+            fs::remove_dir_all(&directory)?;
+        }
+        fs::create_dir_all(&directory)?;
+
+        let names = self.tracked_names();
+
+        for (entity, name) in names {
+            if let Ok((_, _, transform)) = self.tracked.get(entity) {
+                write_transform(
+                    &state_path(&directory, &name)?,
+                    transform,
+                    self.config.format,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore the [`Transform`]s saved under `bookmark`
+    ///
+    /// Tracked entities that are not part of the bookmark are left untouched. The transforms are
+    /// applied when the commands of the current system are applied.
+    pub fn recall_bookmark(&mut self, bookmark: &str) -> io::Result<()> {
+        let directory = bookmark_directory(&self.config.directory, bookmark)?;
+        if !fs::exists(&directory)? {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Bookmark \"{bookmark}\" does not exist"),
+            ));
+        }
+
+        let names = self.tracked_names();
+
+        for (entity, name) in names {
+            let path = state_path(&directory, &name)?;
+            if !fs::exists(&path)? {
+                continue;
+            }
+
+            match read_checked(&path, &self.config) {
+                Ok(transform) => {
+                    self.commands
+                        .entity(entity)
+                        .queue(move |entity: EntityWorldMut| apply_restore(entity, transform));
+                }
+                Err(err) => error!(
+                    "Could not read {} from bookmark {}: {}",
+                    name, bookmark, err
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the bookmark named `bookmark`
+    ///
+    /// Returns whether the bookmark existed.
+    pub fn delete_bookmark(&mut self, bookmark: &str) -> io::Result<bool> {
+        match fs::remove_dir_all(bookmark_directory(&self.config.directory, bookmark)?) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The names of all bookmarks, sorted
+    pub fn bookmarks(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.config.directory.join("bookmarks")) else {
+            return Vec::new();
+        };
+
+        let mut bookmarks = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect::<Vec<_>>();
+        bookmarks.sort();
+        bookmarks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::WhereWasI;
+    use crate::tests::{test_app, test_config};
+
+    #[test]
+    fn test_bookmark_and_recall() {
+        let mut app = test_app(test_config("assets/tests/bookmarks"));
+        let overview = Transform::from_xyz(0.0, 50.0, 0.0);
+        let entity = app.world_mut().spawn((WhereWasI::camera(), overview)).id();
+
+        app.world_mut()
+            .run_system_once(|mut store: WhereWasIStore| {
+                store.bookmark("overview").unwrap();
+                assert_eq!(store.bookmarks(), vec!["overview"]);
+            })
+            .unwrap();
+
+        *app.world_mut().get_mut::<Transform>(entity).unwrap() = Transform::IDENTITY;
+
+        app.world_mut()
+            .run_system_once(|mut store: WhereWasIStore| {
+                store.recall_bookmark("overview").unwrap();
+                assert!(store.recall_bookmark("missing").is_err());
+            })
+            .unwrap();
+
+        assert_eq!(*app.world().get::<Transform>(entity).unwrap(), overview);
+
+        app.world_mut()
+            .run_system_once(|mut store: WhereWasIStore| {
+                assert!(store.delete_bookmark("overview").unwrap());
+                assert!(store.bookmarks().is_empty());
+            })
+            .unwrap();
+
+        fs::remove_dir_all("assets/tests/bookmarks").unwrap();
+    }
+}
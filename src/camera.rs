@@ -0,0 +1,227 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+use bevy_camera::visibility::RenderLayers;
+
+use crate::names::{resolve_names, resolve_tracked_names, validate_name};
+use crate::serialization::versioned_lines;
+use crate::{
+    Field, PersistenceUnavailable, SaveWhereWasI, WhereWasI, WhereWasIConfig, WhereWasIParseError,
+};
+
+/// The order and render layers of a camera, as stored in its file
+#[derive(Clone, Debug, PartialEq)]
+struct CameraSettings {
+    order: isize,
+    layers: Option<RenderLayers>,
+}
+
+/// The path of the settings of the camera `name` in `directory`
+fn camera_path(directory: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    Ok(directory.join("cameras").join(format!("{name}.camera")))
+}
+
+/// Write `settings` for the camera `name` in `directory`
+///
+/// The `layers:` section is left out for cameras without [`RenderLayers`].
+fn write_camera(directory: &Path, name: &str, settings: &CameraSettings) -> io::Result<()> {
+    let path = camera_path(directory, name)?;
+    fs::create_dir_all(directory.join("cameras"))?;
+
+    let mut contents = format!("v0\n\norder:\n{}\n", settings.order);
+    if let Some(layers) = &settings.layers {
+        let layers = layers.iter().map(|layer| layer.to_string());
+        contents.push_str(&format!(
+            "\nlayers:\n{}\n",
+            layers.collect::<Vec<_>>().join(" ")
+        ));
+    }
+    fs::write(path, contents)
+}
+
+/// Parse the settings of a camera from the contents of its file
+fn parse_camera(contents: &str) -> Result<CameraSettings, WhereWasIParseError> {
+    let lines = versioned_lines(contents)?;
+    let value = |key| {
+        let index = lines.iter().position(|line| *line == key)?;
+        Some((index + 2, lines.get(index + 1).copied().unwrap_or("")))
+    };
+
+    let (line, order) = value("order:").ok_or(WhereWasIParseError::MissingLine {
+        line: lines.len() + 1,
+        expected: Field::CameraOrder,
+    })?;
+    let order = order
+        .parse()
+        .map_err(|_| WhereWasIParseError::UnexpectedLine {
+            line,
+            expected: Field::CameraOrder,
+            found: order.into(),
+        })?;
+
+    let layers = value("layers:")
+        .map(|(line, layers)| {
+            layers
+                .split_whitespace()
+                .map(|layer| layer.parse())
+                .collect::<Result<RenderLayers, _>>()
+                .map_err(|_| WhereWasIParseError::UnexpectedLine {
+                    line,
+                    expected: Field::RenderLayers,
+                    found: layers.into(),
+                })
+        })
+        .transpose()?;
+
+    Ok(CameraSettings { order, layers })
+}
+
+/// Restore the order and render layers of tracked cameras
+pub(crate) fn restore_cameras(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &WhereWasI, Option<&mut Camera>)>,
+    config: Res<WhereWasIConfig>,
+) {
+    if !config.restore_on_startup {
+        return;
+    }
+
+    let names = resolve_tracked_names(
+        cameras
+            .iter()
+            .map(|(entity, where_was_i, _)| (entity, where_was_i)),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        let Ok((_, where_was_i, Some(mut camera))) = cameras.get_mut(entity) else {
+            continue;
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        let Ok(path) = camera_path(directory, &name) else {
+            continue;
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                error!("Could not read camera settings of {}: {}", name, err);
+                continue;
+            }
+        };
+        match parse_camera(&contents) {
+            Ok(settings) => {
+                camera.order = settings.order;
+                if let Some(layers) = settings.layers {
+                    commands.entity(entity).insert(layers);
+                }
+            }
+            Err(err) => error!("Could not read camera settings of {}: {}", name, err),
+        }
+    }
+}
+
+/// Save the order and render layers of tracked cameras when all entities are saved
+pub(crate) fn save_cameras(
+    mut requests: MessageReader<SaveWhereWasI>,
+    cameras: Query<(Entity, &WhereWasI, &Camera, Option<&RenderLayers>)>,
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let save_all = requests.read().any(|request| request.window.is_none());
+    if !save_all || unavailable.is_some() || config.dry_run {
+        return;
+    }
+
+    let names = resolve_names(
+        cameras
+            .iter()
+            .filter(|(_, where_was_i, ..)| !where_was_i.follows)
+            .map(|(entity, where_was_i, ..)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        let Ok((_, where_was_i, camera, layers)) = cameras.get(entity) else {
+            continue;
+        };
+        let settings = CameraSettings {
+            order: camera.order,
+            layers: layers.cloned(),
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        if let Err(err) = write_camera(directory, &name, &settings) {
+            error!("Could not save camera settings of {}: {}", name, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{close_window, test_app, test_config};
+
+    #[test]
+    fn test_parse_camera() {
+        assert_eq!(
+            parse_camera("v0\n\norder:\n-1\n\nlayers:\n0 3\n"),
+            Ok(CameraSettings {
+                order: -1,
+                layers: Some(RenderLayers::from_layers(&[0, 3])),
+            })
+        );
+        assert_eq!(
+            parse_camera("v0\n\norder:\n2\n"),
+            Ok(CameraSettings {
+                order: 2,
+                layers: None,
+            })
+        );
+        assert_eq!(
+            parse_camera("v0\n\norder:\n1\n\nlayers:\n0 x\n"),
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 7,
+                expected: Field::RenderLayers,
+                found: "0 x".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_save_and_restore_cameras() {
+        let config = test_config("assets/tests/cameras");
+
+        let mut app = test_app(config.clone());
+        app.add_systems(Update, save_cameras);
+        app.world_mut().spawn((
+            WhereWasI::from_name("debug"),
+            Camera {
+                order: 3,
+                ..default()
+            },
+            RenderLayers::from_layers(&[1, 4]),
+        ));
+        close_window(&mut app);
+        app.update();
+
+        let mut app = test_app(config);
+        app.add_systems(Startup, restore_cameras);
+        let debug = app
+            .world_mut()
+            .spawn((WhereWasI::from_name("debug"), Camera::default()))
+            .id();
+        app.update();
+
+        assert_eq!(app.world().get::<Camera>(debug).unwrap().order, 3);
+        assert_eq!(
+            app.world().get::<RenderLayers>(debug),
+            Some(&RenderLayers::from_layers(&[1, 4]))
+        );
+
+        fs::remove_dir_all("assets/tests/cameras").unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use crate::{SaveRecord, SaveTrigger, SessionMetadata, WhereWasIConfig};
+
+/// Decides at load time whether the savefile of an entity is restored, see
+/// [`WhereWasI::restore_if`](crate::WhereWasI::restore_if)
+///
+/// When the condition doesn't hold, the entity keeps its authored [`Transform`](bevy_transform::prelude::Transform).
+///
+/// ```rust
+/// use bevy_where_was_i::{RestoreCondition, SaveRecord, SessionMetadata, WhereWasI};
+///
+/// fn saved_by_release(_: &SaveRecord, session: Option<&SessionMetadata>) -> bool {
+///     session
+///         .and_then(|session| session.app_version.as_deref())
+///         .is_some_and(|version| !version.ends_with("-dev"))
+/// }
+///
+/// WhereWasI::camera().restore_if(RestoreCondition::SameAppVersion);
+/// WhereWasI::from_name("marker").restore_if(RestoreCondition::Custom(saved_by_release));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub enum RestoreCondition {
+    /// Only restore savefiles written by the same
+    /// [`WhereWasIPlugin::app_version`](crate::WhereWasIPlugin::app_version) as the running
+    /// application
+    SameAppVersion,
+    /// Only restore savefiles that were written less than this long ago
+    MaxAge(Duration),
+    /// Only restore savefiles that were last written for this [`SaveTrigger`]
+    SavedBy(SaveTrigger),
+    /// Only restore savefiles for which this function returns `true`, given the savefile and the
+    /// metadata of the session that wrote it, if any
+    Custom(fn(&SaveRecord, Option<&SessionMetadata>) -> bool),
+}
+
+impl RestoreCondition {
+    /// Whether `record` may be restored
+    pub(crate) fn holds(
+        &self,
+        record: &SaveRecord,
+        session: Option<&SessionMetadata>,
+        config: &WhereWasIConfig,
+    ) -> bool {
+        match self {
+            Self::SameAppVersion => {
+                session.and_then(|session| session.app_version.as_deref()) == config.app_version()
+            }
+            Self::MaxAge(max_age) => record.age().is_some_and(|age| age < *max_age),
+            Self::SavedBy(trigger) => record.trigger == Some(*trigger),
+            Self::Custom(condition) => condition(record, session),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::tests::test_config;
+
+    #[test]
+    fn test_restore_condition() {
+        let config = WhereWasIConfig {
+            app_version: Some("1.2.3".into()),
+            ..test_config("assets/tests")
+        };
+        let record = SaveRecord {
+            transform: Default::default(),
+            modified: Some(SystemTime::now() - Duration::from_secs(60)),
+            trigger: Some(SaveTrigger::WindowClosed),
+        };
+        let session = SessionMetadata {
+            saved_at: SystemTime::now(),
+            entities: 1,
+            app_version: Some("1.2.2".into()),
+            trigger: None,
+            triggers: BTreeMap::new(),
+            saved: BTreeMap::new(),
+            content_hash: None,
+        };
+
+        assert!(!RestoreCondition::SameAppVersion.holds(&record, Some(&session), &config));
+        assert!(!RestoreCondition::SameAppVersion.holds(&record, None, &config));
+        assert!(RestoreCondition::MaxAge(Duration::from_secs(120)).holds(&record, None, &config));
+        assert!(!RestoreCondition::MaxAge(Duration::from_secs(30)).holds(&record, None, &config));
+        assert!(RestoreCondition::SavedBy(SaveTrigger::WindowClosed).holds(&record, None, &config));
+        assert!(!RestoreCondition::SavedBy(SaveTrigger::Autosave).holds(&record, None, &config));
+        assert!(
+            RestoreCondition::Custom(|_, session| session.is_some()).holds(
+                &record,
+                Some(&session),
+                &config
+            )
+        );
+    }
+}
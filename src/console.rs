@@ -0,0 +1,122 @@
+use crate::prelude::*;
+use bevy_console::{AddConsoleCommand, ConsoleCommand, reply};
+use clap::{Parser, Subcommand};
+
+use crate::{ResetWhereWasI, WhereWasIStore};
+
+/// A plugin that registers the `wwi` command with `bevy_console`
+///
+/// Requires the `console` feature, and `bevy_console`'s `ConsolePlugin` to be added.
+///
+/// - `wwi save [name]`: save all tracked entities, or only the one named `name`
+/// - `wwi load <name>`: restore the entity named `name` from its savefile
+/// - `wwi reset [name]`: reset all tracked entities, or only the one named `name`
+/// - `wwi list`: list all savefiles
+pub struct WhereWasIConsolePlugin;
+
+impl Plugin for WhereWasIConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command::<WhereWasICommand, _>(wwi_command);
+    }
+}
+
+/// Manage the savefiles of tracked entities
+#[derive(Parser, ConsoleCommand)]
+#[command(name = "wwi")]
+struct WhereWasICommand {
+    #[command(subcommand)]
+    action: Action,
+}
+
+#[derive(Subcommand)]
+enum Action {
+    /// Save all tracked entities, or only the one named `name`
+    Save { name: Option<String> },
+    /// Restore the entity named `name` from its savefile
+    Load { name: String },
+    /// Reset all tracked entities, or only the one named `name`
+    Reset { name: Option<String> },
+    /// List all savefiles
+    List,
+}
+
+/// Handles the `wwi` console command
+fn wwi_command(
+    mut command: ConsoleCommand<WhereWasICommand>,
+    mut store: WhereWasIStore,
+    mut resets: MessageWriter<ResetWhereWasI>,
+) {
+    let Some(Ok(WhereWasICommand { action })) = command.take() else {
+        return;
+    };
+
+    let selected = |name: &Option<String>| {
+        store
+            .tracked_names()
+            .into_iter()
+            .filter(|(_, resolved)| name.as_ref().is_none_or(|name| name == resolved))
+            .collect::<Vec<_>>()
+    };
+
+    match action {
+        Action::Save { name } => {
+            let entities = selected(&name);
+            if entities.is_empty() {
+                reply!(
+                    command,
+                    "No tracked entity named {}",
+                    name.unwrap_or_default()
+                );
+                command.failed();
+                return;
+            }
+
+            for (entity, name) in entities {
+                match store.save_entity(entity) {
+                    Ok(()) => reply!(command, "Saved {}", name),
+                    Err(err) => reply!(command, "Could not save {}: {}", name, err),
+                }
+            }
+        }
+        Action::Load { name } => {
+            let Some((entity, _)) = selected(&Some(name.clone())).pop() else {
+                reply!(command, "No tracked entity named {}", name);
+                command.failed();
+                return;
+            };
+
+            match store.reload_entity(entity) {
+                Ok(()) => reply!(command, "Loaded {}", name),
+                Err(err) => {
+                    reply!(command, "Could not load {}: {}", name, err);
+                    command.failed();
+                    return;
+                }
+            }
+        }
+        Action::Reset { name } => {
+            let entities = selected(&name);
+            if entities.is_empty() {
+                reply!(
+                    command,
+                    "No tracked entity named {}",
+                    name.unwrap_or_default()
+                );
+                command.failed();
+                return;
+            }
+
+            for (entity, name) in entities {
+                resets.write(ResetWhereWasI { entity });
+                reply!(command, "Reset {}", name);
+            }
+        }
+        Action::List => {
+            for name in store.list() {
+                reply!(command, "{}", name);
+            }
+        }
+    }
+
+    command.ok();
+}
@@ -0,0 +1,67 @@
+use crate::prelude::*;
+
+/// A [`Resource`] with a hash of the content the tracked entities live in, such as the loaded
+/// level
+///
+/// The hash is stored in the [`SessionMetadata`](crate::SessionMetadata) when the plugin saves. If
+/// it differs at startup, the savefiles were written for other content and
+/// [`WhereWasIPlugin::stale_content`](crate::WhereWasIPlugin::stale_content) decides whether they
+/// are restored. Insert it before the plugin restores entities in [`PostStartup`]. Without this
+/// resource, savefiles are never considered stale.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasIContentHash;
+///
+/// fn load_level(mut commands: Commands) {
+///     let level = "...";
+///     commands.insert_resource(WhereWasIContentHash::new(format!("{:x}", level.len())));
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct WhereWasIContentHash(pub String);
+
+impl WhereWasIContentHash {
+    /// Construct a [`WhereWasIContentHash`] with a hash
+    pub fn new(hash: impl Into<String>) -> Self {
+        Self(hash.into())
+    }
+}
+
+/// What happens to savefiles written for other content, see
+/// [`WhereWasIPlugin::stale_content`](crate::WhereWasIPlugin::stale_content)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StaleContentPolicy {
+    /// Restore the savefiles anyway
+    #[default]
+    Restore,
+    /// Keep the authored [`Transform`] of the entities, as if no savefile existed
+    Skip,
+    /// Keep the authored [`Transform`] and write a [`WhereWasIStaleContent`] message for every
+    /// entity, so the application can decide, for example by restoring it with
+    /// [`WhereWasIStore::reload_entity`](crate::WhereWasIStore::reload_entity)
+    Ask,
+}
+
+/// A [`Message`] that is written for every entity that wasn't restored because its savefile was
+/// written for other content, with [`StaleContentPolicy::Ask`]
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct WhereWasIStaleContent {
+    /// The entity that wasn't restored
+    pub entity: Entity,
+    /// The name of the savefile
+    pub name: String,
+    /// The [`WhereWasIContentHash`] the savefile was written with
+    pub saved: String,
+    /// The current [`WhereWasIContentHash`]
+    pub current: String,
+}
+
+/// The saved and current hash if they differ, or `None` if either is missing
+pub(crate) fn stale_content<'a>(
+    saved: Option<&'a str>,
+    current: Option<&'a WhereWasIContentHash>,
+) -> Option<(&'a str, &'a str)> {
+    let (saved, current) = (saved?, current?.0.as_str());
+    (saved != current).then_some((saved, current))
+}
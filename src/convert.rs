@@ -0,0 +1,138 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::serialization::SaveFormat;
+use crate::{read_state, write_transform};
+
+/// Whether `path` looks like a savefile, a snapshot (`camera.state.1`) or an archived copy
+fn is_savefile(path: &Path) -> bool {
+    let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) else {
+        return false;
+    };
+
+    file_name.ends_with(".state")
+        || file_name
+            .rsplit_once(".state.")
+            .is_some_and(|(_, index)| index.parse::<usize>().is_ok())
+}
+
+/// All savefiles in `directory` and its subdirectories
+fn savefiles(directory: &Path, savefiles: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            self::savefiles(&path, savefiles)?;
+        } else if is_savefile(&path) {
+            savefiles.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite the savefiles at `path` from format `from` into format `to`
+///
+/// `path` is either a single savefile or a save directory, in which case all savefiles, snapshots,
+/// bookmarks and archived copies in it are converted. Savefiles that are not in format `from` are
+/// left untouched, so converting a directory twice is harmless.
+///
+/// Returns the number of savefiles that were converted.
+///
+/// ```rust,no_run
+/// use bevy_where_was_i::{SaveFormat, convert};
+///
+/// let converted = convert("./assets/saves", SaveFormat::Text, SaveFormat::Binary)?;
+/// println!("Converted {converted} savefile(s)");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn convert(path: impl AsRef<Path>, from: SaveFormat, to: SaveFormat) -> io::Result<usize> {
+    let path = path.as_ref();
+    let mut paths = Vec::new();
+    if path.is_dir() {
+        savefiles(path, &mut paths)?;
+    } else {
+        paths.push(path.to_path_buf());
+    }
+
+    let mut converted = 0;
+    for path in paths {
+        if from == to || SaveFormat::detect(&path)? != from {
+            continue;
+        }
+
+        let transform = read_state(&path).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })?;
+        write_transform(&path, &transform, to)?;
+        converted += 1;
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_is_savefile() {
+        assert!(is_savefile(Path::new("camera.state")));
+        assert!(is_savefile(Path::new("camera.state.2")));
+        assert!(is_savefile(Path::new(
+            "archive/camera.20250101T000000Z.state"
+        )));
+        assert!(!is_savefile(Path::new("session.meta")));
+        assert!(!is_savefile(Path::new("camera.state.bak")));
+    }
+
+    #[test]
+    fn test_convert_directory() {
+        let directory = Path::new("assets/tests/convert");
+        fs::create_dir_all(directory.join("bookmarks/overview")).unwrap();
+        fs::copy("assets/tests/camera.state", directory.join("camera.state")).unwrap();
+        fs::copy(
+            "assets/tests/identity.state",
+            directory.join("bookmarks/overview/camera.state"),
+        )
+        .unwrap();
+        let expected = read_state(directory.join("camera.state")).unwrap();
+
+        assert_eq!(
+            convert(directory, SaveFormat::Text, SaveFormat::Binary).unwrap(),
+            2
+        );
+        assert_eq!(
+            convert(directory, SaveFormat::Text, SaveFormat::Binary).unwrap(),
+            0
+        );
+        assert_eq!(
+            SaveFormat::detect(directory.join("camera.state")).unwrap(),
+            SaveFormat::Binary
+        );
+        assert_eq!(
+            read_state(directory.join("camera.state")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            read_state(directory.join("bookmarks/overview/camera.state")).unwrap(),
+            Transform::IDENTITY
+        );
+
+        assert_eq!(
+            convert(directory, SaveFormat::Binary, SaveFormat::Text).unwrap(),
+            2
+        );
+        assert_eq!(
+            fs::read(directory.join("camera.state")).unwrap(),
+            include_bytes!("../assets/tests/camera.state")
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
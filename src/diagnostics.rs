@@ -0,0 +1,129 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+
+use crate::WhereWasI;
+
+/// Measurements of the last save, updated by the plugin every time it saves
+#[derive(Resource, Default, Debug)]
+pub(crate) struct SaveMetrics {
+    pub(crate) last: Option<SaveMeasurement>,
+}
+
+/// How much a save wrote and how long it took
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SaveMeasurement {
+    pub(crate) bytes: u64,
+    pub(crate) duration: Duration,
+    pub(crate) saved_at: SystemTime,
+}
+
+/// Adds diagnostics for [`WhereWasIPlugin`](crate::WhereWasIPlugin) to the
+/// [`DiagnosticsStore`](bevy_diagnostic::DiagnosticsStore)
+///
+/// They show up in `LogDiagnosticsPlugin` and other diagnostics overlays, next to the frame time.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasIDiagnosticsPlugin, WhereWasIPlugin};
+///
+/// App::new().add_plugins((WhereWasIPlugin::default(), WhereWasIDiagnosticsPlugin));
+/// ```
+pub struct WhereWasIDiagnosticsPlugin;
+
+impl WhereWasIDiagnosticsPlugin {
+    /// The number of entities with a [`WhereWasI`] component
+    pub const TRACKED_ENTITIES: DiagnosticPath =
+        DiagnosticPath::const_new("where_was_i/tracked_entities");
+    /// The number of bytes written by the last save
+    pub const BYTES_WRITTEN: DiagnosticPath =
+        DiagnosticPath::const_new("where_was_i/bytes_written");
+    /// How long the last save took, in milliseconds
+    pub const SAVE_DURATION: DiagnosticPath =
+        DiagnosticPath::const_new("where_was_i/save_duration");
+    /// When the last save happened, in seconds since the Unix epoch
+    pub const LAST_SAVE: DiagnosticPath = DiagnosticPath::const_new("where_was_i/last_save");
+}
+
+impl Plugin for WhereWasIDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::TRACKED_ENTITIES))
+            .register_diagnostic(Diagnostic::new(Self::BYTES_WRITTEN).with_suffix("B"))
+            .register_diagnostic(Diagnostic::new(Self::SAVE_DURATION).with_suffix("ms"))
+            .register_diagnostic(
+                Diagnostic::new(Self::LAST_SAVE)
+                    .with_suffix("s")
+                    .with_smoothing_factor(0.0),
+            )
+            .init_resource::<SaveMetrics>()
+            .add_systems(Update, diagnostic_system);
+    }
+}
+
+/// Adds the measurements of the current frame
+fn diagnostic_system(
+    mut diagnostics: Diagnostics,
+    tracked: Query<(), With<WhereWasI>>,
+    metrics: Res<SaveMetrics>,
+) {
+    diagnostics.add_measurement(&WhereWasIDiagnosticsPlugin::TRACKED_ENTITIES, || {
+        tracked.iter().len() as f64
+    });
+
+    if let Some(last) = metrics.last
+        && metrics.is_changed()
+    {
+        diagnostics.add_measurement(&WhereWasIDiagnosticsPlugin::BYTES_WRITTEN, || {
+            last.bytes as f64
+        });
+        diagnostics.add_measurement(&WhereWasIDiagnosticsPlugin::SAVE_DURATION, || {
+            last.duration.as_secs_f64() * 1000.0
+        });
+        diagnostics.add_measurement(&WhereWasIDiagnosticsPlugin::LAST_SAVE, || {
+            last.saved_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs_f64()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_diagnostic::DiagnosticsStore;
+
+    use super::*;
+    use crate::save_state;
+    use crate::tests::{close_window, test_app, test_config};
+
+    #[test]
+    fn test_diagnostics() {
+        let mut app = test_app(test_config("assets/tests/diagnostics"));
+        app.add_plugins(WhereWasIDiagnosticsPlugin)
+            .add_systems(Update, save_state);
+        app.world_mut()
+            .spawn((WhereWasI::from_name("diagnostics"), Transform::IDENTITY));
+
+        close_window(&mut app);
+        app.update();
+
+        let store = app.world().resource::<DiagnosticsStore>();
+        let value = |path: &DiagnosticPath| store.get(path).and_then(Diagnostic::value);
+        assert_eq!(
+            value(&WhereWasIDiagnosticsPlugin::TRACKED_ENTITIES),
+            Some(1.0)
+        );
+        assert_eq!(
+            value(&WhereWasIDiagnosticsPlugin::BYTES_WRITTEN),
+            Some(
+                std::fs::metadata("assets/tests/diagnostics/diagnostics.state")
+                    .unwrap()
+                    .len() as f64
+            )
+        );
+        assert!(value(&WhereWasIDiagnosticsPlugin::LAST_SAVE).is_some());
+
+        std::fs::remove_dir_all("assets/tests/diagnostics").unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+use crate::prelude::*;
+
+use crate::WhereWasIStore;
+use crate::names::resolve_tracked_names;
+
+/// Translation and scale differences smaller than this are considered unchanged
+const EPSILON: f32 = 1e-5;
+
+/// Rotations closer than this angle (in radians) are considered unchanged
+///
+/// This is larger than [`EPSILON`] because the angle is computed with `acos`, which is imprecise
+/// for nearly identical rotations.
+const ANGLE_EPSILON: f32 = 1e-3;
+
+/// How far the [`Transform`] of a tracked entity has drifted from its savefile, see
+/// [`WhereWasIStore::diff`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformDiff {
+    /// The tracked entity
+    pub entity: Entity,
+    /// The name the entity is saved under
+    pub name: String,
+    /// The current [`Transform`] of the entity
+    pub current: Transform,
+    /// The [`Transform`] in the savefile, or `None` if it was never saved
+    pub saved: Option<Transform>,
+    /// The [`Transform`] the entity was authored with, see
+    /// [`WhereWasIAuthored`](crate::WhereWasIAuthored)
+    pub authored: Option<Transform>,
+}
+
+/// Whether `from` and `to` differ by more than [`EPSILON`] or [`ANGLE_EPSILON`]
+fn differs(from: &Transform, to: &Transform) -> bool {
+    (to.translation - from.translation).length() > EPSILON
+        || from.rotation.angle_between(to.rotation) > ANGLE_EPSILON
+        || (to.scale - from.scale).length() > EPSILON
+}
+
+impl TransformDiff {
+    /// The translation from the saved to the current position
+    pub fn translation_delta(&self) -> Option<Vec3> {
+        Some(self.current.translation - self.saved?.translation)
+    }
+
+    /// The angle in radians between the saved and the current rotation
+    pub fn rotation_angle(&self) -> Option<f32> {
+        Some(self.saved?.rotation.angle_between(self.current.rotation))
+    }
+
+    /// The difference between the current and the saved scale
+    pub fn scale_delta(&self) -> Option<Vec3> {
+        Some(self.current.scale - self.saved?.scale)
+    }
+
+    /// Whether the current [`Transform`] differs from the savefile, or was never saved
+    pub fn is_dirty(&self) -> bool {
+        self.saved
+            .is_none_or(|saved| differs(&saved, &self.current))
+    }
+
+    /// Whether the current [`Transform`] is still the one the entity was authored with
+    pub fn is_authored(&self) -> bool {
+        self.authored
+            .is_some_and(|authored| !differs(&authored, &self.current))
+    }
+}
+
+/// A [`Message`] that logs the [`TransformDiff`] of every tracked entity
+///
+/// Useful to find out on demand which entities have unsaved changes.
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct LogWhereWasIDiff;
+
+impl WhereWasIStore<'_, '_> {
+    /// How far the [`Transform`] of every tracked entity has drifted from its savefile
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIStore;
+    ///
+    /// fn has_unsaved_changes(store: WhereWasIStore) -> bool {
+    ///     store.diff().iter().any(|diff| diff.is_dirty())
+    /// }
+    /// ```
+    pub fn diff(&self) -> Vec<TransformDiff> {
+        let names = resolve_tracked_names(
+            self.tracked
+                .iter()
+                .map(|(entity, where_was_i, _)| (entity, where_was_i)),
+            self.config.name_collisions,
+        );
+
+        names
+            .into_iter()
+            .filter_map(|(entity, name)| {
+                let (_, _, current) = self.tracked.get(entity).ok()?;
+                let saved = self.saves.get(&name).map(|record| record.transform);
+                let authored = self.authored.get(entity).ok().map(|authored| authored.0);
+                Some(TransformDiff {
+                    entity,
+                    name,
+                    current: *current,
+                    saved,
+                    authored,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Handles [`LogWhereWasIDiff`] messages
+pub(crate) fn log_diff(mut requests: MessageReader<LogWhereWasIDiff>, store: WhereWasIStore) {
+    if requests.read().count() == 0 {
+        return;
+    }
+
+    for diff in store.diff() {
+        match (diff.translation_delta(), diff.rotation_angle()) {
+            (Some(translation), Some(angle)) => info!(
+                "{}: moved {:.3} and rotated {:.2} degrees since last save",
+                diff.name,
+                translation.length(),
+                angle.to_degrees()
+            ),
+            _ if diff.is_authored() => info!("{}: never saved, still authored", diff.name),
+            _ => info!("{}: never saved", diff.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(saved: Option<Transform>) -> TransformDiff {
+        TransformDiff {
+            entity: Entity::PLACEHOLDER,
+            name: "camera".into(),
+            current: Transform::from_xyz(3.0, 4.0, 0.0)
+                .with_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2)),
+            saved,
+            authored: None,
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let diff = diff(Some(Transform::IDENTITY));
+
+        assert_eq!(diff.translation_delta(), Some(Vec3::new(3.0, 4.0, 0.0)));
+        assert!((diff.rotation_angle().unwrap() - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+        assert_eq!(diff.scale_delta(), Some(Vec3::ZERO));
+        assert!(diff.is_dirty());
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let current = diff(None).current;
+        assert!(!diff(Some(current)).is_dirty());
+    }
+
+    #[test]
+    fn test_diff_never_saved() {
+        let diff = diff(None);
+
+        assert_eq!(diff.translation_delta(), None);
+        assert!(diff.is_dirty());
+    }
+
+    #[test]
+    fn test_diff_authored() {
+        let mut diff = diff(None);
+        assert!(!diff.is_authored());
+
+        diff.authored = Some(diff.current);
+        assert!(diff.is_authored());
+
+        diff.authored = Some(Transform::IDENTITY);
+        assert!(!diff.is_authored());
+    }
+}
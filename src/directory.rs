@@ -0,0 +1,412 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+
+/// The environment variable that overrides the save directory
+pub const SAVE_DIR_ENV: &str = "WWI_SAVE_DIR";
+
+/// The optional configuration file next to the executable that overrides the save directory
+///
+/// Only the top-level `directory` key is read, for example `directory = "/var/lib/my_game/saves"`.
+/// A relative directory is relative to the configuration file.
+pub const CONFIG_FILE: &str = "where_was_i.toml";
+
+/// Where the save directory in [`WhereWasIConfig::directory`](crate::WhereWasIConfig::directory)
+/// came from
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum DirectorySource {
+    /// The [`SAVE_DIR_ENV`] environment variable
+    Environment,
+    /// The [`CONFIG_FILE`] at this path
+    ConfigFile(PathBuf),
+    /// [`WhereWasIPlugin::directory`](crate::WhereWasIPlugin::directory)
+    #[default]
+    Plugin,
+    /// [`WhereWasIPlugin::fallback_directory`](crate::WhereWasIPlugin::fallback_directory),
+    /// because the directory from one of the other sources couldn't be written
+    Fallback,
+}
+
+/// Save directories for each platform, so a cross-platform application can declare them in one
+/// place, see [`WhereWasIPlugin::from_platform_directories`](crate::WhereWasIPlugin::from_platform_directories)
+///
+/// Platforms without a directory of their own use [`desktop`](Self::desktop). In the browser,
+/// savefiles are kept in `localStorage` by using a `localStorage:` directory for
+/// [`wasm`](Self::wasm).
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{PlatformDirectories, WhereWasIPlugin};
+///
+/// WhereWasIPlugin::from_platform_directories(PlatformDirectories {
+///     wasm: Some("localStorage:my_app".into()),
+///     android: Some("/data/data/com.example.my_app/files/saves".into()),
+///     ..PlatformDirectories::new("./assets/saves")
+/// });
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlatformDirectories {
+    /// The directory on Windows, macOS, Linux and any platform without a directory below
+    pub desktop: PathBuf,
+    /// The directory on the web (`wasm32`)
+    ///
+    /// The browser has no filesystem, so use a directory like `localStorage:my_app` there, which
+    /// keeps savefiles in `localStorage` under keys like `my_app/camera.state` and requires the
+    /// `web` feature. Other directories can only be written on WASI, in the browser they result in
+    /// [`PersistenceUnavailable`](crate::PersistenceUnavailable).
+    pub wasm: Option<PathBuf>,
+    /// The directory on Android
+    pub android: Option<PathBuf>,
+    /// The directory on iOS
+    pub ios: Option<PathBuf>,
+}
+
+impl PlatformDirectories {
+    /// Use `desktop` on every platform, until others are set
+    pub fn new(desktop: impl Into<PathBuf>) -> Self {
+        Self {
+            desktop: desktop.into(),
+            wasm: None,
+            android: None,
+            ios: None,
+        }
+    }
+
+    /// The directory for the platform the application was compiled for
+    pub fn current(&self) -> &Path {
+        let platform = if cfg!(target_arch = "wasm32") {
+            &self.wasm
+        } else if cfg!(target_os = "android") {
+            &self.android
+        } else if cfg!(target_os = "ios") {
+            &self.ios
+        } else {
+            &None
+        };
+        platform.as_deref().unwrap_or(&self.desktop)
+    }
+}
+
+/// Read the `directory` key from the configuration file at `path`
+///
+/// Returns `Ok(None)` if the file doesn't exist or doesn't set a directory.
+fn read_config_file(path: &Path) -> io::Result<Option<PathBuf>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let Some(directory) =
+        parse_config(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+    else {
+        return Ok(None);
+    };
+    let directory = Path::new(&directory);
+    Ok(Some(match path.parent() {
+        Some(parent) if directory.is_relative() => parent.join(directory),
+        _ => directory.into(),
+    }))
+}
+
+/// The top-level `directory` key of a configuration file
+///
+/// This understands the subset of TOML the key needs: comments, table headers, whose keys are
+/// skipped, and basic and literal strings on a single line.
+fn parse_config(contents: &str) -> Result<Option<String>, String> {
+    let mut in_table = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_table = true;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if in_table || key.trim() != "directory" {
+            continue;
+        }
+
+        let value = value.trim();
+        let (directory, rest) = parse_string(value)
+            .ok_or_else(|| format!("expected a quoted string for directory, found {value}"))??;
+        let rest = rest.trim_start();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return Err(format!("unexpected {rest} after the directory"));
+        }
+        return Ok(Some(directory));
+    }
+
+    Ok(None)
+}
+
+/// The basic (`"..."`) or literal (`'...'`) string at the start of `value` and what follows it,
+/// or `None` if `value` doesn't start with a string
+fn parse_string(value: &str) -> Option<Result<(String, &str), String>> {
+    if let Some(value) = value.strip_prefix('\'') {
+        return Some(
+            value
+                .split_once('\'')
+                .map(|(string, rest)| (string.into(), rest))
+                .ok_or_else(|| "unterminated string".into()),
+        );
+    }
+
+    let mut chars = value.strip_prefix('"')?.chars();
+    let mut string = String::new();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => return Some(Ok((string, chars.as_str()))),
+            '\\' => match chars.next() {
+                Some('\\') => string.push('\\'),
+                Some('"') => string.push('"'),
+                Some('t') => string.push('\t'),
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                escape => {
+                    return Some(Err(format!(
+                        "unsupported escape \\{}",
+                        escape.map(String::from).unwrap_or_default()
+                    )));
+                }
+            },
+            char => string.push(char),
+        }
+    }
+    Some(Err("unterminated string".into()))
+}
+
+/// The save directory from `environment`, the configuration file at `config_file` or `default`,
+/// in that order
+fn resolve_directory_from(
+    environment: Option<PathBuf>,
+    config_file: Option<PathBuf>,
+    default: &Path,
+) -> (PathBuf, DirectorySource) {
+    if let Some(directory) = environment.filter(|directory| !directory.as_os_str().is_empty()) {
+        return (directory, DirectorySource::Environment);
+    }
+
+    if let Some(path) = config_file {
+        match read_config_file(&path) {
+            Ok(Some(directory)) => return (directory, DirectorySource::ConfigFile(path)),
+            Ok(None) => {}
+            Err(err) => warn!("Ignoring {}: {}", path.display(), err),
+        }
+    }
+
+    (default.into(), DirectorySource::Plugin)
+}
+
+/// The internal files directory of the app on Android, because the working directory isn't
+/// writable there
+#[cfg(target_os = "android")]
+fn app_data_directory() -> Option<PathBuf> {
+    bevy_android::ANDROID_APP
+        .get()
+        .and_then(|app| app.internal_data_path())
+}
+
+/// The Documents directory in the sandbox of the app on iOS, because the working directory isn't
+/// writable there
+///
+/// iOS points `HOME` at the sandbox container of the app.
+#[cfg(target_os = "ios")]
+fn app_data_directory() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join("Documents"))
+}
+
+/// The directory that relative save directories are placed in, or `None` to place them in the
+/// working directory
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn app_data_directory() -> Option<PathBuf> {
+    None
+}
+
+/// A directory named after the executable in the data directory of the user, such as
+/// `~/.local/share/my_game` on Linux, `~/Library/Application Support/my_game` on macOS and
+/// `%APPDATA%\my_game` on Windows
+pub(crate) fn user_data_directory() -> Option<PathBuf> {
+    let name = env::current_exe().ok()?.file_stem()?.to_owned();
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let data_directory = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|directory| !directory.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local/share")))
+    };
+    Some(data_directory?.join(name))
+}
+
+/// `directory` in `data_directory` if it is relative
+fn in_data_directory(directory: PathBuf, data_directory: Option<&Path>) -> PathBuf {
+    match data_directory {
+        Some(data_directory) if directory.is_relative() => data_directory.join(directory),
+        _ => directory,
+    }
+}
+
+/// The save directory from [`SAVE_DIR_ENV`], the [`CONFIG_FILE`] next to the executable or
+/// `default`, in that order
+///
+/// On Android, a relative directory is placed in the internal files directory of the app, and on
+/// iOS in its Documents directory.
+pub(crate) fn resolve_directory(default: &Path) -> (PathBuf, DirectorySource) {
+    let config_file = env::current_exe()
+        .ok()
+        .and_then(|executable| Some(executable.parent()?.join(CONFIG_FILE)));
+    let (directory, source) = resolve_directory_from(
+        env::var_os(SAVE_DIR_ENV).map(Into::into),
+        config_file,
+        default,
+    );
+
+    match &source {
+        DirectorySource::Environment => {
+            info!(
+                "Using save directory {} from {}",
+                directory.display(),
+                SAVE_DIR_ENV
+            );
+        }
+        DirectorySource::ConfigFile(path) => {
+            info!(
+                "Using save directory {} from {}",
+                directory.display(),
+                path.display()
+            );
+        }
+        DirectorySource::Plugin | DirectorySource::Fallback => {}
+    }
+    (
+        in_data_directory(directory, app_data_directory().as_deref()),
+        source,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_directories() {
+        let directories = PlatformDirectories {
+            wasm: Some("localStorage:my_app".into()),
+            ..PlatformDirectories::new("assets/saves")
+        };
+        let expected = if cfg!(target_arch = "wasm32") {
+            "localStorage:my_app"
+        } else {
+            "assets/saves"
+        };
+        assert_eq!(directories.current(), Path::new(expected));
+    }
+
+    #[test]
+    fn test_in_data_directory() {
+        let data_directory = Path::new("/data/user/0/com.example.my_app/files");
+        assert_eq!(
+            in_data_directory("assets/saves".into(), Some(data_directory)),
+            data_directory.join("assets/saves")
+        );
+        assert_eq!(
+            in_data_directory("/sdcard/saves".into(), Some(data_directory)),
+            Path::new("/sdcard/saves")
+        );
+        assert_eq!(
+            in_data_directory("assets/saves".into(), None),
+            Path::new("assets/saves")
+        );
+    }
+
+    #[test]
+    fn test_resolve_directory() {
+        let directory = Path::new("assets/tests/directory_overrides");
+        fs::create_dir_all(directory).unwrap();
+        let config_file = directory.join(CONFIG_FILE);
+        fs::write(&config_file, "# Saves\ndirectory = \"saves\"\n").unwrap();
+        let default = Path::new("assets/saves");
+
+        assert_eq!(
+            resolve_directory_from(
+                Some("/tmp/saves".into()),
+                Some(config_file.clone()),
+                default
+            ),
+            ("/tmp/saves".into(), DirectorySource::Environment)
+        );
+        assert_eq!(
+            resolve_directory_from(Some("".into()), Some(config_file.clone()), default),
+            (
+                directory.join("saves"),
+                DirectorySource::ConfigFile(config_file.clone())
+            )
+        );
+        assert_eq!(
+            resolve_directory_from(None, Some(directory.join("missing.toml")), default),
+            (default.into(), DirectorySource::Plugin)
+        );
+
+        fs::write(&config_file, "directory = saves\n").unwrap();
+        assert_eq!(
+            resolve_directory_from(None, Some(config_file), default),
+            (default.into(), DirectorySource::Plugin)
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_parse_config_comments() {
+        assert_eq!(
+            parse_config("# Saves\ndirectory = \"saves\" # CI\n"),
+            Ok(Some("saves".into()))
+        );
+        assert_eq!(
+            parse_config("directory = 'saves#1'#CI\n"),
+            Ok(Some("saves#1".into()))
+        );
+        assert!(parse_config("directory = \"saves\" CI\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_config_escapes() {
+        assert_eq!(
+            parse_config(r#"directory = "C:\\Games\\saves""#),
+            Ok(Some(r"C:\Games\saves".into()))
+        );
+        assert_eq!(
+            parse_config(r#"directory = "my \"game\" saves""#),
+            Ok(Some(r#"my "game" saves"#.into()))
+        );
+        assert_eq!(
+            parse_config(r"directory = 'C:\Games\saves'"),
+            Ok(Some(r"C:\Games\saves".into()))
+        );
+        assert!(parse_config(r#"directory = "C:\Games""#).is_err());
+        assert!(parse_config(r#"directory = "saves"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_config_tables() {
+        assert_eq!(
+            parse_config("[backup]\ndirectory = \"backups\"\n"),
+            Ok(None)
+        );
+        assert_eq!(
+            parse_config("directory = \"saves\"\n\n[backup]\ndirectory = \"backups\"\n"),
+            Ok(Some("saves".into()))
+        );
+    }
+}
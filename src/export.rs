@@ -0,0 +1,259 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "mmap")]
+use std::collections::BTreeMap;
+#[cfg(feature = "mmap")]
+use std::ops::Range;
+
+use crate::names::validate_name;
+#[cfg(feature = "mmap")]
+use crate::saves::state_name;
+use crate::{WhereWasISaves, WhereWasIStore};
+
+/// The first line of an exported archive
+const HEADER: &str = "wwi-archive v0";
+
+/// The subdirectory of the save directory that is not exported, see
+/// [`ArchivePolicy`](crate::ArchivePolicy)
+const EXCLUDED_DIRECTORY: &str = "archive";
+
+/// Create an [`io::Error`] for an archive that can't be imported
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// All files in `directory` and its subdirectories, relative to `directory`
+fn files(directory: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            if path != Path::new(EXCLUDED_DIRECTORY) {
+                self::files(directory, &path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Convert a path read from an archive into a path relative to the save directory
+///
+/// Every component has to be a valid name, so an archive can't write outside the save directory.
+fn relative_path(path: &str) -> io::Result<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.split('/') {
+        validate_name(component).map_err(|err| invalid_data(err.to_string()))?;
+        relative.push(component);
+    }
+    Ok(relative)
+}
+
+/// The line of `bytes` that starts at `position`, moving `position` past it
+#[cfg(feature = "mmap")]
+fn next_line<'a>(bytes: &'a [u8], position: &mut usize) -> io::Result<Option<&'a str>> {
+    let rest = &bytes[*position..];
+    if rest.is_empty() {
+        return Ok(None);
+    }
+    let end = rest
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .unwrap_or(rest.len());
+    *position += (end + 1).min(rest.len());
+    str::from_utf8(&rest[..end])
+        .map(Some)
+        .map_err(|err| invalid_data(err.to_string()))
+}
+
+/// The savefiles in the root of an archive created by
+/// [`export_archive`](WhereWasIStore::export_archive), by name, with the range of their contents
+/// in `bytes`
+///
+/// Only the names and lengths are read, the contents are skipped without being decoded.
+#[cfg(feature = "mmap")]
+pub(crate) fn index_archive(bytes: &[u8]) -> io::Result<BTreeMap<String, Range<usize>>> {
+    let mut position = 0;
+    let header = next_line(bytes, &mut position)?.unwrap_or_default();
+    if header.trim_end() != HEADER {
+        return Err(invalid_data(format!(
+            "Not an archive: {}",
+            header.trim_end()
+        )));
+    }
+
+    let mut index = BTreeMap::new();
+    while let Some(path) = next_line(bytes, &mut position)? {
+        let relative = relative_path(path)?;
+        let length = next_line(bytes, &mut position)?
+            .ok_or_else(|| invalid_data("Unexpected end of archive"))?
+            .trim_end()
+            .parse::<usize>()
+            .map_err(|err| invalid_data(err.to_string()))?;
+
+        let contents = position..position.saturating_add(length);
+        if contents.end > bytes.len() {
+            return Err(invalid_data("Unexpected end of archive"));
+        }
+        position = (contents.end + 1).min(bytes.len());
+
+        if relative.parent() == Some(Path::new(""))
+            && let Some(name) = state_name(&relative)
+        {
+            index.insert(name, contents);
+        }
+    }
+    Ok(index)
+}
+
+impl WhereWasIStore<'_, '_> {
+    /// Bundle all savefiles, snapshots, bookmarks and session metadata into a single file at `path`
+    ///
+    /// The archive can be shared and imported elsewhere with
+    /// [`import_archive`](Self::import_archive). The archived copies of
+    /// [`ArchivePolicy`](crate::ArchivePolicy) are not included.
+    ///
+    /// Returns the number of files that were exported.
+    pub fn export_archive(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let mut paths = Vec::new();
+        if fs::exists(&self.config.directory)? {
+            files(&self.config.directory, Path::new(""), &mut paths)?;
+        }
+        paths.sort();
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{HEADER}")?;
+
+        for relative in &paths {
+            let name = relative
+                .iter()
+                .map(|component| component.to_str())
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid_data("File name is not valid UTF-8"))?
+                .join("/");
+            let contents = fs::read(self.config.directory.join(relative))?;
+
+            writeln!(writer, "{name}")?;
+            writeln!(writer, "{}", contents.len())?;
+            writer.write_all(&contents)?;
+            writeln!(writer)?;
+        }
+
+        writer.flush()?;
+        Ok(paths.len())
+    }
+
+    /// Extract an archive created by [`export_archive`](Self::export_archive) into the save
+    /// directory, overwriting existing files with the same name
+    ///
+    /// [`WhereWasISaves`] is refreshed afterwards, but the imported state is not applied to
+    /// entities until they are restored, for example on the next startup.
+    ///
+    /// Returns the number of files that were imported.
+    pub fn import_archive(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut line = String::new();
+
+        reader.read_line(&mut line)?;
+        if line.trim_end() != HEADER {
+            return Err(invalid_data(format!("Not an archive: {}", line.trim_end())));
+        }
+
+        let mut imported = 0;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let relative = relative_path(line.trim_end_matches('\n'))?;
+
+            line.clear();
+            reader.read_line(&mut line)?;
+            let length = line
+                .trim_end()
+                .parse::<u64>()
+                .map_err(|err| invalid_data(err.to_string()))?;
+
+            let mut contents = Vec::new();
+            (&mut reader).take(length).read_to_end(&mut contents)?;
+            if contents.len() as u64 != length {
+                return Err(invalid_data("Unexpected end of archive"));
+            }
+            reader.read_line(&mut line)?;
+
+            let target = self.config.directory.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target, contents)?;
+            imported += 1;
+        }
+
+        *self.saves = WhereWasISaves::read_directory(&self.config);
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::WhereWasIConfig;
+    use crate::tests::{test_app, test_config};
+
+    #[test]
+    fn test_export_import() {
+        let from = Path::new("assets/tests/export_from");
+        fs::create_dir_all(from.join("bookmarks/overview")).unwrap();
+        fs::copy("assets/tests/camera.state", from.join("camera.state")).unwrap();
+        fs::copy(
+            "assets/tests/identity.state",
+            from.join("bookmarks/overview/camera.state"),
+        )
+        .unwrap();
+
+        let mut app = test_app(test_config("assets/tests/export_from"));
+        let exported = app
+            .world_mut()
+            .run_system_once(|store: WhereWasIStore| {
+                store.export_archive("assets/tests/export.wwi").unwrap()
+            })
+            .unwrap();
+        assert_eq!(exported, 2);
+
+        app.insert_resource(test_config("assets/tests/export_to"));
+        let imported = app
+            .world_mut()
+            .run_system_once(|mut store: WhereWasIStore| {
+                store.import_archive("assets/tests/export.wwi").unwrap()
+            })
+            .unwrap();
+        assert_eq!(imported, 2);
+
+        let to = app.world().resource::<WhereWasIConfig>().directory();
+        assert_eq!(
+            fs::read(to.join("bookmarks/overview/camera.state")).unwrap(),
+            include_bytes!("../assets/tests/identity.state")
+        );
+        assert!(app.world().resource::<WhereWasISaves>().contains("camera"));
+
+        fs::remove_dir_all(from).unwrap();
+        fs::remove_dir_all("assets/tests/export_to").unwrap();
+        fs::remove_file("assets/tests/export.wwi").unwrap();
+    }
+
+    #[test]
+    fn test_relative_path_rejects_traversal() {
+        assert!(relative_path("../camera.state").is_err());
+        assert!(relative_path("bookmarks//camera.state").is_err());
+        assert_eq!(
+            relative_path("bookmarks/overview/camera.state").unwrap(),
+            Path::new("bookmarks/overview/camera.state")
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::prelude::*;
+use bevy_math::I64Vec3;
+use big_space::prelude::CellCoord;
+
+use crate::names::resolve_names;
+use crate::position::{read_position, write_position};
+use crate::{
+    PersistenceUnavailable, SaveWhereWasI, WhereWasI, WhereWasIConfig, WhereWasILoaded,
+    WhereWasIPosition,
+};
+
+/// Restore the [`CellCoord`] of tracked entities together with their [`Transform`]
+///
+/// The [`Transform`] of an entity in a `big_space` grid is relative to its cell, so restoring it
+/// alone would place the entity in the wrong cell. Entities with a [`WhereWasIPosition`] are left
+/// to it.
+pub(crate) fn restore_grid_cells(
+    mut loaded: MessageReader<WhereWasILoaded>,
+    mut cells: Query<(&WhereWasI, &mut CellCoord), Without<WhereWasIPosition>>,
+    config: Res<WhereWasIConfig>,
+) {
+    for loaded in loaded.read() {
+        let Ok((where_was_i, mut cell)) = cells.get_mut(loaded.entity) else {
+            continue;
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        match read_position(directory, &loaded.name) {
+            Ok(Some(WhereWasIPosition {
+                cell: Some(restored),
+                ..
+            })) => {
+                cell.x = restored.x;
+                cell.y = restored.y;
+                cell.z = restored.z;
+            }
+            Ok(_) => {}
+            Err(err) => error!("Could not read grid cell of {}: {}", loaded.name, err),
+        }
+    }
+}
+
+/// Save the [`CellCoord`] of tracked entities when all entities are saved
+pub(crate) fn save_grid_cells(
+    mut requests: MessageReader<SaveWhereWasI>,
+    tracked: Query<(Entity, &WhereWasI, &Transform, &CellCoord), Without<WhereWasIPosition>>,
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let save_all = requests.read().any(|request| request.window.is_none());
+    if !save_all || unavailable.is_some() || config.dry_run {
+        return;
+    }
+
+    let names = resolve_names(
+        tracked
+            .iter()
+            .filter(|(_, where_was_i, ..)| !where_was_i.follows)
+            .map(|(entity, where_was_i, ..)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        let Ok((_, where_was_i, transform, cell)) = tracked.get(entity) else {
+            continue;
+        };
+        let position = WhereWasIPosition {
+            translation: transform.translation.as_dvec3(),
+            cell: Some(I64Vec3::new(cell.x, cell.y, cell.z)),
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        if let Err(err) = write_position(directory, &name, &position) {
+            error!("Could not save grid cell of {}: {}", name, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::{close_window, test_app, test_config};
+    use crate::{load_state, read_saves, save_state};
+
+    #[test]
+    fn test_save_and_restore_grid_cells() {
+        let config = test_config("assets/tests/grid_cells");
+
+        let mut app = test_app(config.clone());
+        app.add_systems(Update, (save_state, save_grid_cells));
+        app.world_mut().spawn((
+            WhereWasI::camera(),
+            Transform::from_xyz(1.0, 2.0, 3.0),
+            CellCoord::new(4_000_000_000, 0, -7),
+        ));
+        close_window(&mut app);
+        app.update();
+
+        let mut app = test_app(config);
+        app.add_systems(Update, (read_saves, load_state, restore_grid_cells).chain());
+        let camera = app
+            .world_mut()
+            .spawn((WhereWasI::camera(), CellCoord::default()))
+            .id();
+        app.update();
+
+        assert_eq!(
+            *app.world().get::<CellCoord>(camera).unwrap(),
+            CellCoord::new(4_000_000_000, 0, -7)
+        );
+        assert_eq!(
+            *app.world().get::<Transform>(camera).unwrap(),
+            Transform::from_xyz(1.0, 2.0, 3.0)
+        );
+
+        fs::remove_dir_all("assets/tests/grid_cells").unwrap();
+    }
+}
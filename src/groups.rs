@@ -0,0 +1,162 @@
+use std::path::PathBuf;
+
+use crate::prelude::*;
+use bevy_ecs::lifecycle::HookContext;
+use bevy_ecs::world::DeferredWorld;
+
+use crate::{SaveFormat, SavePolicy, WhereWasI};
+
+/// Puts a tracked entity in a named group, with its own settings from
+/// [`WhereWasIPlugin::groups`](crate::WhereWasIPlugin::groups)
+///
+/// Entities in a group that isn't configured are saved like entities without a group.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasI, WhereWasIGroup};
+///
+/// fn spawn_panel(mut commands: Commands) {
+///     commands.spawn((WhereWasI::from_name("outliner"), WhereWasIGroup::new("editor_ui")));
+/// }
+/// ```
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+#[component(on_insert = copy_group, on_remove = clear_group)]
+pub struct WhereWasIGroup(pub String);
+
+impl WhereWasIGroup {
+    /// Put the entity in the group `name`
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+/// The settings of a group of entities, see [`WhereWasIGroup`]
+///
+/// Settings that are `None` are taken from [`WhereWasIPlugin`](crate::WhereWasIPlugin).
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{SaveFormat, SaveGroup, WhereWasIPlugin};
+///
+/// WhereWasIPlugin {
+///     groups: BTreeMap::from([(
+///         "editor_ui".into(),
+///         SaveGroup {
+///             directory: Some("./assets/saves/editor".into()),
+///             format: Some(SaveFormat::Binary),
+///             ..default()
+///         },
+///     )]),
+///     ..default()
+/// };
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SaveGroup {
+    /// The directory of the savefiles of the group, by default a subdirectory of the plugin's
+    /// directory named after the group
+    pub directory: Option<PathBuf>,
+    /// The format the savefiles of the group are written in
+    pub format: Option<SaveFormat>,
+    /// When the savefiles of the group are written, unless an entity overrides it
+    pub policy: Option<SavePolicy>,
+}
+
+/// Remember the group on the [`WhereWasI`] component, so every system that saves or loads it
+/// knows where its savefile is
+fn copy_group(mut world: DeferredWorld, context: HookContext) {
+    let Some(group) = world.get::<WhereWasIGroup>(context.entity).cloned() else {
+        return;
+    };
+    if let Some(mut where_was_i) = world.get_mut::<WhereWasI>(context.entity) {
+        where_was_i.group = Some(group.0);
+    }
+}
+
+/// Move the entity back out of its group
+fn clear_group(mut world: DeferredWorld, context: HookContext) {
+    if let Some(mut where_was_i) = world.get_mut::<WhereWasI>(context.entity) {
+        where_was_i.group = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    use super::*;
+    use crate::tests::test_app;
+    use crate::{
+        SaveTrigger, SaveWhereWasI, WhereWasIConfig, WhereWasIPlugin, WhereWasISaves, read_saves,
+        read_state, save_state,
+    };
+
+    #[test]
+    fn test_group_directories() {
+        let plugin = WhereWasIPlugin {
+            groups: BTreeMap::from([
+                (
+                    "editor_ui".into(),
+                    SaveGroup {
+                        format: Some(SaveFormat::Binary),
+                        ..default()
+                    },
+                ),
+                (
+                    "shared".into(),
+                    SaveGroup {
+                        directory: Some("assets/tests/groups_shared".into()),
+                        ..default()
+                    },
+                ),
+            ]),
+            ..WhereWasIPlugin::from_directory("assets/tests/groups")
+        };
+        let config = WhereWasIConfig::from(&plugin);
+
+        let mut app = test_app(config.clone());
+        app.add_systems(Update, save_state);
+        app.world_mut().spawn(WhereWasI::camera());
+        app.world_mut().spawn((
+            WhereWasI::from_name("outliner"),
+            WhereWasIGroup::new("editor_ui"),
+        ));
+        app.world_mut().spawn((
+            WhereWasI::from_name("overview"),
+            WhereWasIGroup::new("shared"),
+        ));
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI {
+                trigger: SaveTrigger::Manual,
+                window: None,
+            });
+        app.update();
+
+        for path in [
+            "assets/tests/groups/camera.state",
+            "assets/tests/groups/editor_ui/outliner.state",
+            "assets/tests/groups_shared/overview.state",
+        ] {
+            assert_eq!(read_state(path).unwrap(), Transform::IDENTITY, "{path}");
+        }
+        assert_eq!(
+            SaveFormat::detect("assets/tests/groups/editor_ui/outliner.state").unwrap(),
+            SaveFormat::Binary
+        );
+
+        let mut app = test_app(config);
+        app.add_systems(Startup, read_saves);
+        app.update();
+        let saves = app.world().resource::<WhereWasISaves>();
+        assert_eq!(
+            saves.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["camera", "outliner", "overview"]
+        );
+
+        fs::remove_dir_all("assets/tests/groups").unwrap();
+        fs::remove_dir_all("assets/tests/groups_shared").unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+use crate::prelude::*;
+
+use crate::WhereWasI;
+
+/// The maximum number of [`Transform`]s kept per entity for undo and redo
+const MAX_HISTORY: usize = 16;
+
+/// The in-session history of restores of an entity with a [`WhereWasI`]
+/// component
+///
+/// Every restore (at startup, by resetting, recalling a bookmark, ...) and every save records the
+/// [`Transform`] the entity had at that moment, so it can be reverted with [`UndoWhereWasI`]. The
+/// history is kept in memory only, savefiles are never touched.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct WhereWasIHistory {
+    undo: Vec<Transform>,
+    redo: Vec<Transform>,
+}
+
+impl WhereWasIHistory {
+    /// Whether there is a [`Transform`] to go back to
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is an undone [`Transform`] to go forward to
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Record `current` before it is replaced by a restore
+    pub(crate) fn record(&mut self, current: Transform) {
+        if self.undo.last() != Some(&current) {
+            push_bounded(&mut self.undo, current);
+        }
+        self.redo.clear();
+    }
+
+    /// Go back to the previous [`Transform`], if any
+    fn undo(&mut self, current: Transform) -> Option<Transform> {
+        let previous = self.undo.pop()?;
+        push_bounded(&mut self.redo, current);
+        Some(previous)
+    }
+
+    /// Go forward to the last undone [`Transform`], if any
+    fn redo(&mut self, current: Transform) -> Option<Transform> {
+        let next = self.redo.pop()?;
+        push_bounded(&mut self.undo, current);
+        Some(next)
+    }
+}
+
+/// Push `transform` onto `stack`, dropping the oldest entry if it's full
+fn push_bounded(stack: &mut Vec<Transform>, transform: Transform) {
+    if stack.len() == MAX_HISTORY {
+        stack.remove(0);
+    }
+    stack.push(transform);
+}
+
+/// Apply a restored `transform` to `entity`, recording the current one in its
+/// [`WhereWasIHistory`]
+///
+/// The translation is clamped according to [`WhereWasI::clamp_translation`].
+pub(crate) fn apply_restore(mut entity: EntityWorldMut, transform: Transform) {
+    let transform = entity
+        .get::<WhereWasI>()
+        .map_or(transform, |where_was_i| where_was_i.clamp(transform));
+    if let Some(current) = entity.get::<Transform>().copied()
+        && let Some(mut history) = entity.get_mut::<WhereWasIHistory>()
+    {
+        history.record(current);
+    }
+    entity.insert(transform);
+}
+
+/// A [`Message`] that reverts the last restore of an entity
+#[derive(Message, Clone, Copy, Debug)]
+pub struct UndoWhereWasI {
+    /// The entity with a [`WhereWasI`] component
+    pub entity: Entity,
+}
+
+/// A [`Message`] that re-applies the last restore reverted with [`UndoWhereWasI`]
+#[derive(Message, Clone, Copy, Debug)]
+pub struct RedoWhereWasI {
+    /// The entity with a [`WhereWasI`] component
+    pub entity: Entity,
+}
+
+/// Handles [`UndoWhereWasI`] and [`RedoWhereWasI`] messages
+pub(crate) fn undo_redo(
+    mut undos: MessageReader<UndoWhereWasI>,
+    mut redos: MessageReader<RedoWhereWasI>,
+    mut entities: Query<(&mut WhereWasIHistory, &mut Transform)>,
+) {
+    for undo in undos.read() {
+        if let Ok((mut history, mut transform)) = entities.get_mut(undo.entity)
+            && let Some(previous) = history.undo(*transform)
+        {
+            *transform = previous;
+        }
+    }
+
+    for redo in redos.read() {
+        if let Ok((mut history, mut transform)) = entities.get_mut(redo.entity)
+            && let Some(next) = history.redo(*transform)
+        {
+            *transform = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let (a, b, c) = (
+            Transform::from_xyz(1.0, 0.0, 0.0),
+            Transform::from_xyz(2.0, 0.0, 0.0),
+            Transform::from_xyz(3.0, 0.0, 0.0),
+        );
+        let mut history = WhereWasIHistory::default();
+
+        history.record(a);
+        history.record(b);
+        assert_eq!(history.undo(c), Some(b));
+        assert_eq!(history.undo(b), Some(a));
+        assert_eq!(history.undo(a), None);
+        assert_eq!(history.redo(a), Some(b));
+        assert_eq!(history.redo(b), Some(c));
+        assert!(!history.can_redo());
+
+        history.undo(c);
+        history.record(a);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = WhereWasIHistory::default();
+        for x in 0..MAX_HISTORY + 4 {
+            history.record(Transform::from_xyz(x as f32, 0.0, 0.0));
+        }
+
+        assert_eq!(history.undo.len(), MAX_HISTORY);
+        assert_eq!(history.undo[0].translation.x, 4.0);
+    }
+}
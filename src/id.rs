@@ -0,0 +1,125 @@
+use crate::prelude::*;
+use bevy_ecs::lifecycle::HookContext;
+use bevy_ecs::world::DeferredWorld;
+use uuid::Uuid;
+
+use crate::{SaveWhereWasI, WhereWasI, WhereWasIConfig};
+
+/// A stable identity for a tracked entity, used instead of the name of its [`WhereWasI`]
+///
+/// The savefile of the entity is named after the UUID, so it is matched with the entity by ID
+/// rather than by a hand-written name. This only works if the ID itself survives the session, for
+/// example in a scene or by respawning the entity with
+/// [`WhereWasIPlugin::respawn_entity`](crate::WhereWasIPlugin::respawn_entity).
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasI, WhereWasIId};
+/// use uuid::uuid;
+///
+/// fn spawn_marker(mut commands: Commands) {
+///     commands.spawn((
+///         WhereWasI::from_name("marker"),
+///         WhereWasIId(uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8")),
+///     ));
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[component(on_insert = use_id_as_name)]
+pub struct WhereWasIId(pub Uuid);
+
+impl WhereWasIId {
+    /// A new random ID
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    /// The ID in the name of a savefile, if it is named after one
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Uuid::try_parse(name).ok().map(Self)
+    }
+}
+
+/// Name the savefile of the entity after its ID
+fn use_id_as_name(mut world: DeferredWorld, context: HookContext) {
+    let Some(id) = world.get::<WhereWasIId>(context.entity).copied() else {
+        return;
+    };
+    if let Some(mut where_was_i) = world.get_mut::<WhereWasI>(context.entity) {
+        where_was_i.name = id.0.hyphenated().to_string();
+    }
+}
+
+/// Give every tracked entity without a [`WhereWasIId`] a new one when it's saved for the first
+/// time, see [`WhereWasIPlugin::generate_ids`](crate::WhereWasIPlugin::generate_ids)
+pub(crate) fn generate_ids(
+    mut commands: Commands,
+    requests: MessageReader<SaveWhereWasI>,
+    without_id: Query<(Entity, &WhereWasI), Without<WhereWasIId>>,
+    config: Res<WhereWasIConfig>,
+) {
+    if !config.generate_ids || requests.is_empty() {
+        return;
+    }
+
+    for (entity, where_was_i) in &without_id {
+        let id = WhereWasIId::generate();
+        info!("Assigned ID {} to {}", id.0, where_was_i.name);
+        commands.entity(entity).insert(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{SaveTrigger, save_state};
+
+    #[test]
+    fn test_id_names_savefile() {
+        let mut app = test_app(test_config("assets/tests/ids"));
+        let id = WhereWasIId::generate();
+        let entity = app
+            .world_mut()
+            .spawn((WhereWasI::from_name("marker"), id))
+            .id();
+
+        assert_eq!(
+            app.world().get::<WhereWasI>(entity).unwrap().name,
+            id.0.hyphenated().to_string()
+        );
+        assert_eq!(
+            WhereWasIId::from_name(&app.world().get::<WhereWasI>(entity).unwrap().name),
+            Some(id)
+        );
+        assert_eq!(WhereWasIId::from_name("marker"), None);
+    }
+
+    #[test]
+    fn test_generate_ids() {
+        let mut app = test_app(WhereWasIConfig {
+            generate_ids: true,
+            ..test_config("assets/tests/generate_ids")
+        });
+        app.add_systems(Update, (generate_ids, save_state).chain());
+        let entity = app.world_mut().spawn(WhereWasI::camera()).id();
+
+        app.update();
+        assert!(app.world().get::<WhereWasIId>(entity).is_none());
+
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI {
+                trigger: SaveTrigger::Manual,
+                window: None,
+            });
+        app.update();
+
+        let id = app.world().get::<WhereWasIId>(entity).unwrap().0;
+        assert!(fs::exists(format!("assets/tests/generate_ids/{id}.state")).unwrap());
+
+        fs::remove_dir_all("assets/tests/generate_ids").unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use crate::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::timestamp::format_timestamp;
+use crate::{ResetWhereWasI, WhereWasIStore};
+
+/// A plugin that shows an egui window listing all tracked entities, with buttons to save, load
+/// and reset each of them
+///
+/// Requires the `inspector` feature, and [`EguiPlugin`](bevy_egui::EguiPlugin) to be added
+/// (which is already the case when using `bevy-inspector-egui`). Add it next to
+/// [`WhereWasIPlugin`](crate::WhereWasIPlugin):
+///
+/// ```rust,ignore
+/// use bevy::prelude::*;
+/// use bevy_egui::EguiPlugin;
+/// use bevy_where_was_i::{WhereWasIInspectorPlugin, WhereWasIPlugin};
+///
+/// App::new()
+///     .add_plugins((DefaultPlugins, EguiPlugin::default()))
+///     .add_plugins((WhereWasIPlugin::default(), WhereWasIInspectorPlugin));
+/// ```
+pub struct WhereWasIInspectorPlugin;
+
+impl Plugin for WhereWasIInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(EguiPrimaryContextPass, inspector_window);
+    }
+}
+
+/// What to do with an entity after a button in the inspector window was clicked
+enum Action {
+    Save(Entity, String),
+    Load(Entity, String),
+    Reset(Entity),
+}
+
+/// Draw the inspector window and handle its buttons
+fn inspector_window(
+    mut contexts: EguiContexts,
+    mut store: WhereWasIStore,
+    mut resets: MessageWriter<ResetWhereWasI>,
+) -> Result {
+    let names = store.tracked_names();
+    let mut actions = Vec::new();
+
+    egui::Window::new("Where was I").show(contexts.ctx_mut()?, |ui| {
+        egui::Grid::new("where_was_i_entities")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Name");
+                ui.strong("Last saved");
+                ui.end_row();
+
+                for (entity, name) in names {
+                    let last_saved = store
+                        .get(&name)
+                        .and_then(|record| record.modified)
+                        .map_or_else(|| "never".into(), format_timestamp);
+
+                    ui.label(&name);
+                    ui.label(last_saved);
+                    if ui.button("Save").clicked() {
+                        actions.push(Action::Save(entity, name.clone()));
+                    }
+                    if ui.button("Load").clicked() {
+                        actions.push(Action::Load(entity, name.clone()));
+                    }
+                    if ui.button("Reset").clicked() {
+                        actions.push(Action::Reset(entity));
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+
+    for action in actions {
+        match action {
+            Action::Save(entity, name) => {
+                if let Err(err) = store.save_entity(entity) {
+                    error!("Could not save {}: {}", name, err);
+                }
+            }
+            Action::Load(entity, name) => {
+                if let Err(err) = store.reload_entity(entity) {
+                    error!("Could not load {}: {}", name, err);
+                }
+            }
+            Action::Reset(entity) => {
+                resets.write(ResetWhereWasI { entity });
+            }
+        }
+    }
+    Ok(())
+}
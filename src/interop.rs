@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use crate::prelude::*;
+
+use crate::WhereWasIStore;
+use crate::history::apply_restore;
+
+/// The [`Transform`]s of all tracked entities, keyed by the name they are saved under
+///
+/// This is what would be written to disk when the application closes. It implements [`Reflect`]
+/// (and `Serialize` and `Deserialize` with the `serde` feature), so it can be embedded in a larger
+/// snapshot of the world, for example one made with `bevy_save`. Combine it with
+/// [`WhereWasIPlugin::restore_on_startup`](crate::WhereWasIPlugin::restore_on_startup) to let
+/// the other crate decide when entities are restored.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasIRecords, WhereWasIStore};
+///
+/// fn capture(store: WhereWasIStore, mut commands: Commands) {
+///     commands.insert_resource(store.records());
+/// }
+///
+/// fn apply(mut store: WhereWasIStore, records: Res<WhereWasIRecords>) {
+///     store.apply_records(&records);
+/// }
+/// ```
+#[derive(Resource, Reflect, Clone, Debug, Default, PartialEq)]
+#[reflect(Resource, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WhereWasIRecords {
+    /// The [`Transform`] of every tracked entity, keyed by name
+    pub transforms: BTreeMap<String, Transform>,
+}
+
+impl WhereWasIStore<'_, '_> {
+    /// The current [`Transform`]s of all tracked entities, see [`WhereWasIRecords`]
+    pub fn records(&self) -> WhereWasIRecords {
+        let transforms = self
+            .tracked_names()
+            .into_iter()
+            .filter_map(|(entity, name)| {
+                let (_, _, transform) = self.tracked.get(entity).ok()?;
+                Some((name, *transform))
+            })
+            .collect();
+
+        WhereWasIRecords { transforms }
+    }
+
+    /// Restore tracked entities from externally loaded `records`
+    ///
+    /// Tracked entities without a record are left untouched, savefiles are not written. The
+    /// transforms are applied when the commands of the current system are applied.
+    ///
+    /// Returns the number of entities that were restored.
+    pub fn apply_records(&mut self, records: &WhereWasIRecords) -> usize {
+        let mut applied = 0;
+
+        for (entity, name) in self.tracked_names() {
+            if let Some(&transform) = records.transforms.get(&name) {
+                self.commands
+                    .entity(entity)
+                    .queue(move |entity: EntityWorldMut| apply_restore(entity, transform));
+                applied += 1;
+            }
+        }
+        applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::tests::test_config;
+    use crate::{WhereWasI, WhereWasISaves};
+
+    #[test]
+    fn test_records_round_trip() {
+        let mut world = World::new();
+        world.insert_resource(test_config("assets/tests/records"));
+        world.init_resource::<WhereWasISaves>();
+
+        let camera = world
+            .spawn((WhereWasI::camera(), Transform::from_xyz(1.0, 2.0, 3.0)))
+            .id();
+        let light = world
+            .spawn((
+                WhereWasI::from_name("light"),
+                Transform::from_xyz(4.0, 5.0, 6.0),
+            ))
+            .id();
+
+        let records = world
+            .run_system_once(|store: WhereWasIStore| store.records())
+            .unwrap();
+        assert_eq!(
+            records.transforms.keys().collect::<Vec<_>>(),
+            vec!["camera", "light"]
+        );
+
+        world.entity_mut(camera).insert(Transform::IDENTITY);
+        world.entity_mut(light).insert(Transform::IDENTITY);
+        let mut partial = records.clone();
+        partial.transforms.remove("light");
+
+        let applied = world
+            .run_system_once(move |mut store: WhereWasIStore| store.apply_records(&partial))
+            .unwrap();
+
+        assert_eq!(applied, 1);
+        assert_eq!(
+            *world.entity(camera).get::<Transform>().unwrap(),
+            records.transforms["camera"]
+        );
+        assert_eq!(
+            *world.entity(light).get::<Transform>().unwrap(),
+            Transform::IDENTITY
+        );
+    }
+}
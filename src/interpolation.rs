@@ -0,0 +1,72 @@
+use crate::prelude::*;
+use bevy_transform_interpolation::{RotationEasingState, ScaleEasingState, TranslationEasingState};
+
+use crate::WhereWasILoaded;
+
+/// The easing state of the interpolation components an entity may have
+type EasingStates = (
+    Option<&'static mut TranslationEasingState>,
+    Option<&'static mut RotationEasingState>,
+    Option<&'static mut ScaleEasingState>,
+);
+
+/// Ease restored entities from and to their restored [`Transform`]
+///
+/// Without this, bevy_transform_interpolation keeps easing from the pose the entity was spawned
+/// with until the next fixed timestep, which shows as a snap back for one frame.
+pub(crate) fn reset_easing(
+    mut loaded: MessageReader<WhereWasILoaded>,
+    mut easing: Query<EasingStates>,
+) {
+    for loaded in loaded.read() {
+        let Ok((translation, rotation, scale)) = easing.get_mut(loaded.entity) else {
+            continue;
+        };
+        let restored = loaded.restored;
+
+        if let Some(mut translation) = translation {
+            translation.start = Some(restored.translation);
+            translation.end = Some(restored.translation);
+        }
+        if let Some(mut rotation) = rotation {
+            rotation.start = Some(restored.rotation);
+            rotation.end = Some(restored.rotation);
+        }
+        if let Some(mut scale) = scale {
+            scale.start = Some(restored.scale);
+            scale.end = Some(restored.scale);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{WhereWasI, load_state, read_saves};
+
+    #[test]
+    fn test_reset_easing() {
+        let mut app = test_app(test_config("assets/tests"));
+        app.add_systems(Update, (read_saves, load_state, reset_easing).chain());
+        let camera = app
+            .world_mut()
+            .spawn((
+                WhereWasI::camera(),
+                TranslationEasingState {
+                    start: Some(Vec3::ZERO),
+                    end: Some(Vec3::ZERO),
+                },
+                RotationEasingState::default(),
+            ))
+            .id();
+        app.update();
+
+        let restored = *app.world().get::<Transform>(camera).unwrap();
+        let translation = app.world().get::<TranslationEasingState>(camera).unwrap();
+        assert_eq!(translation.start, Some(restored.translation));
+        assert_eq!(translation.end, Some(restored.translation));
+        let rotation = app.world().get::<RotationEasingState>(camera).unwrap();
+        assert_eq!(rotation.end, Some(restored.rotation));
+    }
+}
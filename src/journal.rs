@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::prelude::*;
+
+use crate::names::resolve_names;
+use crate::replay::{read_samples, write_sample};
+use crate::{PersistenceUnavailable, WhereWasI, WhereWasIConfig, write_state};
+
+/// The path of the crash-recovery journal in `directory`
+fn journal_path(directory: &Path) -> PathBuf {
+    directory.join("recovery.journal")
+}
+
+/// Appends the latest [`Transform`] of every tracked entity that moved to the crash-recovery
+/// journal, see [`WhereWasIPlugin::journal`](crate::WhereWasIPlugin::journal)
+#[derive(Resource)]
+pub(crate) struct Journal {
+    timer: Timer,
+    writer: Option<BufWriter<File>>,
+    last: HashMap<String, Transform>,
+}
+
+impl Journal {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval, TimerMode::Repeating),
+            writer: None,
+            last: HashMap::new(),
+        }
+    }
+
+    /// Append the transforms that changed since they were last journaled
+    ///
+    /// The journal is created on the first call, replacing the journal of a previous session that
+    /// has been recovered by then.
+    fn append(
+        &mut self,
+        directory: &Path,
+        transforms: impl IntoIterator<Item = (String, Transform)>,
+    ) -> io::Result<usize> {
+        let writer = match self.writer {
+            Some(ref mut writer) => writer,
+            None => {
+                fs::create_dir_all(directory)?;
+                let mut writer = BufWriter::new(File::create(journal_path(directory))?);
+                writer.write_all(b"v0\n")?;
+                self.writer.insert(writer)
+            }
+        };
+
+        let mut written = 0;
+        for (name, transform) in transforms {
+            if self.last.get(&name) == Some(&transform) {
+                continue;
+            }
+            write_sample(writer, 0.0, &name, &transform)?;
+            self.last.insert(name, transform);
+            written += 1;
+        }
+        writer.flush()?;
+        writer.get_ref().sync_data()?;
+
+        Ok(written)
+    }
+}
+
+/// Write the transforms in the journal of a previous session that didn't shut down cleanly into
+/// proper savefiles
+///
+/// This runs before the savefiles are read, so the recovered transforms are restored like any
+/// other savefile. The journal is removed afterwards.
+pub(crate) fn recover_journal(
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let path = journal_path(&config.directory);
+    if unavailable.is_some() || config.dry_run || !path.is_file() {
+        return;
+    }
+
+    let samples = match read_samples(&path) {
+        Ok(samples) => samples,
+        Err(err) => {
+            error!("Could not read the crash-recovery journal: {}", err);
+            return;
+        }
+    };
+
+    let latest: HashMap<String, Transform> = samples
+        .into_iter()
+        .map(|sample| (sample.name, sample.transform))
+        .collect();
+    warn!(
+        "The previous session didn't shut down cleanly, recovering {} transform(s) from the journal",
+        latest.len()
+    );
+    for (name, transform) in &latest {
+        if let Err(err) = write_state(&config, name, transform) {
+            error!("Could not recover the transform of {}: {}", name, err);
+        }
+    }
+
+    if let Err(err) = fs::remove_file(&path) {
+        error!("Could not remove the crash-recovery journal: {}", err);
+    }
+}
+
+/// Append the transforms of tracked entities to the journal at its interval
+///
+/// Journaling stops at the first error.
+pub(crate) fn write_journal(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut journal: ResMut<Journal>,
+    tracked: Query<(Entity, &WhereWasI, &Transform)>,
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    if !journal.timer.tick(time.delta()).just_finished() || unavailable.is_some() || config.dry_run
+    {
+        return;
+    }
+
+    let transforms: HashMap<Entity, Transform> = tracked
+        .iter()
+        .map(|(entity, _, transform)| (entity, *transform))
+        .collect();
+    let entries = resolve_names(
+        tracked
+            .iter()
+            .filter(|(_, where_was_i, _)| !where_was_i.follows)
+            .map(|(entity, where_was_i, _)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    )
+    .into_iter()
+    .map(|(entity, name)| (name, transforms[&entity]));
+
+    match journal.append(&config.directory, entries) {
+        Ok(written) => debug!("Journaled {} transform(s)", written),
+        Err(err) => {
+            error!(
+                "Could not write the crash-recovery journal, disabling it: {}",
+                err
+            );
+            commands.remove_resource::<Journal>();
+        }
+    }
+}
+
+/// Remove the journal when the application exits cleanly, after the final save
+pub(crate) fn close_journal(
+    mut exits: MessageReader<AppExit>,
+    mut journal: ResMut<Journal>,
+    config: Res<WhereWasIConfig>,
+) {
+    if exits.is_empty() {
+        return;
+    }
+    exits.clear();
+
+    if journal.writer.take().is_some()
+        && let Err(err) = fs::remove_file(journal_path(&config.directory))
+    {
+        error!("Could not remove the crash-recovery journal: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{WhereWasISaves, read_saves, read_state};
+
+    #[test]
+    fn test_journal_recovery() {
+        let config = test_config("assets/tests/journal");
+        let mut app = test_app(config.clone());
+        app.init_resource::<Time>()
+            .insert_resource(Journal::new(Duration::from_secs(1)))
+            .add_systems(Update, write_journal);
+        let camera = app.world_mut().spawn(WhereWasI::camera()).id();
+
+        for x in [1.0, 2.0] {
+            app.world_mut()
+                .get_mut::<Transform>(camera)
+                .unwrap()
+                .translation
+                .x = x;
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(1));
+            app.update();
+        }
+        assert!(journal_path(&config.directory).is_file());
+        // Simulate a crash by dropping the app without exiting
+        drop(app);
+
+        let mut app = test_app(config.clone());
+        app.add_systems(PreStartup, (recover_journal, read_saves).chain());
+        app.update();
+
+        assert_eq!(
+            read_state("assets/tests/journal/camera.state").unwrap(),
+            Transform::from_xyz(2.0, 0.0, 0.0)
+        );
+        assert!(app.world().resource::<WhereWasISaves>().contains("camera"));
+        assert!(!journal_path(&config.directory).exists());
+
+        fs::remove_dir_all("assets/tests/journal").unwrap();
+    }
+
+    #[test]
+    fn test_close_journal() {
+        let config = test_config("assets/tests/close_journal");
+        let mut app = test_app(config.clone());
+        app.init_resource::<Time>()
+            .add_message::<AppExit>()
+            .insert_resource(Journal::new(Duration::from_secs(1)))
+            .add_systems(Update, write_journal)
+            .add_systems(Last, close_journal);
+        app.world_mut().spawn(WhereWasI::camera());
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(1));
+        app.update();
+        assert!(journal_path(&config.directory).is_file());
+
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+        assert!(!journal_path(&config.directory).exists());
+
+        fs::remove_dir_all("assets/tests/close_journal").unwrap();
+    }
+}
@@ -1,16 +1,164 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+// Lets `#[derive(Persist)]` refer to this crate by name in its own tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as bevy_where_was_i;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::{self, BufRead};
-use std::path::Path;
-use std::{fs::File, io::BufWriter};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::prelude::*;
+use archive::archive_state;
+use authored::record_authored;
+use autosave::{Autosave, SaveSink, finish_autosave, save_entries, tick_autosave, write_states};
+use bevy_ecs::message::MessageCursor;
+use bevy_ecs::system::RunSystemOnce;
+use bevy_math::bounding::Aabb3d;
+use bevy_platform::time::Instant;
+#[cfg(feature = "window")]
+use bevy_window::{AppLifecycle, PrimaryWindow, WindowClosing};
+use content::stale_content;
+use diagnostics::SaveMetrics;
+use diff::log_diff;
+use directory::{resolve_directory, user_data_directory};
+use history::undo_redo;
+use journal::{Journal, close_journal, recover_journal, write_journal};
+use logging::log_summary;
+use names::{resolve_tracked_names, validate_name};
+use persistence::check_persistence;
+use policy::save_by_policy;
+use position::{restore_positions, save_positions};
+use rename::handle_renames;
+use replay::{ReplayPlayback, ReplayRecorder, play_replay, record_replay, start_replay};
+use respawn::respawn_entities;
+use rules::follow_names;
+use schema::{check_schema, save_schema};
+use scope::{LevelScope, apply_level};
+use session::{detect_first_run, read_session};
+use snapshots::{restore_snapshot, rotate_snapshots};
+use throttle::{RequestedSave, SaveAdmitted, SaveThrottle};
+#[cfg(feature = "window")]
+use windows::{
+    SavedWindows, read_saved_windows, restore_windows, track_primary_window, track_windows,
+};
 
-use bevy::prelude::*;
-use bevy::window::WindowClosing;
-use serialization::{deserialize_transform, serialize_transform};
+pub use app::WhereWasIAppExt;
+pub use archive::ArchivePolicy;
+pub use authored::{RestorePolicy, WhereWasIAuthored};
+pub use autosave::WhereWasISaveCompleted;
+#[cfg(feature = "derive")]
+pub use bevy_where_was_i_derive::Persist;
+pub use condition::RestoreCondition;
+#[cfg(feature = "console")]
+pub use console::WhereWasIConsolePlugin;
+pub use content::{StaleContentPolicy, WhereWasIContentHash, WhereWasIStaleContent};
+pub use convert::convert;
+pub use diagnostics::WhereWasIDiagnosticsPlugin;
+pub use diff::{LogWhereWasIDiff, TransformDiff};
+pub use directory::{CONFIG_FILE, DirectorySource, PlatformDirectories, SAVE_DIR_ENV};
+pub use groups::{SaveGroup, WhereWasIGroup};
+pub use history::{RedoWhereWasI, UndoWhereWasI, WhereWasIHistory};
+#[cfg(feature = "uuid")]
+pub use id::WhereWasIId;
+#[cfg(feature = "inspector")]
+pub use inspector::WhereWasIInspectorPlugin;
+pub use interop::WhereWasIRecords;
+pub use logging::{SUMMARY_LOG_TARGET, SummaryLogLevel};
+#[cfg(feature = "mmap")]
+pub use mapped::WhereWasIArchive;
+pub use names::{InvalidNameError, NameCollisionPolicy};
+#[cfg(feature = "debug-ui")]
+pub use overlay::WhereWasIDebugOverlayPlugin;
+pub use persist::{Persist, PersistValue, Persistable, PersistedFields};
+pub use persistence::{
+    PersistenceUnavailable, WhereWasIDirectoryFallback, WhereWasIDirectoryProbe,
+};
+pub use policy::SavePolicy;
+pub use position::WhereWasIPosition;
+pub use rename::RenamePolicy;
+pub use replay::PlayWhereWasIReplay;
+pub use respawn::EntitySpawner;
+pub use rules::WhereWasISkip;
+pub use saves::{SaveRecord, WhereWasISaves};
+pub use schema::{SCHEMA_FILE, SchemaChange, SchemaEntry, SchemaKind, WhereWasISchema};
+pub use scope::WhereWasILevel;
+pub use serialization::{Field, SaveFormat, WhereWasIParseError};
+pub use session::{
+    SaveTrigger, SessionMetadata, WhereWasIFirstRun, WhereWasISession, WhereWasIStarted,
+};
+pub use snapshots::RestoreWhereWasISnapshot;
+pub use store::{WhereWasIReloadError, WhereWasIStore};
+pub use throttle::SaveRateLimit;
+pub use validation::InvalidValuePolicy;
+#[cfg(feature = "window")]
+pub use windows::{WhereWasIWindow, WindowSpawner};
 
+mod app;
+mod archive;
+mod authored;
+mod autosave;
+mod bookmarks;
+#[cfg(feature = "camera-settings")]
+mod camera;
+mod condition;
+#[cfg(feature = "console")]
+mod console;
+mod content;
+mod convert;
+mod diagnostics;
+mod diff;
+mod directory;
+mod export;
+#[cfg(feature = "big_space")]
+mod grid;
+mod groups;
+mod history;
+#[cfg(feature = "uuid")]
+mod id;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod interop;
+#[cfg(feature = "interpolation")]
+mod interpolation;
+mod journal;
+mod logging;
+#[cfg(feature = "mmap")]
+mod mapped;
+mod names;
+#[cfg(feature = "debug-ui")]
+mod overlay;
+mod persist;
+mod persistence;
+mod policy;
+mod position;
+mod prelude;
+mod rename;
+mod replay;
+mod respawn;
+mod rules;
+mod saves;
+mod schema;
+mod scope;
 mod serialization;
+mod session;
+mod snapshots;
+mod storage;
+mod store;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod throttle;
+mod timestamp;
+mod validation;
+#[cfg(feature = "viewports")]
+mod viewport;
+#[cfg(feature = "web")]
+mod web;
+#[cfg(feature = "window")]
+mod windows;
 
 /// A component that saves a [`Transform`] to disk and restores it when you reopn the application.
 ///
@@ -22,16 +170,61 @@ mod serialization;
 ///
 /// WhereWasI::from_name("my_entity");
 /// ```
-#[derive(Component)]
-#[require(Transform)]
+///
+/// It is registered for reflection by [`WhereWasIPlugin`], so it shows up in reflection-based
+/// tooling such as inspectors and can be included in `DynamicScene`s.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[require(Transform, WhereWasIHistory)]
+#[component(on_add = record_authored)]
 pub struct WhereWasI {
     name: String,
+    default: Option<Transform>,
+    bounds: Option<Aabb3d>,
+    window: Option<Entity>,
+    group: Option<String>,
+    policy: Option<SavePolicy>,
+    follows: bool,
+    scope: Option<LevelScope>,
+    #[reflect(ignore)]
+    condition: Option<RestoreCondition>,
+    ttl: Option<Duration>,
 }
 
 impl WhereWasI {
-    /// Construct a [`WhereWasI`] plugin with a name
-    pub fn from_name(name: &str) -> Self {
-        Self { name: name.into() }
+    /// Construct a [`WhereWasI`] component with a name
+    pub fn from_name(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            default: None,
+            bounds: None,
+            window: None,
+            group: None,
+            policy: None,
+            follows: false,
+            scope: None,
+            condition: None,
+            ttl: None,
+        }
+    }
+
+    /// Construct a [`WhereWasI`] component that restores from the savefile of the entity named
+    /// `name`, but never writes it
+    ///
+    /// This is useful for a minimap or picture-in-picture camera that should start where the main
+    /// camera was left. Followers don't take part in
+    /// [`WhereWasIPlugin::name_collisions`], so the entity that writes the savefile keeps its name.
+    ///
+    /// ```rust
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// WhereWasI::follow("camera");
+    /// ```
+    pub fn follow(name: impl Into<String>) -> Self {
+        Self {
+            follows: true,
+            ..Self::from_name(name)
+        }
     }
 
     /// A shorthand used for cameras
@@ -45,180 +238,2840 @@ impl WhereWasI {
     pub fn camera() -> Self {
         WhereWasI::from_name("camera")
     }
+
+    /// Use `transform` as the starting pose when no savefile could be restored
+    ///
+    /// This is useful for the first run of an application: the authored pose is used until a
+    /// savefile exists, after which the saved pose takes precedence.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// WhereWasI::camera()
+    ///     .or_default(Transform::from_xyz(10.0, 10.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y));
+    /// ```
+    pub fn or_default(mut self, transform: Transform) -> Self {
+        self.default = Some(transform);
+        self
+    }
+
+    /// Keep the translation of restored savefiles within `bounds`
+    ///
+    /// This prevents a savefile created in an older, larger version of a level from placing the
+    /// entity outside the playable volume of the current one.
+    ///
+    /// ```rust
+    /// use bevy::math::bounding::Aabb3d;
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// WhereWasI::camera().clamp_translation(Aabb3d::new(Vec3::ZERO, Vec3::new(50.0, 20.0, 50.0)));
+    /// ```
+    pub fn clamp_translation(mut self, bounds: Aabb3d) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Bind the entity to the window `window`
+    ///
+    /// When a secondary window closes, only the entities bound to it are saved. Closing the
+    /// primary window still saves all entities. This is useful in multi-window editors, where
+    /// closing a detached panel shouldn't save the state of every other window.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// fn spawn_preview(mut commands: Commands) {
+    ///     let window = commands.spawn(Window::default()).id();
+    ///     commands.spawn((Camera3d::default(), WhereWasI::from_name("preview").in_window(window)));
+    /// }
+    /// ```
+    pub fn in_window(mut self, window: Entity) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// Also save the entity as soon as its [`Transform`] differs from its savefile, see
+    /// [`SavePolicy::OnChange`]
+    ///
+    /// ```rust
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// WhereWasI::from_name("marker").save_on_change();
+    /// ```
+    pub fn save_on_change(mut self) -> Self {
+        self.policy = Some(SavePolicy::OnChange);
+        self
+    }
+
+    /// Never autosave the entity, see [`SavePolicy::ExitOnly`]
+    pub fn exit_only(mut self) -> Self {
+        self.policy = Some(SavePolicy::ExitOnly);
+        self
+    }
+
+    /// Autosave the entity with `interval` instead of the interval of the plugin, see
+    /// [`SavePolicy::Autosave`]
+    pub fn autosave_every(mut self, interval: Duration) -> Self {
+        self.policy = Some(SavePolicy::Autosave(interval));
+        self
+    }
+
+    /// Remember a separate pose for every level, by saving the entity under its name plus the
+    /// current [`WhereWasILevel`]
+    ///
+    /// When the level changes, the entity is saved for the level it leaves and restored from the
+    /// savefile of the level it enters, or reverted to its authored [`Transform`] if it was never
+    /// saved there.
+    ///
+    /// ```rust
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// WhereWasI::camera().scoped_by_level();
+    /// ```
+    pub fn scoped_by_level(mut self) -> Self {
+        self.scope = Some(LevelScope {
+            name: self.name.clone(),
+            level: None,
+        });
+        self
+    }
+
+    /// Only restore the savefile when `condition` holds, otherwise keep the authored [`Transform`]
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use bevy_where_was_i::{RestoreCondition, WhereWasI};
+    ///
+    /// WhereWasI::camera().restore_if(RestoreCondition::MaxAge(Duration::from_secs(24 * 60 * 60)));
+    /// ```
+    pub fn restore_if(mut self, condition: RestoreCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Only restore the savefile if it was written less than `ttl` ago, otherwise keep the authored
+    /// [`Transform`]
+    ///
+    /// This keeps ephemeral entities, such as debug probes, from resurrecting their position days
+    /// later, while other entities are restored no matter how old their savefile is. It applies on
+    /// top of [`restore_if`](Self::restore_if) and [`WhereWasIPlugin::max_age`].
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// WhereWasI::from_name("debug_probe").ttl(Duration::from_secs(24 * 60 * 60));
+    /// ```
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// The name the entity is saved under
+    ///
+    /// For entities created with [`WhereWasI::scoped_by_level`], this is the name without the
+    /// level.
+    ///
+    /// ```rust
+    /// use bevy_where_was_i::WhereWasI;
+    ///
+    /// let mut where_was_i = WhereWasI::camera();
+    /// assert_eq!(where_was_i.name(), "camera");
+    ///
+    /// where_was_i.set_name("overview");
+    /// assert_eq!(where_was_i.name(), "overview");
+    /// ```
+    pub fn name(&self) -> &str {
+        match &self.scope {
+            Some(scope) => &scope.name,
+            None => &self.name,
+        }
+    }
+
+    /// Change the name the entity is saved under
+    ///
+    /// The existing savefile is moved or deleted according to
+    /// [`WhereWasIPlugin::rename_policy`].
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+        if let Some(scope) = &mut self.scope {
+            scope.name.clone_from(&self.name);
+            self.name = scope.key();
+        }
+    }
+
+    /// `transform` with its translation clamped to the bounds set with
+    /// [`WhereWasI::clamp_translation`]
+    pub(crate) fn clamp(&self, mut transform: Transform) -> Transform {
+        if let Some(bounds) = self.bounds {
+            transform.translation = transform
+                .translation
+                .clamp(bounds.min.into(), bounds.max.into());
+        }
+        transform
+    }
 }
 
-/// A [`Resource`] to store the `directory` in so we can access in the systems of this plugin.
-#[derive(Resource)]
-struct WhereWasIConfig {
-    directory: String,
+/// Marks entities that [`load_state`] has handled, whether or not they had a savefile
+#[derive(Component, Clone, Copy)]
+struct Initialized;
+
+/// A [`Message`] that deletes the savefile of an entity and reverts its [`Transform`] to the one it
+/// was spawned with (or the one passed to [`WhereWasI::or_default`])
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{ResetWhereWasI, WhereWasI};
+///
+/// fn reset_view(mut resets: MessageWriter<ResetWhereWasI>, cameras: Query<Entity, With<WhereWasI>>) {
+///     for entity in cameras.iter() {
+///         resets.write(ResetWhereWasI { entity });
+///     }
+/// }
+/// ```
+#[derive(Message, Clone, Copy, Debug)]
+pub struct ResetWhereWasI {
+    /// The entity with a [`WhereWasI`] component to reset
+    pub entity: Entity,
 }
 
-/// Plugin that saves the [`Transform`] state after closing a Bevy application, and restores it
-/// when launching the application again.
-pub struct WhereWasIPlugin {
-    /// The directory where savefiles will be stored and loaded from
-    pub directory: String,
+/// A [`Message`] that saves the [`Transform`] of all tracked entities
+///
+/// The plugin writes one when a window closes, or when an autosave is due. Several requests in the
+/// same frame are coalesced into a single save, recorded with the most important [`SaveTrigger`].
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::SaveWhereWasI;
+///
+/// fn save_on_checkpoint(mut requests: MessageWriter<SaveWhereWasI>) {
+///     requests.write(SaveWhereWasI::default());
+/// }
+/// ```
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct SaveWhereWasI {
+    /// What caused the save
+    pub trigger: SaveTrigger,
+    /// Only save the entities bound to this window with [`WhereWasI::in_window`], or all entities
+    /// if `None`
+    pub window: Option<Entity>,
 }
 
-impl Default for WhereWasIPlugin {
-    fn default() -> Self {
-        Self {
-            directory: "./assets/saves".into(),
-        }
-    }
+/// Which closing windows request a save, see [`WhereWasIPlugin::save_on_close`]
+#[cfg(feature = "window")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaveOnClose {
+    /// Closing any window saves, a secondary window only saves the entities bound to it
+    #[default]
+    AnyWindow,
+    /// Only closing the [`PrimaryWindow`] saves
+    PrimaryWindow,
+    /// Only closing this window saves
+    ///
+    /// Window entities don't exist yet when the plugin is built, so set this with
+    /// [`WhereWasIConfig::set_save_on_close`] after spawning the window.
+    Window(Entity),
 }
 
-impl Plugin for WhereWasIPlugin {
-    fn build(&self, app: &mut App) {
-        app.insert_resource(WhereWasIConfig {
-            directory: self.directory.clone(),
-        })
-        .add_systems(Update, save_state)
-        .add_systems(PostStartup, load_state);
-    }
+/// A [`Message`] written when the [`Transform`] of an entity could not be saved
+#[derive(Message, Debug)]
+pub struct WhereWasISaveFailed {
+    /// The name of the [`WhereWasI`] component
+    pub name: String,
+    /// The error that occurred while writing the savefile
+    pub error: io::Error,
 }
 
-/// Read file `filename` line-by-line
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+/// A [`Message`] written for every entity whose [`Transform`] was restored from a savefile
+#[derive(Message, Clone, Debug)]
+pub struct WhereWasILoaded {
+    /// The entity that was restored
+    pub entity: Entity,
+    /// The name of the [`WhereWasI`] component
+    pub name: String,
+    /// The [`Transform`] the entity had before it was restored
+    pub previous: Transform,
+    /// The [`Transform`] the entity was restored to
+    pub restored: Transform,
+    /// What caused the savefile to be written, see [`SaveRecord::trigger`]
+    pub trigger: Option<SaveTrigger>,
 }
 
-/// Load the state of all [`Transform`]s belonging to [`WhereWasI`] components
-fn load_state(mut to_save: Query<(&WhereWasI, &mut Transform)>, config: Res<WhereWasIConfig>) {
-    let mut initialized = 0;
+/// An [`EntityEvent`] triggered on every entity right after it was restored from its savefile
+///
+/// This is the observer counterpart of [`WhereWasILoaded`], to react to the restore of a single
+/// entity without reading messages every frame.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasI, WhereWasIRestored};
+///
+/// fn spawn_camera(mut commands: Commands) {
+///     commands
+///         .spawn(WhereWasI::camera())
+///         .observe(|restored: On<WhereWasIRestored>| {
+///             info!("Camera restored to {}", restored.restored.translation);
+///         });
+/// }
+/// ```
+#[derive(EntityEvent, Clone, Debug)]
+pub struct WhereWasIRestored {
+    /// The entity that was restored
+    pub entity: Entity,
+    /// The name of the [`WhereWasI`] component
+    pub name: String,
+    /// The [`Transform`] the entity had before it was restored
+    pub previous: Transform,
+    /// The [`Transform`] the entity was restored to
+    pub restored: Transform,
+}
 
-    for (where_was_i, mut transform) in to_save.iter_mut() {
-        let (directory, filename) = (&config.directory, &where_was_i.name);
-        let filepath = format!("{directory}/{filename}.state");
+/// An [`EntityEvent`] triggered on every entity whose savefile was written
+///
+/// This is the observer counterpart of [`WhereWasISaveCompleted`], it isn't triggered in a
+/// [`dry_run`](WhereWasIPlugin::dry_run).
+#[derive(EntityEvent, Clone, Debug)]
+pub struct WhereWasIPersisted {
+    /// The entity that was saved
+    pub entity: Entity,
+    /// The name of its savefile
+    pub name: String,
+    /// The [`Transform`] that was saved
+    pub transform: Transform,
+}
 
-        if let Ok(contents) = read_lines(filepath) {
-            match deserialize_transform(contents) {
-                Ok(new) => {
-                    *transform = new;
-                    initialized += 1;
-                }
-                Err(err) => {
-                    error!("Could not deserialize transform: {}", err.message);
-                }
+/// A [`Message`] with a summary of every pass that restored newly spawned entities, written after
+/// their [`WhereWasILoaded`] and [`WhereWasILoadFailed`] messages
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasILoadCompleted;
+///
+/// fn show_toast(mut completed: MessageReader<WhereWasILoadCompleted>) {
+///     for load in completed.read() {
+///         info!("Restored {} entities", load.restored.len());
+///     }
+/// }
+/// ```
+#[derive(Message, Clone, Debug)]
+pub struct WhereWasILoadCompleted {
+    /// The names of the entities that were restored from their savefile
+    pub restored: Vec<String>,
+    /// The names of the entities whose savefile could not be read
+    pub failed: Vec<String>,
+    /// How long restoring the entities took
+    pub duration: Duration,
+}
+
+/// A [`Message`] written for every entity whose savefile exists but could not be read
+#[derive(Message, Clone, Debug)]
+pub struct WhereWasILoadFailed {
+    /// The entity that could not be restored
+    pub entity: Entity,
+    /// The name of the [`WhereWasI`] component
+    pub name: String,
+    /// The error that occurred while reading the savefile
+    pub error: WhereWasIParseError,
+}
+
+/// A [`Resource`] with the configuration of [`WhereWasIPlugin`], so we can access it in the systems
+/// of this plugin.
+#[derive(Resource, Clone)]
+pub struct WhereWasIConfig {
+    directory: PathBuf,
+    directory_source: DirectorySource,
+    fallback_directory: Option<PathBuf>,
+    summary_log_level: SummaryLogLevel,
+    strict: bool,
+    name_collisions: NameCollisionPolicy,
+    history: usize,
+    archive: Option<ArchivePolicy>,
+    app_version: Option<String>,
+    max_age: Option<Duration>,
+    delete_expired: bool,
+    restore_on_startup: bool,
+    format: SaveFormat,
+    dry_run: bool,
+    invalid_values: InvalidValuePolicy,
+    strict_rotations: bool,
+    validate_save: Option<SaveValidator>,
+    prepare_save: Option<SavePreparer>,
+    autosave: Option<Duration>,
+    skip_unchanged: bool,
+    save_parallelism: usize,
+    rate_limit: Option<SaveRateLimit>,
+    #[cfg(feature = "window")]
+    primary_window: Option<String>,
+    #[cfg(feature = "window")]
+    respawn_window: Option<WindowSpawner>,
+    #[cfg(feature = "window")]
+    save_on_close: SaveOnClose,
+    record_replay: Option<Duration>,
+    journal: Option<Duration>,
+    respawn_entity: Option<EntitySpawner>,
+    generate_ids: bool,
+    rename_policy: RenamePolicy,
+    stale_content: StaleContentPolicy,
+    restore_policy: RestorePolicy,
+    groups: BTreeMap<String, WhereWasIConfig>,
+    policy: Option<SavePolicy>,
+}
+
+impl WhereWasIConfig {
+    /// The directory where savefiles are stored and loaded from
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Where [`directory`](Self::directory) came from, see [`SAVE_DIR_ENV`] and [`CONFIG_FILE`]
+    pub fn directory_source(&self) -> &DirectorySource {
+        &self.directory_source
+    }
+
+    /// The directory that is used when [`directory`](Self::directory) can't be written, see
+    /// [`WhereWasIPlugin::fallback_directory`]
+    pub fn fallback_directory(&self) -> Option<&Path> {
+        self.fallback_directory.as_deref()
+    }
+
+    /// The level the summaries of saves and restores are logged at
+    pub fn summary_log_level(&self) -> SummaryLogLevel {
+        self.summary_log_level
+    }
+
+    /// Save to `directory` instead, together with the groups that are saved inside the current
+    /// directory
+    pub(crate) fn fall_back_to(&mut self, directory: PathBuf) {
+        for group in self.groups.values_mut() {
+            if let Ok(relative) = group.directory.strip_prefix(&self.directory) {
+                group.directory = directory.join(relative);
             }
         }
+        self.directory = directory;
+        self.directory_source = DirectorySource::Fallback;
     }
 
-    info!("Initialized {} transform(s)", initialized);
-}
+    /// Whether unreadable savefiles panic at startup, see [`WhereWasIPlugin::strict`]
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
 
-/// Saves the state of all [`Transform`]s belonging to [`WhereWasI`] components when closing a
-/// window
-///
-/// Note: this doesn't work for WASM.
-fn save_state(
-    mut events: MessageReader<WindowClosing>,
-    to_save: Query<(&WhereWasI, &Transform)>,
-    config: Res<WhereWasIConfig>,
-) {
-    let directory = &config.directory;
-    let mut saved_files = 0;
+    /// What to do when several entities share the same [`WhereWasI`] name
+    pub fn name_collisions(&self) -> NameCollisionPolicy {
+        self.name_collisions
+    }
 
-    if events.read().next().is_some() {
-        for (where_was_i, transform) in to_save.iter() {
-            let filename = where_was_i.name.clone();
+    /// How many older snapshots are kept per savefile, see [`WhereWasIPlugin::history`]
+    pub fn history(&self) -> usize {
+        self.history
+    }
 
-            if let Ok(false) = fs::exists(directory) {
-                fs::create_dir_all(directory).expect("Could not create directory");
-            }
+    /// How overwritten savefiles are archived, see [`WhereWasIPlugin::archive`]
+    pub fn archive(&self) -> Option<&ArchivePolicy> {
+        self.archive.as_ref()
+    }
 
-            let mut writer = BufWriter::new(
-                File::create(format!("{directory}/{filename}.state"))
-                    .expect("Error occurred while opening file"),
-            );
+    /// The version of the application recorded in the [`SessionMetadata`]
+    pub fn app_version(&self) -> Option<&str> {
+        self.app_version.as_deref()
+    }
 
-            #[cfg(not(target_arch = "wasm32"))]
-            serialize_transform(&mut writer, transform)
-                .expect("Error occurred while writing to disk");
+    /// Savefiles older than this are ignored at startup, see [`WhereWasIPlugin::max_age`]
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
 
-            saved_files += 1;
-        }
-        info!("Saved {} transforms to: {}", saved_files, directory);
+    /// Whether expired savefiles are deleted, see [`WhereWasIPlugin::delete_expired`]
+    pub fn delete_expired(&self) -> bool {
+        self.delete_expired
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Whether savefiles are restored at startup, see [`WhereWasIPlugin::restore_on_startup`]
+    pub fn restore_on_startup(&self) -> bool {
+        self.restore_on_startup
+    }
 
-    const TRANSFORM: Transform = Transform {
-        translation: Vec3::new(4.0, 3.5, -2.0),
-        rotation: Quat::from_xyzw(-0.1, 0.7, 0.4, 0.6),
-        scale: Vec3::new(12.6, -1.0, 2.4),
-    };
-    const SAVE_STATE_FILE: &str = "assets/tests/system_save_test.state";
+    /// The format savefiles are written in, see [`WhereWasIPlugin::format`]
+    pub fn format(&self) -> SaveFormat {
+        self.format
+    }
 
-    fn setup_camera_with_transform(mut commands: Commands<'_, '_>) {
-        commands.spawn((WhereWasI::from_name("system_save_test"), TRANSFORM));
+    /// Whether saves are only logged instead of written, see [`WhereWasIPlugin::dry_run`]
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
     }
 
-    fn setup_camera_without_transform(mut commands: Commands<'_, '_>) {
-        commands.spawn((Camera::default(), WhereWasI::camera()));
+    /// What happens to savefiles with invalid values, see [`WhereWasIPlugin::invalid_values`]
+    pub fn invalid_values(&self) -> InvalidValuePolicy {
+        self.invalid_values
     }
 
-    #[test]
-    fn test_save() {
-        let mut app = App::new();
+    /// Whether non-unit rotations fail to load, see [`WhereWasIPlugin::strict_rotations`]
+    pub fn strict_rotations(&self) -> bool {
+        self.strict_rotations
+    }
 
-        if let Ok(true) = fs::exists(SAVE_STATE_FILE) {
-            fs::remove_file("assets/tests/system_save_test.state").unwrap();
-        }
-        assert!(!fs::exists(SAVE_STATE_FILE).unwrap());
+    /// The callback that can veto saves, see [`WhereWasIPlugin::validate_save`]
+    pub fn validate_save(&self) -> Option<SaveValidator> {
+        self.validate_save
+    }
 
-        app.insert_resource(WhereWasIConfig {
-            directory: "assets/tests".into(),
-        });
-        app.add_message::<WindowClosing>();
-        app.add_systems(Startup, setup_camera_with_transform);
-        app.add_systems(Update, save_state);
+    /// The callback that can adjust or veto saves, see [`WhereWasIPlugin::prepare_save`]
+    pub fn prepare_save(&self) -> Option<SavePreparer> {
+        self.prepare_save
+    }
 
-        // Send an `WindowClosing` event
-        app.world_mut()
-            .resource_mut::<Messages<WindowClosing>>()
-            .write(WindowClosing {
-                window: Entity::from_raw_u32(322).unwrap(),
-            });
+    /// The interval between autosaves, if enabled
+    pub fn autosave(&self) -> Option<Duration> {
+        self.autosave
+    }
 
-        app.update();
+    /// Whether savefiles that haven't changed since they were last saved are left untouched
+    pub fn skip_unchanged(&self) -> bool {
+        self.skip_unchanged
+    }
 
-        let lines = read_lines("assets/tests/system_save_test.state").unwrap();
-        assert_eq!(deserialize_transform(lines).unwrap(), TRANSFORM);
+    /// How many savefiles are written concurrently, see [`WhereWasIPlugin::save_parallelism`]
+    pub fn save_parallelism(&self) -> usize {
+        self.save_parallelism
+    }
 
-        fs::remove_file("assets/tests/system_save_test.state").unwrap();
+    /// The limits on how much is written to disk, if any
+    pub fn rate_limit(&self) -> Option<&SaveRateLimit> {
+        self.rate_limit.as_ref()
     }
 
-    #[test]
-    fn test_load() {
-        let mut app = App::new();
+    /// The name the primary window is tracked under, see [`WhereWasIPlugin::primary_window`]
+    #[cfg(feature = "window")]
+    pub fn primary_window(&self) -> Option<&str> {
+        self.primary_window.as_deref()
+    }
 
-        app.insert_resource(WhereWasIConfig {
-            directory: "assets/tests".into(),
-        });
-        app.add_systems(Startup, setup_camera_without_transform);
-        app.add_systems(Update, load_state);
+    /// The callback that re-creates windows, see [`WhereWasIPlugin::respawn_window`]
+    #[cfg(feature = "window")]
+    pub fn respawn_window(&self) -> Option<WindowSpawner> {
+        self.respawn_window
+    }
 
-        app.update();
+    /// Which closing windows request a save
+    #[cfg(feature = "window")]
+    pub fn save_on_close(&self) -> SaveOnClose {
+        self.save_on_close
+    }
 
-        let result = app
-            .world_mut()
-            .query::<&Transform>()
-            .single(app.world())
-            .expect("`load_state` should have added a Transform to the world");
+    /// Change which closing windows request a save
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::{SaveOnClose, WhereWasIConfig};
+    ///
+    /// fn spawn_editor(mut commands: Commands, mut config: ResMut<WhereWasIConfig>) {
+    ///     let editor = commands.spawn(Window::default()).id();
+    ///     config.set_save_on_close(SaveOnClose::Window(editor));
+    /// }
+    /// ```
+    #[cfg(feature = "window")]
+    pub fn set_save_on_close(&mut self, save_on_close: SaveOnClose) {
+        self.save_on_close = save_on_close;
+    }
 
-        const TRANSFORM: Transform = Transform {
-            translation: Vec3::new(10.000002, 10.0, 10.0),
-            rotation: Quat::from_xyzw(-0.27984813, 0.36470526, 0.11591691, 0.88047624),
-            scale: Vec3::new(1.0, 1.0, 1.0),
+    /// The interval at which replays are recorded, if they are recorded at all
+    pub fn record_replay(&self) -> Option<Duration> {
+        self.record_replay
+    }
+
+    /// The interval at which the crash-recovery journal is written, if it is written at all
+    pub fn journal(&self) -> Option<Duration> {
+        self.journal
+    }
+
+    /// The callback that re-creates tracked entities, see [`WhereWasIPlugin::respawn_entity`]
+    pub fn respawn_entity(&self) -> Option<EntitySpawner> {
+        self.respawn_entity
+    }
+
+    /// Whether tracked entities get a `WhereWasIId` when they're saved for the first time
+    pub fn generate_ids(&self) -> bool {
+        self.generate_ids
+    }
+
+    /// What happens to the savefile of a renamed entity, see [`WhereWasIPlugin::rename_policy`]
+    pub fn rename_policy(&self) -> RenamePolicy {
+        self.rename_policy
+    }
+
+    /// What happens to savefiles written for other content, see
+    /// [`WhereWasIPlugin::stale_content`]
+    pub fn stale_content(&self) -> StaleContentPolicy {
+        self.stale_content
+    }
+
+    /// What happens to entities that aren't restored from a savefile, see
+    /// [`WhereWasIPlugin::restore_policy`]
+    pub fn restore_policy(&self) -> RestorePolicy {
+        self.restore_policy
+    }
+
+    /// The configuration of the entities in `group`, see [`WhereWasIGroup`]
+    ///
+    /// This is the configuration of the plugin itself for entities without a group or in a group
+    /// that isn't configured.
+    pub fn group(&self, group: Option<&str>) -> &WhereWasIConfig {
+        group
+            .and_then(|group| self.groups.get(group))
+            .unwrap_or(self)
+    }
+
+    /// Add the configuration of another instance of [`WhereWasIPlugin`] as a group named
+    /// `instance`, together with the groups of that instance
+    fn add_instance(&mut self, instance: String, mut config: WhereWasIConfig) {
+        for (name, group) in std::mem::take(&mut config.groups)
+            .into_iter()
+            .chain([(instance, config)])
+        {
+            if self.groups.contains_key(&name) {
+                warn!(
+                    "The group {} is configured by several instances of WhereWasIPlugin, keeping the first",
+                    name
+                );
+                continue;
+            }
+            self.groups.insert(name, group);
+        }
+    }
+
+    /// The configuration of the plugin itself, followed by those of all groups
+    pub(crate) fn with_groups(&self) -> impl Iterator<Item = &WhereWasIConfig> {
+        std::iter::once(self).chain(self.groups.values())
+    }
+}
+
+impl Default for WhereWasIConfig {
+    fn default() -> Self {
+        (&WhereWasIPlugin::default()).into()
+    }
+}
+
+impl From<&WhereWasIPlugin> for WhereWasIConfig {
+    fn from(plugin: &WhereWasIPlugin) -> Self {
+        let config = Self {
+            directory: plugin.directory.clone(),
+            directory_source: DirectorySource::Plugin,
+            fallback_directory: plugin.fallback_directory.clone(),
+            summary_log_level: plugin.summary_log_level,
+            strict: plugin.strict,
+            name_collisions: plugin.name_collisions,
+            history: plugin.history,
+            archive: plugin.archive,
+            app_version: plugin.app_version.clone(),
+            max_age: plugin.max_age,
+            delete_expired: plugin.delete_expired,
+            restore_on_startup: plugin.restore_on_startup,
+            format: plugin.format,
+            dry_run: plugin.dry_run,
+            invalid_values: plugin.invalid_values,
+            strict_rotations: plugin.strict_rotations,
+            validate_save: plugin.validate_save,
+            prepare_save: plugin.prepare_save,
+            autosave: plugin.autosave,
+            skip_unchanged: plugin.skip_unchanged,
+            save_parallelism: plugin.save_parallelism,
+            rate_limit: plugin.rate_limit,
+            #[cfg(feature = "window")]
+            primary_window: plugin.primary_window.clone(),
+            #[cfg(feature = "window")]
+            respawn_window: plugin.respawn_window,
+            #[cfg(feature = "window")]
+            save_on_close: plugin.save_on_close,
+            record_replay: plugin.record_replay,
+            journal: plugin.journal,
+            respawn_entity: plugin.respawn_entity,
+            generate_ids: plugin.generate_ids,
+            rename_policy: plugin.rename_policy,
+            stale_content: plugin.stale_content,
+            restore_policy: plugin.restore_policy,
+            groups: BTreeMap::new(),
+            policy: None,
         };
-        assert_eq!(*result, TRANSFORM);
+
+        let groups = plugin
+            .groups
+            .iter()
+            .map(|(name, group)| {
+                let config = Self {
+                    directory: group
+                        .directory
+                        .clone()
+                        .unwrap_or_else(|| plugin.directory.join(name)),
+                    format: group.format.unwrap_or(plugin.format),
+                    policy: group.policy,
+                    ..config.clone()
+                };
+                (name.clone(), config)
+            })
+            .collect();
+        Self { groups, ..config }
+    }
+}
+
+/// Plugin that saves the [`Transform`] state after closing a Bevy application, and restores it
+/// when launching the application again.
+#[derive(Clone)]
+pub struct WhereWasIPlugin {
+    /// The directory where savefiles will be stored and loaded from
+    ///
+    /// It can be overridden without recompiling with the [`SAVE_DIR_ENV`] environment variable or a
+    /// [`CONFIG_FILE`] next to the executable, see [`WhereWasIConfig::directory_source`].
+    pub directory: PathBuf,
+    /// The directory to save to instead when [`directory`](Self::directory) can't be written
+    ///
+    /// Projects that embed their assets, for example with `bevy_embedded_assets`, or that are
+    /// installed in a read-only location have no writable `./assets`. By default the plugin then
+    /// falls back to a directory named after the executable in the data directory of the user,
+    /// such as `~/.local/share/my_game` on Linux, and writes a [`WhereWasIDirectoryFallback`]
+    /// message. Set it to `None` to insert [`PersistenceUnavailable`] instead.
+    pub fallback_directory: Option<PathBuf>,
+    /// The level the summaries after every save and restore are logged at, such as
+    /// "Saved 3 transforms"
+    ///
+    /// Summaries are logged with the [`SUMMARY_LOG_TARGET`] target and structured fields with the
+    /// names of the entities, the directory and the duration, so they can be filtered separately.
+    pub summary_log_level: SummaryLogLevel,
+    /// Panic at startup if a savefile exists but can't be read
+    ///
+    /// This is useful in CI to detect that a checked-in savefile has gone stale or corrupt.
+    pub strict: bool,
+    /// What to do when several entities share the same [`WhereWasI`] name
+    pub name_collisions: NameCollisionPolicy,
+    /// How many older snapshots to keep per savefile
+    ///
+    /// When larger than zero, `camera.state` is moved to `camera.state.1` before it is
+    /// overwritten, `camera.state.1` to `camera.state.2`, and so on. Use
+    /// [`RestoreWhereWasISnapshot`] to go back to an older snapshot.
+    pub history: usize,
+    /// Keep a timestamped copy of every savefile in an `archive` subdirectory before it is
+    /// overwritten, see [`ArchivePolicy`]
+    pub archive: Option<ArchivePolicy>,
+    /// The version of the application, recorded in the [`SessionMetadata`] when saving
+    pub app_version: Option<String>,
+    /// Ignore savefiles that were written longer ago than this at startup
+    ///
+    /// Entities with an expired savefile keep their authored [`Transform`], as if no savefile
+    /// existed. Restoring a months-old camera position in a level that has changed since is often
+    /// worse than starting fresh.
+    pub max_age: Option<Duration>,
+    /// Delete expired savefiles instead of only ignoring them, see [`WhereWasIPlugin::max_age`]
+    pub delete_expired: bool,
+    /// Restore tracked entities from their savefiles when they are spawned
+    ///
+    /// Disable this when another crate restores the entities, for example from a snapshot that
+    /// includes [`WhereWasIRecords`]. Savefiles are still written when the application closes.
+    pub restore_on_startup: bool,
+    /// The format savefiles are written in
+    ///
+    /// Existing savefiles are still read in the format they were written in, see [`convert`] to
+    /// migrate them.
+    pub format: SaveFormat,
+    /// Serialize savefiles as usual, but only log what would be written instead of touching the
+    /// disk
+    ///
+    /// Each savefile is logged with its name, size and destination. Useful to debug persistence
+    /// in environments where the save directory isn't writable.
+    pub dry_run: bool,
+    /// What to do with savefiles that contain NaN, infinite or zero values, see
+    /// [`InvalidValuePolicy`]
+    ///
+    /// Such values would otherwise be written straight into the [`Transform`] and break rendering
+    /// and camera math.
+    pub invalid_values: InvalidValuePolicy,
+    /// Fail to load savefiles whose rotation isn't a unit quaternion
+    ///
+    /// By default rotations are normalized when they are loaded, with a warning if their length
+    /// was noticeably off, which usually means the savefile was edited by hand.
+    pub strict_rotations: bool,
+    /// Called with the name and [`Transform`] of every entity before it is saved
+    ///
+    /// Returning an error vetoes the save: the previous savefile is kept and a
+    /// [`WhereWasISaveFailed`] message is written with the reason. Use this to avoid persisting
+    /// obviously bad state, such as a camera that fell through the floor.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIPlugin;
+    ///
+    /// WhereWasIPlugin {
+    ///     validate_save: Some(|_name, transform| {
+    ///         if transform.translation.y < 0.0 {
+    ///             return Err("below the floor".into());
+    ///         }
+    ///         Ok(())
+    ///     }),
+    ///     ..default()
+    /// };
+    /// ```
+    pub validate_save: Option<SaveValidator>,
+    /// Called with the name and [`Transform`] of every entity before it is serialized, to adjust
+    /// what is written to the savefile
+    ///
+    /// The entity itself keeps its [`Transform`], only the savefile gets the adjusted one. Returning
+    /// an error vetoes the save, like [`validate_save`](Self::validate_save), which runs
+    /// afterwards on the adjusted [`Transform`].
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIPlugin;
+    ///
+    /// WhereWasIPlugin {
+    ///     prepare_save: Some(|_name, transform| {
+    ///         // Keep the horizon level
+    ///         let (yaw, pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
+    ///         transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+    ///         transform.translation = transform.translation.round();
+    ///         Ok(())
+    ///     }),
+    ///     ..default()
+    /// };
+    /// ```
+    pub prepare_save: Option<SavePreparer>,
+    /// Also save periodically, with this interval, while the application is running
+    ///
+    /// Autosaves serialize and write the savefiles on the [`AsyncComputeTaskPool`], so saving many
+    /// entities doesn't hitch the frame. A [`WhereWasISaveCompleted`] message is written when an
+    /// autosave has finished. The save when the window closes still happens, and waits for an
+    /// autosave in flight first.
+    ///
+    /// [`AsyncComputeTaskPool`]: bevy_tasks::AsyncComputeTaskPool
+    pub autosave: Option<Duration>,
+    /// Don't write savefiles whose [`Transform`] is exactly the one that was last saved or loaded
+    ///
+    /// This cuts down on disk writes with many tracked entities or frequent autosaves. The
+    /// comparison uses the records in [`WhereWasISaves`], so a savefile that is deleted by another
+    /// program while the application runs is only written again once its entity moves.
+    pub skip_unchanged: bool,
+    /// The maximum number of batches of savefiles that are serialized and written concurrently
+    ///
+    /// With thousands of tracked entities, raising this spreads the work over the
+    /// [`IoTaskPool`](bevy_tasks::IoTaskPool) and keeps the save when the window closes short.
+    /// The default of `1` writes all savefiles one after the other, which is best for spinning
+    /// disks.
+    pub save_parallelism: usize,
+    /// Limit how many savefiles and bytes are written, so frequent saves of a rapidly moving
+    /// entity can't hammer the disk, see [`SaveRateLimit`]
+    pub rate_limit: Option<SaveRateLimit>,
+    /// Track the [`PrimaryWindow`] under this name, as if it was spawned with a
+    /// [`WhereWasIWindow`]
+    ///
+    /// The primary window is usually spawned by `WindowPlugin`, so there is no spawn site to add
+    /// the component to. A [`WhereWasIWindow`] that is already on the primary window is kept.
+    #[cfg(feature = "window")]
+    pub primary_window: Option<String>,
+    /// Called at startup for every window with a [`WhereWasIWindow`] that was open when the
+    /// previous session saved, but doesn't exist yet
+    ///
+    /// It receives the name of the window and returns the entity of the window it spawned. The
+    /// recorded size and position are applied to it afterwards.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIPlugin;
+    ///
+    /// WhereWasIPlugin {
+    ///     respawn_window: Some(|commands, name| {
+    ///         commands
+    ///             .spawn(Window {
+    ///                 title: name.into(),
+    ///                 ..default()
+    ///             })
+    ///             .id()
+    ///     }),
+    ///     ..default()
+    /// };
+    /// ```
+    #[cfg(feature = "window")]
+    pub respawn_window: Option<WindowSpawner>,
+    /// Which closing windows request a save
+    ///
+    /// By default closing any window saves. Use [`SaveOnClose::PrimaryWindow`] so that closing a
+    /// transient tool window doesn't save an in-progress camera move.
+    #[cfg(feature = "window")]
+    pub save_on_close: SaveOnClose,
+    /// Record the [`Transform`]s of tracked entities with this interval to a `.replay` file in
+    /// the `replays` subdirectory, to review the session later with [`PlayWhereWasIReplay`]
+    ///
+    /// Only entities that moved since the previous sample are recorded.
+    pub record_replay: Option<Duration>,
+    /// Append the latest [`Transform`] of every tracked entity that moved to a crash-recovery
+    /// journal with this interval
+    ///
+    /// The journal is removed when the application exits cleanly. If the previous session crashed
+    /// instead, the journal is written into proper savefiles at startup, before they are loaded.
+    /// This is a lot cheaper than [`autosave`](Self::autosave), since only the entities that moved
+    /// are appended to a single file.
+    pub journal: Option<Duration>,
+    /// Called at startup for every savefile without a matching [`WhereWasI`] entity, for example
+    /// to re-create markers or annotations placed by the user
+    ///
+    /// It receives the name of the savefile and the saved [`Transform`], and returns the entity it
+    /// spawned. A [`WhereWasI`] component with that name is added to it, unless the callback
+    /// added one itself.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIPlugin;
+    ///
+    /// WhereWasIPlugin {
+    ///     respawn_entity: Some(|commands, name, transform| {
+    ///         commands.spawn((Name::new(name.to_string()), *transform)).id()
+    ///     }),
+    ///     ..default()
+    /// };
+    /// ```
+    pub respawn_entity: Option<EntitySpawner>,
+    /// Give every tracked entity without a `WhereWasIId` a new one when it's saved for the first
+    /// time, so its savefile is named after the ID from then on
+    ///
+    /// Requires the `uuid` feature. The ID has to survive the session to be of use, for example
+    /// because the entity is respawned with [`respawn_entity`](Self::respawn_entity).
+    pub generate_ids: bool,
+    /// What happens to the savefile of an entity when the name of its [`WhereWasI`] changes
+    /// while the application runs, see [`RenamePolicy`]
+    ///
+    /// This also applies when an ID is assigned with [`generate_ids`](Self::generate_ids).
+    pub rename_policy: RenamePolicy,
+    /// What happens at startup to savefiles that were written for other content, according to the
+    /// [`WhereWasIContentHash`] stored with them, see [`StaleContentPolicy`]
+    ///
+    /// Restoring a camera into a level that was rebuilt since often points it at nothing.
+    pub stale_content: StaleContentPolicy,
+    /// What happens to entities that aren't restored from a savefile, see [`RestorePolicy`]
+    ///
+    /// By default they keep their current [`Transform`]. An entity that is spawned after startup
+    /// may have moved by the time it would be restored in the next frame, use
+    /// [`RestorePolicy::PreferAuthoredIfNoSave`] to put it back where it was spawned.
+    pub restore_policy: RestorePolicy,
+    /// Restore entities without a savefile in the save directory from this archive, created with
+    /// [`WhereWasIStore::export_archive`]
+    ///
+    /// The archive is mapped into memory at startup and only the savefiles of tracked entities are
+    /// decoded, so a large archive doesn't slow down startup. Requires the `mmap` feature, see
+    /// [`WhereWasIArchive`].
+    #[cfg(feature = "mmap")]
+    pub restore_archive: Option<PathBuf>,
+    /// The settings of groups of entities, keyed by the name used in [`WhereWasIGroup`]
+    ///
+    /// Each group can store its savefiles in its own directory and format, see [`SaveGroup`].
+    pub groups: BTreeMap<String, SaveGroup>,
+    /// The label of this instance, to add the plugin more than once with different settings
+    ///
+    /// The first instance that is added runs all systems. Every other instance needs a label, and
+    /// is configured like a group named after it: entities with `WhereWasIGroup::new(label)` are
+    /// saved to and restored from its directory, with its settings. Its own groups are added too.
+    /// Settings that apply to the whole application, such as windows, autosaves, replays and the
+    /// journal, are only taken from the first instance.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIPlugin;
+    ///
+    /// App::new().add_plugins((
+    ///     WhereWasIPlugin::from_directory("./assets/saves/local"),
+    ///     WhereWasIPlugin {
+    ///         instance: Some("shared".into()),
+    ///         ..WhereWasIPlugin::from_directory("./assets/bookmarks")
+    ///     },
+    /// ));
+    /// ```
+    pub instance: Option<String>,
+    /// Whether the plugin saves and restores anything at all
+    ///
+    /// When disabled, the resources and messages of the plugin are still added so the rest of the
+    /// application keeps working, but no systems are added and no files are touched. This lets
+    /// release builds keep the [`WhereWasI`] components in their spawn code:
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::WhereWasIPlugin;
+    ///
+    /// WhereWasIPlugin {
+    ///     enabled: cfg!(debug_assertions),
+    ///     ..default()
+    /// };
+    /// ```
+    pub enabled: bool,
+}
+
+/// A callback that can veto saving a [`Transform`], see [`WhereWasIPlugin::validate_save`]
+pub type SaveValidator = fn(&str, &Transform) -> Result<(), String>;
+
+/// A callback that can adjust or veto saving a [`Transform`], see
+/// [`WhereWasIPlugin::prepare_save`]
+pub type SavePreparer = fn(&str, &mut Transform) -> Result<(), String>;
+
+impl Default for WhereWasIPlugin {
+    fn default() -> Self {
+        Self {
+            directory: "./assets/saves".into(),
+            fallback_directory: user_data_directory(),
+            summary_log_level: SummaryLogLevel::Info,
+            strict: false,
+            name_collisions: NameCollisionPolicy::default(),
+            history: 0,
+            archive: None,
+            app_version: None,
+            max_age: None,
+            delete_expired: false,
+            restore_on_startup: true,
+            format: SaveFormat::default(),
+            dry_run: false,
+            invalid_values: InvalidValuePolicy::default(),
+            strict_rotations: false,
+            validate_save: None,
+            prepare_save: None,
+            autosave: None,
+            skip_unchanged: true,
+            save_parallelism: 1,
+            rate_limit: None,
+            #[cfg(feature = "window")]
+            primary_window: None,
+            #[cfg(feature = "window")]
+            respawn_window: None,
+            #[cfg(feature = "window")]
+            save_on_close: SaveOnClose::AnyWindow,
+            record_replay: None,
+            journal: None,
+            respawn_entity: None,
+            generate_ids: false,
+            rename_policy: RenamePolicy::Move,
+            stale_content: StaleContentPolicy::Restore,
+            restore_policy: RestorePolicy::KeepCurrent,
+            #[cfg(feature = "mmap")]
+            restore_archive: None,
+            groups: BTreeMap::new(),
+            instance: None,
+            enabled: true,
+        }
+    }
+}
+
+impl WhereWasIPlugin {
+    /// Construct a [`WhereWasIPlugin`] that stores savefiles in `directory`
+    ///
+    /// ```rust
+    /// use bevy_where_was_i::WhereWasIPlugin;
+    ///
+    /// WhereWasIPlugin::from_directory("./assets/saves/my_game");
+    /// ```
+    pub fn from_directory(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Construct a [`WhereWasIPlugin`] that stores savefiles in the directory of `directories` for
+    /// the current platform
+    pub fn from_platform_directories(directories: PlatformDirectories) -> Self {
+        Self::from_directory(directories.current())
+    }
+}
+
+impl From<&str> for WhereWasIPlugin {
+    fn from(directory: &str) -> Self {
+        Self::from_directory(directory)
+    }
+}
+
+impl Plugin for WhereWasIPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(mut config) = app.world_mut().get_resource_mut::<WhereWasIConfig>() {
+            let Some(instance) = self.instance.clone() else {
+                panic!(
+                    "WhereWasIPlugin was added more than once, set `instance` on every instance but the first"
+                );
+            };
+            config.add_instance(instance, WhereWasIConfig::from(self));
+            return;
+        }
+
+        let config = if self.enabled {
+            let (directory, directory_source) = resolve_directory(&self.directory);
+            WhereWasIConfig {
+                directory_source,
+                ..WhereWasIConfig::from(&WhereWasIPlugin {
+                    directory,
+                    ..self.clone()
+                })
+            }
+        } else {
+            WhereWasIConfig::from(self)
+        };
+
+        app.insert_resource(config)
+            .register_type::<WhereWasI>()
+            .register_type::<WhereWasIHistory>()
+            .register_type::<WhereWasIAuthored>()
+            .register_type::<WhereWasIRecords>()
+            .register_type::<WhereWasISkip>()
+            .register_type::<WhereWasIPosition>()
+            .add_message::<ResetWhereWasI>()
+            .add_message::<SaveWhereWasI>()
+            .add_message::<SaveAdmitted>()
+            .add_message::<WhereWasISaveFailed>()
+            .add_message::<WhereWasISaveCompleted>()
+            .add_message::<WhereWasILoaded>()
+            .add_message::<WhereWasILoadFailed>()
+            .add_message::<WhereWasILoadCompleted>()
+            .add_message::<RestoreWhereWasISnapshot>()
+            .add_message::<UndoWhereWasI>()
+            .add_message::<RedoWhereWasI>()
+            .add_message::<LogWhereWasIDiff>()
+            .add_message::<PlayWhereWasIReplay>()
+            .add_message::<WhereWasIStaleContent>()
+            .add_message::<WhereWasIStarted>()
+            .add_message::<WhereWasIDirectoryFallback>()
+            .init_resource::<WhereWasISaves>()
+            .init_resource::<WhereWasIFirstRun>()
+            .init_resource::<WhereWasISession>()
+            .init_resource::<SaveMetrics>()
+            .init_resource::<SaveThrottle>()
+            .init_resource::<WhereWasISchema>();
+
+        #[cfg(feature = "window")]
+        app.register_type::<WhereWasIWindow>()
+            .init_resource::<SavedWindows>();
+
+        if !self.enabled {
+            return;
+        }
+
+        // Check the save directory right away, so a misconfiguration shows up at launch
+        if let Err(err) = app.world_mut().run_system_once(check_persistence) {
+            error!("Could not check the save directory: {}", err);
+        }
+
+        app.add_systems(
+            Update,
+            (
+                save_state.after(SaveRequests),
+                (follow_names, handle_renames).chain().before(save_state),
+                save_by_policy.after(save_state),
+                save_positions.after(SaveRequests),
+                save_schema.after(save_state),
+                reset_state,
+                restore_snapshot,
+                undo_redo,
+                log_diff,
+                (
+                    start_replay,
+                    play_replay.run_if(resource_exists::<ReplayPlayback>),
+                )
+                    .chain(),
+            ),
+        )
+        .add_systems(
+            PreStartup,
+            (
+                (recover_journal, read_saves, detect_first_run).chain(),
+                check_schema,
+            ),
+        )
+        .add_systems(
+            PostStartup,
+            (
+                apply_level.before(load_state),
+                load_state,
+                respawn_entities.after(load_state),
+            ),
+        )
+        .add_systems(
+            PreUpdate,
+            (apply_level, load_state, restore_positions).chain(),
+        )
+        .add_systems(
+            Last,
+            (
+                save_on_exit,
+                (save_positions, save_schema)
+                    .after(save_on_exit)
+                    .run_if(saving_on_exit),
+            ),
+        );
+
+        #[cfg(feature = "window")]
+        app.add_systems(
+            Update,
+            (
+                request_save_on_close.in_set(SaveRequests),
+                request_save_on_suspend
+                    .in_set(SaveRequests)
+                    .run_if(resource_exists::<Messages<AppLifecycle>>),
+                track_windows.after(save_state),
+            ),
+        )
+        .add_systems(PreStartup, (read_saved_windows, track_primary_window))
+        .add_systems(PostStartup, restore_windows)
+        .add_systems(
+            Last,
+            track_windows.after(save_on_exit).run_if(saving_on_exit),
+        );
+
+        if let Some(interval) = self.autosave {
+            app.insert_resource(Autosave::new(interval)).add_systems(
+                Update,
+                (
+                    tick_autosave.before(save_state),
+                    finish_autosave.after(save_state),
+                ),
+            );
+        }
+
+        if let Some(interval) = self.record_replay {
+            app.insert_resource(ReplayRecorder::new(interval))
+                .add_systems(
+                    Update,
+                    record_replay
+                        .after(play_replay)
+                        .run_if(resource_exists::<ReplayRecorder>),
+                );
+        }
+
+        if let Some(interval) = self.journal {
+            app.insert_resource(Journal::new(interval))
+                .add_systems(Update, write_journal.run_if(resource_exists::<Journal>))
+                .add_systems(
+                    Last,
+                    close_journal
+                        .after(save_on_exit)
+                        .run_if(resource_exists::<Journal>),
+                );
+        }
+
+        #[cfg(feature = "uuid")]
+        app.add_systems(
+            Update,
+            id::generate_ids.before(save_state).before(handle_renames),
+        );
+
+        // Entities restored in `PostStartup` are picked up in the `PreUpdate` of the same frame
+        #[cfg(feature = "interpolation")]
+        app.add_systems(PreUpdate, interpolation::reset_easing.after(load_state));
+
+        #[cfg(feature = "mmap")]
+        if let Some(path) = &self.restore_archive {
+            match WhereWasIArchive::open(path) {
+                Ok(archive) => {
+                    app.insert_resource(archive);
+                }
+                Err(err) => error!("Could not open archive {}: {}", path.display(), err),
+            }
+        }
+        #[cfg(feature = "mmap")]
+        app.add_systems(
+            PostStartup,
+            mapped::restore_from_archive
+                .before(load_state)
+                .run_if(resource_exists::<WhereWasIArchive>),
+        )
+        .add_systems(
+            PreUpdate,
+            mapped::restore_from_archive
+                .before(load_state)
+                .run_if(resource_exists::<WhereWasIArchive>),
+        );
+
+        #[cfg(feature = "big_space")]
+        app.add_systems(PreUpdate, grid::restore_grid_cells.after(load_state))
+            .add_systems(Update, grid::save_grid_cells.after(SaveRequests))
+            .add_systems(
+                Last,
+                grid::save_grid_cells
+                    .after(save_on_exit)
+                    .run_if(saving_on_exit),
+            );
+
+        #[cfg(feature = "camera-settings")]
+        app.add_systems(PostStartup, camera::restore_cameras)
+            .add_systems(Update, camera::save_cameras.after(SaveRequests))
+            .add_systems(
+                Last,
+                camera::save_cameras
+                    .after(save_on_exit)
+                    .run_if(saving_on_exit),
+            );
+
+        #[cfg(feature = "web")]
+        app.init_resource::<web::PageHidden>()
+            .add_systems(Update, web::request_save_on_page_hide.in_set(SaveRequests));
+
+        #[cfg(all(feature = "web", target_arch = "wasm32", target_os = "unknown"))]
+        app.add_systems(Startup, web::listen_for_page_hide);
+
+        #[cfg(feature = "viewports")]
+        app.add_systems(PostStartup, viewport::restore_viewports)
+            .add_systems(Update, viewport::save_viewports.after(SaveRequests))
+            .add_systems(
+                Last,
+                viewport::save_viewports
+                    .after(save_on_exit)
+                    .run_if(saving_on_exit),
+            );
+    }
+
+    fn is_unique(&self) -> bool {
+        false
+    }
+}
+
+/// Read and deserialize the savefile at `path`, in the format it was written in
+///
+/// The file is read once, both to detect its format and to deserialize it.
+fn read_state(path: impl AsRef<Path>) -> Result<Transform, WhereWasIParseError> {
+    let contents = storage::read(path.as_ref())?;
+    let mut reader = contents.as_slice();
+    SaveFormat::detect_buffered(&mut reader)?.deserialize(reader)
+}
+
+/// The path of the savefile for `name` in `directory`
+///
+/// Fails with [`io::ErrorKind::InvalidInput`] if `name` can't be used as a file name, see
+/// [`InvalidNameError`].
+fn state_path(directory: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    Ok(directory.join(format!("{name}.state")))
+}
+
+/// Serialize `transform` into the savefile for `name`
+///
+/// The directory is created if it doesn't exist yet. The previous savefile is archived according to
+/// [`WhereWasIConfig::archive`] and older snapshots are rotated according to
+/// [`WhereWasIConfig::history`].
+///
+/// In a `localStorage:` directory, savefiles are always written as [`SaveFormat::Text`] and no
+/// archived copies or snapshots are kept.
+///
+/// With [`WhereWasIConfig::dry_run`], `transform` is only serialized in memory and logged. The
+/// savefile gets `transform` as adjusted by [`WhereWasIConfig::prepare_save`]. Fails with
+/// [`io::ErrorKind::InvalidData`] if it or [`WhereWasIConfig::validate_save`] rejects `transform`.
+fn write_state(config: &WhereWasIConfig, name: &str, transform: &Transform) -> io::Result<u64> {
+    let path = state_path(&config.directory, name)?;
+    let mut transform = *transform;
+    if let Some(prepare) = config.prepare_save {
+        prepare(name, &mut transform).map_err(|reason| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Rejected by prepare_save: {reason}"),
+            )
+        })?;
+    }
+    let transform = &transform;
+    if let Some(validate) = config.validate_save {
+        validate(name, transform).map_err(|reason| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Rejected by validate_save: {reason}"),
+            )
+        })?;
+    }
+    if config.dry_run {
+        let mut writer = BufWriter::new(Vec::new());
+        config.format.serialize(&mut writer, transform)?;
+        let buffer = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        info!(
+            "Dry run: would write {} bytes for {} to {}",
+            buffer.len(),
+            name,
+            path.display()
+        );
+        return Ok(buffer.len() as u64);
+    }
+
+    if storage::is_local_storage(&config.directory) {
+        return write_transform(&path, transform, SaveFormat::Text);
+    }
+    fs::create_dir_all(&config.directory)?;
+    if let Some(archive) = &config.archive {
+        archive_state(&config.directory, name, archive)?;
+    }
+    rotate_snapshots(&config.directory, name, config.history)?;
+
+    write_transform(&path, transform, config.format)
+}
+
+/// Serialize `transform` in `format` into the file at `path`, creating its parent directories if
+/// needed
+///
+/// Returns the number of bytes written.
+fn write_transform(path: &Path, transform: &Transform, format: SaveFormat) -> io::Result<u64> {
+    if let Some(parent) = path.parent() {
+        storage::create_dir_all(parent)?;
+    }
+
+    let mut writer = BufWriter::new(Vec::new());
+    format.serialize(&mut writer, transform)?;
+    let contents = writer
+        .into_inner()
+        .map_err(io::IntoInnerError::into_error)?;
+    storage::write(path, &contents)?;
+    Ok(contents.len() as u64)
+}
+
+/// Read the savefile at `path` in any [`SaveFormat`], outside of a Bevy app
+///
+/// This is meant for tools that inspect or edit savefiles, such as the `wwi` binary.
+pub fn read_savefile(path: impl AsRef<Path>) -> Result<Transform, WhereWasIParseError> {
+    read_state(path)
+}
+
+/// Write `transform` in `format` to the savefile at `path`, outside of a Bevy app
+///
+/// Unlike saves made by [`WhereWasIPlugin`], no snapshots or archived copies are kept.
+pub fn write_savefile(
+    path: impl AsRef<Path>,
+    transform: &Transform,
+    format: SaveFormat,
+) -> io::Result<()> {
+    write_transform(path.as_ref(), transform, format).map(|_| ())
+}
+
+/// Read all savefiles, including those of groups, into [`WhereWasISaves`] and the metadata of
+/// the previous session into [`WhereWasISession`]
+fn read_saves(
+    mut saves: ResMut<WhereWasISaves>,
+    mut session: ResMut<WhereWasISession>,
+    config: Res<WhereWasIConfig>,
+) {
+    *saves = WhereWasISaves::default();
+
+    for config in config.with_groups() {
+        let mut read = WhereWasISaves::read_directory(config);
+
+        if let Some(max_age) = config.max_age {
+            for name in read.remove_expired(max_age) {
+                info!("Ignoring expired savefile for {}", name);
+
+                if config.delete_expired
+                    && let Err(err) = state_path(&config.directory, &name)
+                        .and_then(|path| storage::remove_file(&path))
+                {
+                    error!("Could not delete expired savefile for {}: {}", name, err);
+                }
+            }
+        }
+        saves.merge(read);
+    }
+
+    match read_session(&config.directory) {
+        Ok(previous) => {
+            if let Some(previous) = &previous {
+                saves.set_triggers(&previous.triggers);
+                saves.set_saved_at(&previous.saved);
+            }
+            session.previous = previous;
+        }
+        Err(err) => error!("Could not read session metadata: {}", err),
+    }
+}
+
+/// Load the state of the [`Transform`]s belonging to [`WhereWasI`] components that haven't been
+/// loaded yet
+///
+/// This runs in [`PostStartup`] for the entities spawned at startup, and in [`PreUpdate`] for
+/// entities spawned later. Savefiles are served from [`WhereWasISaves`], which was read once at
+/// startup, so the save directory isn't touched again.
+///
+/// Writes a [`WhereWasILoaded`] or [`WhereWasILoadFailed`] message for every entity that has a
+/// savefile. In strict mode, any failure panics after all entities have been processed.
+fn load_state(
+    mut commands: Commands,
+    mut to_load: Query<(
+        Entity,
+        &WhereWasI,
+        &mut Transform,
+        &mut WhereWasIHistory,
+        Option<&WhereWasIAuthored>,
+    )>,
+    pending: Query<(), (With<WhereWasI>, Without<Initialized>)>,
+    (mut loaded, mut load_failed, mut completed, mut stale): (
+        MessageWriter<WhereWasILoaded>,
+        MessageWriter<WhereWasILoadFailed>,
+        MessageWriter<WhereWasILoadCompleted>,
+        MessageWriter<WhereWasIStaleContent>,
+    ),
+    saves: Res<WhereWasISaves>,
+    (session, content_hash): (Res<WhereWasISession>, Option<Res<WhereWasIContentHash>>),
+    config: Res<WhereWasIConfig>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+
+    let started = Instant::now();
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    let mut errors = Vec::new();
+
+    // Names are resolved among all tracked entities, so collisions with entities that were loaded
+    // earlier are resolved the same way as when saving
+    let names = resolve_tracked_names(
+        to_load
+            .iter()
+            .map(|(entity, where_was_i, ..)| (entity, where_was_i)),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        if !pending.contains(entity) {
+            continue;
+        }
+        let Ok((_, where_was_i, mut transform, mut history, authored)) = to_load.get_mut(entity)
+        else {
+            continue;
+        };
+        commands.entity(entity).insert(Initialized);
+
+        // The pose of entities that aren't restored from a savefile
+        let fallback = match config.restore_policy {
+            RestorePolicy::KeepCurrent => where_was_i.default,
+            RestorePolicy::PreferAuthoredIfNoSave => {
+                authored.map(|authored| authored.0).or(where_was_i.default)
+            }
+        };
+
+        if !config.restore_on_startup {
+            if let Some(fallback) = fallback {
+                *transform = fallback;
+            }
+            continue;
+        }
+
+        if let Some(record) = saves.get(&name)
+            && let Some(condition) = &where_was_i.condition
+            && !condition.holds(record, session.previous(), &config)
+        {
+            info!("Not restoring {}, its restore condition doesn't hold", name);
+            if let Some(fallback) = fallback {
+                *transform = fallback;
+            }
+            continue;
+        }
+
+        if let Some(record) = saves.get(&name)
+            && let Some(ttl) = where_was_i.ttl
+            && record.age().is_some_and(|age| age > ttl)
+        {
+            info!(
+                "Not restoring {}, its savefile outlived its time to live",
+                name
+            );
+            if let Some(fallback) = fallback {
+                *transform = fallback;
+            }
+            continue;
+        }
+
+        let saved_hash = session
+            .previous()
+            .and_then(|session| session.content_hash.as_deref());
+        if saves.contains(&name)
+            && config.stale_content != StaleContentPolicy::Restore
+            && let Some((saved, current)) = stale_content(saved_hash, content_hash.as_deref())
+        {
+            info!("Not restoring {}, it was saved for other content", name);
+            if config.stale_content == StaleContentPolicy::Ask {
+                stale.write(WhereWasIStaleContent {
+                    entity,
+                    name: name.clone(),
+                    saved: saved.into(),
+                    current: current.into(),
+                });
+            }
+            if let Some(fallback) = fallback {
+                *transform = fallback;
+            }
+            continue;
+        }
+
+        if let Some(record) = saves.get(&name) {
+            let previous = *transform;
+            history.record(previous);
+            *transform = where_was_i.clamp(record.transform);
+            restored.push(name.clone());
+            commands.trigger(WhereWasIRestored {
+                entity,
+                name: name.clone(),
+                previous,
+                restored: *transform,
+            });
+            loaded.write(WhereWasILoaded {
+                entity,
+                name,
+                previous,
+                restored: *transform,
+                trigger: record.trigger,
+            });
+            continue;
+        }
+
+        let error = match validate_name(&name) {
+            Ok(()) => saves.failure(&name).cloned(),
+            Err(err) => Some(err.into()),
+        };
+
+        if let Some(error) = error {
+            errors.push(format!("{}: {}", name, error));
+            failed.push(name.clone());
+            load_failed.write(WhereWasILoadFailed {
+                entity,
+                name,
+                error,
+            });
+        }
+
+        if let Some(fallback) = fallback {
+            *transform = fallback;
+        }
+    }
+
+    log_summary!(
+        config.summary_log_level,
+        names = ?restored,
+        directory = %config.directory.display(),
+        duration = ?started.elapsed(),
+        "Initialized {} transform(s)",
+        restored.len()
+    );
+    completed.write(WhereWasILoadCompleted {
+        restored,
+        failed,
+        duration: started.elapsed(),
+    });
+
+    if config.strict && !errors.is_empty() {
+        panic!("Could not load savefile(s): {}", errors.join(", "));
+    }
+}
+
+/// The systems that write [`SaveWhereWasI`] requests that are handled in the same frame
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SaveRequests;
+
+/// Request a save when one or more windows close
+///
+/// Closing a secondary window only saves the entities bound to it, closing any other window saves
+/// all entities. Windows that don't match [`WhereWasIPlugin::save_on_close`] are ignored.
+#[cfg(feature = "window")]
+fn request_save_on_close(
+    mut closing: MessageReader<WindowClosing>,
+    mut requests: MessageWriter<SaveWhereWasI>,
+    windows: Query<Has<PrimaryWindow>, With<Window>>,
+    config: Res<WhereWasIConfig>,
+) {
+    for closed in closing.read() {
+        let saves = match config.save_on_close {
+            SaveOnClose::AnyWindow => true,
+            SaveOnClose::PrimaryWindow => windows.get(closed.window).is_ok_and(|primary| primary),
+            SaveOnClose::Window(window) => closed.window == window,
+        };
+        if !saves {
+            debug!("Not saving, window {} closed", closed.window);
+            continue;
+        }
+
+        let secondary = windows.get(closed.window).is_ok_and(|primary| !primary);
+        requests.write(SaveWhereWasI {
+            trigger: SaveTrigger::WindowClosed,
+            window: secondary.then_some(closed.window),
+        });
+    }
+}
+
+/// Request a save of all entities when the application is about to be suspended
+///
+/// Mobile platforms suspend applications that are sent to the background and may stop them
+/// without an [`AppExit`], so this is the last reliable moment to save. On iOS, the application is
+/// suspended before it can be terminated, so this also covers `UIApplicationWillTerminate`, at
+/// which point the schedule no longer runs.
+#[cfg(feature = "window")]
+fn request_save_on_suspend(
+    mut lifecycle: MessageReader<AppLifecycle>,
+    mut requests: MessageWriter<SaveWhereWasI>,
+) {
+    if lifecycle
+        .read()
+        .any(|lifecycle| *lifecycle == AppLifecycle::WillSuspend)
+    {
+        requests.write(SaveWhereWasI {
+            trigger: SaveTrigger::Suspended,
+            window: None,
+        });
+    }
+}
+
+/// Save all entities when the application exits with an [`AppExit`]
+///
+/// Without windows, nothing else saves when the application stops. Nothing is saved if a save of
+/// all entities was already requested, for example because the last window closed.
+pub(crate) fn save_on_exit(world: &mut World) {
+    if world
+        .get_resource::<Messages<AppExit>>()
+        .is_none_or(|exits| exits.is_empty())
+    {
+        return;
+    }
+
+    let requested = MessageCursor::<SaveWhereWasI>::default()
+        .read(world.resource::<Messages<SaveWhereWasI>>())
+        .any(|request| request.window.is_none());
+    if requested {
+        return;
+    }
+
+    world.write_message(SaveWhereWasI {
+        trigger: SaveTrigger::Exit,
+        window: None,
+    });
+    if let Err(err) = world.run_system_cached(save_state) {
+        error!("Could not save when exiting: {}", err);
+    }
+}
+
+/// Whether all entities are saved in this frame because the application exits, see
+/// [`save_on_exit`]
+///
+/// `Update` doesn't run again after an [`AppExit`], so systems that save next to the savefiles
+/// also run in `Last` with this condition.
+pub(crate) fn saving_on_exit(requests: Option<Res<Messages<SaveWhereWasI>>>) -> bool {
+    requests.is_some_and(|requests| {
+        MessageCursor::<SaveWhereWasI>::default()
+            .read(&requests)
+            .any(|request| request.trigger == SaveTrigger::Exit)
+    })
+}
+
+/// Saves the state of all [`Transform`]s belonging to [`WhereWasI`] components when a save is
+/// requested with [`SaveWhereWasI`]
+///
+/// All requests of a frame result in a single save, which is postponed while it would exceed the
+/// [`SaveRateLimit`]. Unless a request covers all entities, only the entities bound to the windows
+/// of the requests are saved. Autosaves are written in the background, any other save is written
+/// right away after the outcome of an autosave in flight is recorded. Entities that fail to save
+/// are reported with a [`WhereWasISaveFailed`] message, the remaining entities are still saved.
+/// Nothing is saved if [`PersistenceUnavailable`] was inserted at startup.
+///
+/// In the browser, savefiles are kept in `localStorage` when the save directory is a
+/// `localStorage:` directory, which requires the `web` feature, see [`PlatformDirectories::wasm`].
+/// The `web` feature also saves when the page is hidden or unloaded, with
+/// [`SaveTrigger::PageHidden`].
+fn save_state(
+    mut requests: MessageReader<SaveWhereWasI>,
+    mut admitted: MessageWriter<SaveAdmitted>,
+    mut to_save: Query<(Entity, &WhereWasI, &Transform, &mut WhereWasIHistory)>,
+    mut sink: SaveSink,
+    mut autosave: Option<ResMut<Autosave>>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let requests = requests.read().copied().collect::<Vec<_>>();
+    let requested = requests
+        .iter()
+        .map(|request| request.trigger)
+        .max()
+        .map(|trigger| RequestedSave {
+            trigger,
+            windows: requests
+                .iter()
+                .map(|request| request.window)
+                .collect::<Option<HashSet<_>>>(),
+        });
+    let Some(save) = sink.admit(requested) else {
+        return;
+    };
+    if let Some(unavailable) = unavailable {
+        warn!("Not saving transforms: {}", unavailable.reason);
+        return;
+    }
+    admitted.write(SaveAdmitted(save.clone()));
+    let RequestedSave { trigger, windows } = save;
+
+    // Record the autosave in flight first, so it counts when leaving out unchanged entities
+    if trigger != SaveTrigger::Autosave
+        && let Some((outcomes, duration)) = autosave.as_mut().and_then(|autosave| autosave.flush())
+    {
+        sink.finish(outcomes, duration, SaveTrigger::Autosave);
+    }
+
+    let started = Instant::now();
+    let mut entries = save_entries(
+        to_save
+            .iter()
+            .map(|(entity, where_was_i, transform, _)| (entity, where_was_i, transform)),
+        sink.config(),
+        sink.saves(),
+    );
+    if trigger == SaveTrigger::Autosave {
+        entries.retain(|entry| {
+            to_save.get(entry.entity).is_ok_and(|(_, where_was_i, ..)| {
+                matches!(
+                    sink.config().policy(where_was_i),
+                    SavePolicy::Inherit | SavePolicy::OnChange
+                )
+            })
+        });
+    }
+    if let Some(windows) = windows {
+        entries.retain(|entry| {
+            to_save.get(entry.entity).is_ok_and(|(_, where_was_i, ..)| {
+                where_was_i
+                    .window
+                    .is_some_and(|window| windows.contains(&window))
+            })
+        });
+    }
+
+    if trigger == SaveTrigger::Autosave
+        && let Some(mut autosave) = autosave
+    {
+        autosave.start(sink.config(), entries);
+        return;
+    }
+
+    let outcomes = write_states(sink.config(), entries);
+    for (entity, transform) in sink.finish(outcomes, started.elapsed(), trigger) {
+        if let Ok((.., mut history)) = to_save.get_mut(entity) {
+            history.record(transform);
+        }
+    }
+}
+
+/// Handles [`ResetWhereWasI`] messages by removing the savefile and restoring the
+/// [`WhereWasIAuthored`] transform
+///
+/// The record of the savefile is removed from [`WhereWasISaves`] as well, so the next save writes
+/// it again even if the entity is back at the saved pose.
+fn reset_state(
+    mut resets: MessageReader<ResetWhereWasI>,
+    mut to_reset: Query<(
+        Entity,
+        &WhereWasI,
+        &mut Transform,
+        &mut WhereWasIHistory,
+        Option<&WhereWasIAuthored>,
+    )>,
+    mut saves: ResMut<WhereWasISaves>,
+    config: Res<WhereWasIConfig>,
+) {
+    if resets.is_empty() {
+        return;
+    }
+
+    let names: HashMap<Entity, String> = resolve_tracked_names(
+        to_reset
+            .iter()
+            .map(|(entity, where_was_i, ..)| (entity, where_was_i)),
+        config.name_collisions,
+    )
+    .into_iter()
+    .collect();
+
+    for reset in resets.read() {
+        let Ok((_, where_was_i, mut transform, mut history, authored)) =
+            to_reset.get_mut(reset.entity)
+        else {
+            warn!("Cannot reset entity {} without WhereWasI", reset.entity);
+            continue;
+        };
+        let Some(name) = names.get(&reset.entity) else {
+            continue;
+        };
+
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        if !where_was_i.follows {
+            match state_path(directory, name).and_then(|path| storage::remove_file(&path)) {
+                Ok(()) => {
+                    saves.remove(name);
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    saves.remove(name);
+                }
+                Err(err) => error!("Could not remove savefile for {}: {}", name, err),
+            }
+        }
+
+        if let Some(authored) = authored.map(|authored| authored.0).or(where_was_i.default) {
+            history.record(*transform);
+            *transform = authored;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::serialization::{deserialize_transform, serialize_transform};
+
+    const TRANSFORM: Transform = Transform {
+        translation: Vec3::new(4.0, 3.5, -2.0),
+        rotation: Quat::from_xyzw(-0.1, 0.7, 0.1, 0.7),
+        scale: Vec3::new(12.6, -1.0, 2.4),
+    };
+    const SAVE_STATE_FILE: &str = "assets/tests/save/system_save_test.state";
+
+    /// An [`App`] with the resources and messages of [`WhereWasIPlugin`], but without its systems
+    pub(crate) fn test_app(config: WhereWasIConfig) -> App {
+        let mut app = App::new();
+        app.insert_resource(config)
+            .init_resource::<WhereWasISaves>()
+            .init_resource::<WhereWasISession>()
+            .init_resource::<SaveMetrics>()
+            .init_resource::<SaveThrottle>()
+            .add_message::<ResetWhereWasI>()
+            .add_message::<SaveWhereWasI>()
+            .add_message::<SaveAdmitted>()
+            .add_message::<WhereWasISaveFailed>()
+            .add_message::<WhereWasISaveCompleted>()
+            .add_message::<WhereWasILoaded>()
+            .add_message::<WhereWasILoadFailed>()
+            .add_message::<WhereWasILoadCompleted>()
+            .add_message::<RestoreWhereWasISnapshot>()
+            .add_message::<UndoWhereWasI>()
+            .add_message::<RedoWhereWasI>()
+            .add_message::<LogWhereWasIDiff>()
+            .add_message::<PlayWhereWasIReplay>()
+            .add_message::<WhereWasIDirectoryFallback>()
+            .add_message::<WhereWasIStaleContent>();
+        app
+    }
+
+    /// A [`WhereWasIConfig`] with `directory` and default settings otherwise
+    pub(crate) fn test_config(directory: &str) -> WhereWasIConfig {
+        WhereWasIConfig {
+            directory: directory.into(),
+            fallback_directory: None,
+            ..default()
+        }
+    }
+
+    /// Request a save as if a window closed, which triggers [`save_state`]
+    pub(crate) fn close_window(app: &mut App) {
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI {
+                trigger: SaveTrigger::WindowClosed,
+                window: None,
+            });
+    }
+
+    fn setup_camera_with_transform(mut commands: Commands<'_, '_>) {
+        commands.spawn((WhereWasI::from_name("system_save_test"), TRANSFORM));
+    }
+
+    fn setup_camera_without_transform(mut commands: Commands<'_, '_>) {
+        commands.spawn((bevy::camera::Camera::default(), WhereWasI::camera()));
+    }
+
+    fn setup_entity_with_default(mut commands: Commands<'_, '_>) {
+        commands.spawn(WhereWasI::from_name("no_savefile").or_default(TRANSFORM));
+    }
+
+    fn setup_entity_with_invalid_savefile(mut commands: Commands<'_, '_>) {
+        commands.spawn(WhereWasI::from_name("invalid_version"));
+    }
+
+    #[test]
+    fn test_save() {
+        let mut app = test_app(test_config("assets/tests/save"));
+
+        if let Ok(true) = fs::exists("assets/tests/save") {
+            fs::remove_dir_all("assets/tests/save").unwrap();
+        }
+        assert!(!fs::exists(SAVE_STATE_FILE).unwrap());
+
+        app.add_systems(Startup, setup_camera_with_transform);
+        app.add_systems(Update, save_state);
+
+        close_window(&mut app);
+        app.update();
+
+        let file = File::open(SAVE_STATE_FILE).unwrap();
+        assert_eq!(
+            deserialize_transform(BufReader::new(file)).unwrap(),
+            TRANSFORM
+        );
+
+        let session = read_session(Path::new("assets/tests/save"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.entities, 1);
+
+        fs::remove_dir_all("assets/tests/save").unwrap();
+    }
+
+    #[test]
+    fn test_load() {
+        let mut app = test_app(test_config("assets/tests"));
+
+        app.add_systems(Startup, setup_camera_without_transform);
+        app.add_systems(Update, (read_saves, load_state).chain());
+
+        app.update();
+
+        let result = app
+            .world_mut()
+            .query::<&Transform>()
+            .single(app.world())
+            .expect("`load_state` should have added a Transform to the world");
+
+        const TRANSFORM: Transform = Transform {
+            translation: Vec3::new(10.000002, 10.0, 10.0),
+            rotation: Quat::from_xyzw(-0.27984813, 0.36470526, 0.11591691, 0.88047624),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+        };
+        assert_eq!(*result, TRANSFORM);
+
+        let loaded = app.world().resource::<Messages<WhereWasILoaded>>();
+        let loaded = loaded.iter_current_update_messages().collect::<Vec<_>>();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "camera");
+        assert_eq!(loaded[0].previous, Transform::IDENTITY);
+        assert_eq!(loaded[0].restored, TRANSFORM);
+
+        let completed = app.world().resource::<Messages<WhereWasILoadCompleted>>();
+        let completed = completed.iter_current_update_messages().collect::<Vec<_>>();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].restored, vec!["camera"]);
+        assert!(completed[0].failed.is_empty());
+    }
+
+    #[derive(Resource, Default)]
+    struct Observed(Vec<&'static str>);
+
+    #[test]
+    fn test_observers() {
+        let spawn_observed = |app: &mut App| {
+            app.init_resource::<Observed>();
+            app.world_mut()
+                .spawn(WhereWasI::camera())
+                .observe(|_: On<WhereWasIRestored>, mut observed: ResMut<Observed>| {
+                    observed.0.push("restored");
+                })
+                .observe(
+                    |_: On<WhereWasIPersisted>, mut observed: ResMut<Observed>| {
+                        observed.0.push("persisted");
+                    },
+                );
+        };
+
+        let mut app = test_app(WhereWasIConfig {
+            skip_unchanged: false,
+            ..test_config("assets/tests/observers")
+        });
+        app.add_systems(Update, (read_saves, load_state, save_state).chain());
+        spawn_observed(&mut app);
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI::default());
+        app.update();
+        assert_eq!(app.world().resource::<Observed>().0, vec!["persisted"]);
+
+        let mut app = test_app(test_config("assets/tests/observers"));
+        app.add_systems(Update, (read_saves, load_state).chain());
+        spawn_observed(&mut app);
+        app.update();
+        assert_eq!(app.world().resource::<Observed>().0, vec!["restored"]);
+        assert_eq!(
+            app.world()
+                .resource::<WhereWasISaves>()
+                .get("camera")
+                .unwrap()
+                .trigger,
+            Some(SaveTrigger::Manual)
+        );
+
+        fs::remove_dir_all("assets/tests/observers").unwrap();
+    }
+
+    #[test]
+    fn test_load_late_spawn() {
+        let mut app = test_app(test_config("assets/tests"));
+
+        app.add_systems(Startup, setup_entity_with_default);
+        app.add_systems(PreStartup, read_saves);
+        app.add_systems(Update, load_state);
+
+        app.update();
+        let entity = app
+            .world_mut()
+            .spawn((WhereWasI::camera(), Transform::IDENTITY))
+            .id();
+        app.update();
+
+        let camera = app.world().get::<Transform>(entity).unwrap();
+        assert_eq!(camera.translation, Vec3::new(10.000002, 10.0, 10.0));
+
+        let loaded = app.world().resource::<Messages<WhereWasILoaded>>();
+        let names = loaded
+            .iter_current_update_messages()
+            .map(|loaded| loaded.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["camera"]);
+    }
+
+    #[test]
+    fn test_load_clamps_translation() {
+        let mut app = test_app(test_config("assets/tests"));
+
+        app.add_systems(Startup, |mut commands: Commands<'_, '_>| {
+            commands.spawn(
+                WhereWasI::camera().clamp_translation(Aabb3d::new(Vec3::ZERO, Vec3::splat(5.0))),
+            );
+        });
+        app.add_systems(Update, (read_saves, load_state).chain());
+
+        app.update();
+
+        let result = app
+            .world_mut()
+            .query::<&Transform>()
+            .single(app.world())
+            .unwrap();
+        assert_eq!(result.translation, Vec3::splat(5.0));
+    }
+
+    #[test]
+    fn test_load_falls_back_to_default() {
+        let mut app = test_app(test_config("assets/tests"));
+
+        app.add_systems(Startup, setup_entity_with_default);
+        app.add_systems(Update, (read_saves, load_state).chain());
+
+        app.update();
+
+        let result = app
+            .world_mut()
+            .query::<&Transform>()
+            .single(app.world())
+            .expect("`setup_entity_with_default` should have spawned a single entity");
+
+        assert_eq!(*result, TRANSFORM);
+    }
+
+    #[test]
+    fn test_load_restore_condition() {
+        let mut app = test_app(test_config("assets/tests"));
+        app.add_systems(Update, (read_saves, load_state).chain());
+        let camera = app
+            .world_mut()
+            .spawn(
+                WhereWasI::camera()
+                    .or_default(TRANSFORM)
+                    .restore_if(RestoreCondition::Custom(|_, _| false)),
+            )
+            .id();
+        app.update();
+
+        assert_eq!(*app.world().get::<Transform>(camera).unwrap(), TRANSFORM);
+        assert!(
+            app.world()
+                .resource::<Messages<WhereWasILoaded>>()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_load_ttl() {
+        let ttl_app = |ttl: Duration| {
+            let mut app = test_app(test_config("assets/tests"));
+            // Pretend the savefile was written two days ago
+            let saved_days_ago = |mut saves: ResMut<WhereWasISaves>| {
+                let mut record = saves.get("camera").unwrap().clone();
+                record.modified =
+                    Some(std::time::SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60));
+                saves.insert("camera", record);
+            };
+            app.add_systems(Update, (read_saves, saved_days_ago, load_state).chain());
+            let camera = app
+                .world_mut()
+                .spawn(WhereWasI::camera().or_default(TRANSFORM).ttl(ttl))
+                .id();
+            app.update();
+            *app.world().get::<Transform>(camera).unwrap()
+        };
+
+        assert_eq!(ttl_app(Duration::from_secs(24 * 60 * 60)), TRANSFORM);
+        assert_ne!(ttl_app(Duration::from_secs(3 * 24 * 60 * 60)), TRANSFORM);
+    }
+
+    #[test]
+    fn test_load_stale_content() {
+        let stale_app = |hash: &str| {
+            let mut app = test_app(WhereWasIConfig {
+                stale_content: StaleContentPolicy::Ask,
+                ..test_config("assets/tests")
+            });
+            // Pretend the previous session saved with another hash, after `read_saves` read it
+            let saved_with_hash = |mut session: ResMut<WhereWasISession>| {
+                session.previous = Some(SessionMetadata {
+                    saved_at: std::time::SystemTime::now(),
+                    entities: 1,
+                    app_version: None,
+                    trigger: None,
+                    triggers: BTreeMap::new(),
+                    saved: BTreeMap::new(),
+                    content_hash: Some("level-v1".into()),
+                });
+            };
+            app.insert_resource(WhereWasIContentHash::new(hash))
+                .add_systems(Update, (read_saves, saved_with_hash, load_state).chain());
+            let camera = app
+                .world_mut()
+                .spawn(WhereWasI::camera().or_default(TRANSFORM))
+                .id();
+            app.update();
+            (app, camera)
+        };
+
+        let (app, camera) = stale_app("level-v2");
+        assert_eq!(*app.world().get::<Transform>(camera).unwrap(), TRANSFORM);
+        let stale = app.world().resource::<Messages<WhereWasIStaleContent>>();
+        assert_eq!(
+            stale.iter_current_update_messages().collect::<Vec<_>>(),
+            vec![&WhereWasIStaleContent {
+                entity: camera,
+                name: "camera".into(),
+                saved: "level-v1".into(),
+                current: "level-v2".into(),
+            }]
+        );
+
+        let (app, camera) = stale_app("level-v1");
+        assert_ne!(*app.world().get::<Transform>(camera).unwrap(), TRANSFORM);
+        assert!(
+            app.world()
+                .resource::<Messages<WhereWasIStaleContent>>()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_load_failed() {
+        let mut app = test_app(test_config("assets/tests"));
+
+        app.add_systems(Startup, setup_entity_with_invalid_savefile);
+        app.add_systems(Update, (read_saves, load_state).chain());
+
+        app.update();
+
+        let failures = app.world().resource::<Messages<WhereWasILoadFailed>>();
+        let errors = failures
+            .iter_current_update_messages()
+            .map(|failure| &failure.error)
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            errors.as_slice(),
+            [WhereWasIParseError::UnsupportedVersion { found, .. }] if found == "v1"
+        ));
+
+        let completed = app.world().resource::<Messages<WhereWasILoadCompleted>>();
+        let failed = completed
+            .iter_current_update_messages()
+            .flat_map(|load| load.failed.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec!["invalid_version"]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Could not load savefile(s): invalid_version: Line 1: unsupported version"
+    )]
+    fn test_load_failed_strict() {
+        let mut app = test_app(WhereWasIConfig {
+            strict: true,
+            ..test_config("assets/tests")
+        });
+
+        app.add_systems(Startup, setup_entity_with_invalid_savefile);
+        app.add_systems(Update, (read_saves, load_state).chain());
+
+        app.update();
+    }
+
+    #[test]
+    fn test_load_disabled() {
+        let mut app = test_app(WhereWasIConfig {
+            restore_on_startup: false,
+            ..test_config("assets/tests")
+        });
+        app.add_systems(Startup, setup_camera_without_transform);
+        app.add_systems(Update, (read_saves, load_state).chain());
+
+        app.update();
+
+        let result = app
+            .world_mut()
+            .query::<&Transform>()
+            .single(app.world())
+            .unwrap();
+        assert_eq!(*result, Transform::IDENTITY);
+    }
+
+    #[test]
+    fn test_load_ignores_expired() {
+        let directory = Path::new("assets/tests/expired");
+        fs::create_dir_all(directory).unwrap();
+        fs::copy("assets/tests/camera.state", directory.join("camera.state")).unwrap();
+        File::options()
+            .write(true)
+            .open(directory.join("camera.state"))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60))
+            .unwrap();
+
+        let mut app = test_app(WhereWasIConfig {
+            max_age: Some(Duration::from_secs(24 * 60 * 60)),
+            delete_expired: true,
+            ..test_config("assets/tests/expired")
+        });
+        app.add_systems(Startup, setup_camera_without_transform);
+        app.add_systems(Update, (read_saves, load_state).chain());
+
+        app.update();
+
+        let result = app
+            .world_mut()
+            .query::<&Transform>()
+            .single(app.world())
+            .unwrap();
+        assert_eq!(*result, Transform::IDENTITY);
+        assert!(!fs::exists(directory.join("camera.state")).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut app = test_app(test_config("assets/tests"));
+        let filepath = state_path(Path::new("assets/tests"), "system_reset_test").unwrap();
+
+        let mut writer = BufWriter::new(File::create(&filepath).unwrap());
+        serialize_transform(&mut writer, &TRANSFORM).unwrap();
+        drop(writer);
+
+        app.add_systems(Startup, |mut commands: Commands<'_, '_>| {
+            commands.spawn(WhereWasI::from_name("system_reset_test"));
+        });
+        app.add_systems(PostStartup, (read_saves, load_state).chain());
+        app.add_systems(Update, reset_state);
+
+        app.update();
+
+        let entity = app
+            .world_mut()
+            .query_filtered::<Entity, With<WhereWasI>>()
+            .single(app.world())
+            .unwrap();
+        assert_eq!(*app.world().get::<Transform>(entity).unwrap(), TRANSFORM);
+
+        app.world_mut()
+            .resource_mut::<Messages<ResetWhereWasI>>()
+            .write(ResetWhereWasI { entity });
+        app.update();
+
+        assert_eq!(
+            *app.world().get::<Transform>(entity).unwrap(),
+            Transform::IDENTITY
+        );
+        assert!(!fs::exists(filepath).unwrap());
+
+        // Undoing the reset brings back the restored transform
+        app.add_systems(Update, undo_redo);
+        app.world_mut()
+            .resource_mut::<Messages<UndoWhereWasI>>()
+            .write(UndoWhereWasI { entity });
+        app.update();
+
+        assert_eq!(*app.world().get::<Transform>(entity).unwrap(), TRANSFORM);
+    }
+
+    #[test]
+    fn test_save_after_reset() {
+        let directory = Path::new("assets/tests/reset_save");
+        let filepath = state_path(directory, "camera").unwrap();
+        write_savefile(&filepath, &TRANSFORM, SaveFormat::Text).unwrap();
+        let mut app = test_app(test_config("assets/tests/reset_save"));
+        app.add_systems(PostStartup, (read_saves, load_state).chain())
+            .add_systems(Update, (reset_state, save_state).chain());
+        let entity = app.world_mut().spawn(WhereWasI::camera()).id();
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<Messages<ResetWhereWasI>>()
+            .write(ResetWhereWasI { entity });
+        app.update();
+        assert!(!fs::exists(&filepath).unwrap());
+        assert!(!app.world().resource::<WhereWasISaves>().contains("camera"));
+
+        // Back at the pose of the removed savefile, which is written again
+        *app.world_mut().get_mut::<Transform>(entity).unwrap() = TRANSFORM;
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI {
+                trigger: SaveTrigger::Manual,
+                window: None,
+            });
+        app.update();
+        assert_eq!(read_state(&filepath).unwrap(), TRANSFORM);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_save_rejects_path_traversal() {
+        let mut app = test_app(test_config("assets/tests/traversal/saves"));
+
+        app.add_systems(Startup, |mut commands: Commands<'_, '_>| {
+            commands.spawn(WhereWasI::from_name("../oops"));
+        });
+        app.add_systems(Update, save_state);
+
+        close_window(&mut app);
+        app.update();
+
+        let failures = app.world().resource::<Messages<WhereWasISaveFailed>>();
+        let kinds = failures
+            .iter_current_update_messages()
+            .map(|failure| failure.error.kind())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec![io::ErrorKind::InvalidInput]);
+        assert!(!fs::exists("assets/tests/traversal").unwrap());
+    }
+
+    #[test]
+    fn test_save_failure_does_not_panic() {
+        // A directory can't be created inside a regular file
+        let mut app = test_app(test_config("assets/tests/identity.state/saves"));
+
+        app.add_systems(Startup, setup_camera_with_transform);
+        app.add_systems(Update, save_state);
+
+        close_window(&mut app);
+        app.update();
+
+        let failures = app.world().resource::<Messages<WhereWasISaveFailed>>();
+        let failed = failures
+            .iter_current_update_messages()
+            .map(|failure| failure.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec!["system_save_test"]);
+    }
+
+    #[test]
+    fn test_validate_save() {
+        let mut app = test_app(WhereWasIConfig {
+            validate_save: Some(|name, transform| {
+                if name == "system_save_test" && transform.translation.y > 0.0 {
+                    return Err("too high".into());
+                }
+                Ok(())
+            }),
+            ..test_config("assets/tests/validate_save")
+        });
+
+        app.add_systems(Startup, setup_camera_with_transform);
+        app.add_systems(Update, save_state);
+
+        close_window(&mut app);
+        app.update();
+
+        let failures = app.world().resource::<Messages<WhereWasISaveFailed>>();
+        let errors = failures
+            .iter_current_update_messages()
+            .map(|failure| failure.error.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec!["Rejected by validate_save: too high"]);
+        assert!(!fs::exists("assets/tests/validate_save").unwrap());
+    }
+
+    #[test]
+    fn test_prepare_save() {
+        let mut app = test_app(WhereWasIConfig {
+            prepare_save: Some(|name, transform| {
+                if name == "rejected" {
+                    return Err("not today".into());
+                }
+                transform.translation = transform.translation.round();
+                Ok(())
+            }),
+            ..test_config("assets/tests/prepare_save")
+        });
+        app.add_systems(Update, save_state);
+        let camera = app
+            .world_mut()
+            .spawn((WhereWasI::camera(), Transform::from_xyz(1.4, 2.6, -0.2)))
+            .id();
+        app.world_mut().spawn(WhereWasI::from_name("rejected"));
+
+        close_window(&mut app);
+        app.update();
+
+        assert_eq!(
+            read_state("assets/tests/prepare_save/camera.state").unwrap(),
+            Transform::from_xyz(1.0, 3.0, -0.0)
+        );
+        assert_eq!(
+            app.world().get::<Transform>(camera).unwrap().translation,
+            Vec3::new(1.4, 2.6, -0.2)
+        );
+        let failures = app.world().resource::<Messages<WhereWasISaveFailed>>();
+        let errors = failures
+            .iter_current_update_messages()
+            .map(|failure| failure.error.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec!["Rejected by prepare_save: not today"]);
+
+        fs::remove_dir_all("assets/tests/prepare_save").unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "window")]
+    fn test_save_requests_are_coalesced() {
+        let mut app = test_app(test_config("assets/tests/coalesced_save"));
+        app.add_message::<WindowClosing>();
+        app.add_systems(Update, (request_save_on_close, save_state).chain());
+        app.world_mut()
+            .spawn((WhereWasI::camera(), Transform::IDENTITY));
+
+        for window in [1, 2] {
+            app.world_mut()
+                .resource_mut::<Messages<WindowClosing>>()
+                .write(WindowClosing {
+                    window: Entity::from_raw_u32(window).unwrap(),
+                });
+        }
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI::default());
+        app.update();
+
+        let completed = app.world().resource::<Messages<WhereWasISaveCompleted>>();
+        let triggers = completed
+            .iter_current_update_messages()
+            .map(|save| save.trigger)
+            .collect::<Vec<_>>();
+        assert_eq!(triggers, vec![SaveTrigger::WindowClosed]);
+        assert!(
+            completed
+                .iter_current_update_messages()
+                .all(|save| save.bytes > 0 && save.failed.is_empty())
+        );
+        let session = read_session(Path::new("assets/tests/coalesced_save"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.trigger, Some(SaveTrigger::WindowClosed));
+
+        app.update();
+        let completed = app.world().resource::<Messages<WhereWasISaveCompleted>>();
+        assert_eq!(completed.iter_current_update_messages().count(), 0);
+
+        fs::remove_dir_all("assets/tests/coalesced_save").unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "window")]
+    fn test_close_secondary_window() {
+        let mut app = test_app(WhereWasIConfig {
+            skip_unchanged: false,
+            ..test_config("assets/tests/secondary_window")
+        });
+        app.add_message::<WindowClosing>();
+        app.add_systems(Update, (request_save_on_close, save_state).chain());
+        let primary = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+        let secondary = app.world_mut().spawn(Window::default()).id();
+        app.world_mut().spawn(WhereWasI::camera());
+        app.world_mut()
+            .spawn(WhereWasI::from_name("preview").in_window(secondary));
+
+        let saved = |app: &mut App, window| {
+            app.world_mut()
+                .resource_mut::<Messages<WindowClosing>>()
+                .write(WindowClosing { window });
+            app.update();
+            app.world()
+                .resource::<Messages<WhereWasISaveCompleted>>()
+                .iter_current_update_messages()
+                .flat_map(|save| save.saved.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(saved(&mut app, secondary), vec!["preview"]);
+        assert_eq!(saved(&mut app, primary), vec!["camera", "preview"]);
+
+        app.world_mut()
+            .resource_mut::<WhereWasIConfig>()
+            .set_save_on_close(SaveOnClose::PrimaryWindow);
+        assert!(saved(&mut app, secondary).is_empty());
+        assert_eq!(saved(&mut app, primary), vec!["camera", "preview"]);
+
+        app.world_mut()
+            .resource_mut::<WhereWasIConfig>()
+            .set_save_on_close(SaveOnClose::Window(secondary));
+        assert!(saved(&mut app, primary).is_empty());
+        assert_eq!(saved(&mut app, secondary), vec!["preview"]);
+
+        fs::remove_dir_all("assets/tests/secondary_window").unwrap();
+    }
+
+    #[test]
+    fn test_skip_unchanged() {
+        let mut app = test_app(test_config("assets/tests/skip_unchanged"));
+        app.world_mut().resource_mut::<WhereWasISaves>().insert(
+            "system_save_test",
+            SaveRecord {
+                transform: TRANSFORM,
+                modified: None,
+                trigger: None,
+            },
+        );
+
+        app.add_systems(Startup, setup_camera_with_transform);
+        app.add_systems(Update, save_state);
+
+        close_window(&mut app);
+        app.update();
+        assert!(!fs::exists("assets/tests/skip_unchanged").unwrap());
+
+        app.world_mut()
+            .query::<&mut Transform>()
+            .single_mut(app.world_mut())
+            .unwrap()
+            .translation
+            .x += 1.0;
+        close_window(&mut app);
+        app.update();
+        assert!(fs::exists("assets/tests/skip_unchanged/system_save_test.state").unwrap());
+
+        fs::remove_dir_all("assets/tests/skip_unchanged").unwrap();
+    }
+
+    #[test]
+    fn test_follow() {
+        let directory = "assets/tests/follow";
+        let config = WhereWasIConfig {
+            name_collisions: NameCollisionPolicy::Suffix,
+            ..test_config(directory)
+        };
+        write_state(&config, "camera", &TRANSFORM).unwrap();
+
+        let mut app = test_app(config);
+        app.add_systems(Update, (read_saves, load_state, save_state).chain());
+        let minimap = app.world_mut().spawn(WhereWasI::follow("camera")).id();
+        let camera = app.world_mut().spawn(WhereWasI::camera()).id();
+        app.update();
+
+        assert_eq!(*app.world().get::<Transform>(camera).unwrap(), TRANSFORM);
+        assert_eq!(*app.world().get::<Transform>(minimap).unwrap(), TRANSFORM);
+
+        app.world_mut()
+            .get_mut::<Transform>(minimap)
+            .unwrap()
+            .translation
+            .x = 1.0;
+        close_window(&mut app);
+        app.update();
+
+        assert_eq!(
+            read_state(format!("{directory}/camera.state")).unwrap(),
+            TRANSFORM
+        );
+        assert!(!fs::exists(format!("{directory}/camera_1.state")).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let mut app = test_app(WhereWasIConfig {
+            dry_run: true,
+            ..test_config("assets/tests/dry_run")
+        });
+
+        app.add_systems(Startup, setup_camera_with_transform);
+        app.add_systems(Update, save_state);
+
+        close_window(&mut app);
+        app.update();
+
+        let metrics = app.world().resource::<SaveMetrics>();
+        assert!(metrics.last.unwrap().bytes > 0);
+        assert!(app.world().resource::<WhereWasISaves>().is_empty());
+        assert!(!fs::exists("assets/tests/dry_run").unwrap());
+    }
+
+    #[test]
+    fn test_types_are_registered() {
+        let mut app = App::new();
+        app.add_plugins(WhereWasIPlugin::default());
+
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        assert!(
+            registry
+                .get_type_data::<ReflectComponent>(TypeId::of::<WhereWasI>())
+                .is_some()
+        );
+        assert!(
+            registry
+                .get_type_data::<ReflectComponent>(TypeId::of::<WhereWasIHistory>())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_multiple_instances() {
+        let mut app = App::new();
+        app.add_plugins((
+            WhereWasIPlugin::from_directory("assets/tests/instances_local"),
+            WhereWasIPlugin {
+                instance: Some("shared".into()),
+                groups: BTreeMap::from([("editor_ui".into(), SaveGroup::default())]),
+                ..WhereWasIPlugin::from_directory("assets/tests/instances_shared")
+            },
+        ));
+
+        let config = app.world().resource::<WhereWasIConfig>();
+        assert_eq!(
+            config.group(None).directory(),
+            Path::new("assets/tests/instances_local")
+        );
+        assert_eq!(
+            config.group(Some("shared")).directory(),
+            Path::new("assets/tests/instances_shared")
+        );
+        assert_eq!(
+            config.group(Some("editor_ui")).directory(),
+            Path::new("assets/tests/instances_shared/editor_ui")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "window")]
+    fn test_save_on_suspend() {
+        let directory = Path::new("assets/tests/save_on_suspend");
+        let mut app = test_app(test_config("assets/tests/save_on_suspend"));
+        app.add_message::<AppLifecycle>()
+            .add_systems(Update, (request_save_on_suspend, save_state).chain())
+            .add_systems(PostStartup, load_state);
+        app.world_mut()
+            .spawn((WhereWasI::camera(), Transform::from_xyz(1.0, 2.0, 3.0)));
+
+        for lifecycle in [AppLifecycle::Running, AppLifecycle::WillResume] {
+            app.world_mut().write_message(lifecycle);
+            app.update();
+        }
+        assert!(!fs::exists(state_path(directory, "camera").unwrap()).unwrap());
+
+        app.world_mut().write_message(AppLifecycle::WillSuspend);
+        app.update();
+        let session = read_session(directory).unwrap().unwrap();
+        assert_eq!(session.trigger, Some(SaveTrigger::Suspended));
+
+        // The application may be stopped while suspended, and restores at the next start
+        let mut app = test_app(test_config("assets/tests/save_on_suspend"));
+        app.add_systems(PreStartup, read_saves)
+            .add_systems(PostStartup, load_state);
+        let camera = app.world_mut().spawn(WhereWasI::camera()).id();
+        app.update();
+        assert_eq!(
+            app.world().get::<Transform>(camera),
+            Some(&Transform::from_xyz(1.0, 2.0, 3.0))
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_save_on_exit() {
+        let directory = Path::new("assets/tests/save_on_exit");
+        let mut app = test_app(WhereWasIConfig {
+            skip_unchanged: false,
+            ..test_config("assets/tests/save_on_exit")
+        });
+        app.add_message::<AppExit>()
+            .add_systems(Update, save_state)
+            .add_systems(Last, save_on_exit);
+        app.world_mut()
+            .spawn((WhereWasI::camera(), Transform::from_xyz(1.0, 2.0, 3.0)));
+
+        // Closing the last window exits the application, which doesn't save twice
+        close_window(&mut app);
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+        let session = read_session(directory).unwrap().unwrap();
+        assert_eq!(session.trigger, Some(SaveTrigger::WindowClosed));
+
+        fs::remove_dir_all(directory).unwrap();
+        app.update();
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+        let session = read_session(directory).unwrap().unwrap();
+        assert_eq!(session.trigger, Some(SaveTrigger::Exit));
+        assert_eq!(
+            read_state(state_path(directory, "camera").unwrap()).unwrap(),
+            Transform::from_xyz(1.0, 2.0, 3.0)
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_probe_directory_when_added() {
+        let mut app = App::new();
+        app.add_plugins(WhereWasIPlugin {
+            fallback_directory: None,
+            ..WhereWasIPlugin::from_directory("assets/tests/identity.state/saves")
+        });
+
+        let probe = app.world().resource::<WhereWasIDirectoryProbe>();
+        assert!(!probe.is_writable());
+        assert!(app.world().contains_resource::<PersistenceUnavailable>());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let directory = "assets/tests/disabled";
+        let mut app = App::new();
+        app.add_plugins(WhereWasIPlugin {
+            enabled: false,
+            ..WhereWasIPlugin::from_directory(directory)
+        });
+        app.world_mut().spawn(WhereWasI::camera());
+        app.update();
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI::default());
+        app.update();
+
+        assert!(!fs::exists(directory).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "added more than once")]
+    fn test_instance_without_label() {
+        App::new().add_plugins((
+            WhereWasIPlugin::from_directory("assets/tests/instances_first"),
+            WhereWasIPlugin::from_directory("assets/tests/instances_second"),
+        ));
     }
 }
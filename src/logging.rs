@@ -0,0 +1,36 @@
+/// The target of the summaries that are logged after every save and restore
+///
+/// Filter it to silence the summaries without silencing the warnings and errors of the plugin, for
+/// example with `RUST_LOG=bevy_where_was_i::summary=warn`.
+pub const SUMMARY_LOG_TARGET: &str = "bevy_where_was_i::summary";
+
+/// The level the summaries of saves and restores are logged at, see
+/// [`WhereWasIPlugin::summary_log_level`](crate::WhereWasIPlugin::summary_log_level)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SummaryLogLevel {
+    /// Don't log summaries
+    Off,
+    /// Log summaries at the debug level
+    Debug,
+    /// Log summaries at the info level
+    #[default]
+    Info,
+}
+
+/// Log a summary at a [`SummaryLogLevel`], with the [`SUMMARY_LOG_TARGET`] target and structured
+/// fields like `tracing`'s macros
+macro_rules! log_summary {
+    ($level:expr, $($arg:tt)+) => {
+        match $level {
+            $crate::SummaryLogLevel::Off => {}
+            $crate::SummaryLogLevel::Debug => {
+                ::bevy_log::debug!(target: $crate::SUMMARY_LOG_TARGET, $($arg)+)
+            }
+            $crate::SummaryLogLevel::Info => {
+                ::bevy_log::info!(target: $crate::SUMMARY_LOG_TARGET, $($arg)+)
+            }
+        }
+    };
+}
+
+pub(crate) use log_summary;
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::prelude::*;
+use memmap2::Mmap;
+
+use crate::export::index_archive;
+use crate::names::resolve_tracked_names;
+use crate::serialization::WhereWasIParseError;
+use crate::validation::check_values;
+use crate::{Initialized, SaveFormat, SaveRecord, WhereWasI, WhereWasIConfig, WhereWasISaves};
+
+/// An archive created by [`WhereWasIStore::export_archive`](crate::WhereWasIStore::export_archive),
+/// mapped into memory to restore entities from
+///
+/// Opening the archive only indexes the savefiles in it, a savefile is decoded when it's read. Set
+/// [`WhereWasIPlugin::restore_archive`](crate::WhereWasIPlugin::restore_archive) to restore from it
+/// at startup, which only decodes the savefiles of entities that are tracked. Requires the `mmap`
+/// feature.
+///
+/// ```rust,no_run
+/// use bevy_where_was_i::WhereWasIArchive;
+///
+/// let archive = WhereWasIArchive::open("workspace.wwi").unwrap();
+/// if let Some(Ok(camera)) = archive.get("camera") {
+///     println!("The camera was at {}", camera.translation);
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct WhereWasIArchive {
+    map: Mmap,
+    index: BTreeMap<String, Range<usize>>,
+}
+
+impl WhereWasIArchive {
+    /// Map the archive at `path` into memory and index the savefiles in it
+    ///
+    /// The archive must not be modified while it is open, the savefiles read from it are undefined
+    /// otherwise.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the archive is only read, and must not be modified while it's mapped, see above
+        let map = unsafe { Mmap::map(&file)? };
+        let index = index_archive(&map)?;
+        Ok(Self { map, index })
+    }
+
+    /// The number of savefiles in the archive
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Whether the archive contains no savefiles
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Whether the archive contains a savefile for `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// The names of the savefiles in the archive
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    /// Decode the savefile for `name`, in the format it was written in, or `None` if the archive
+    /// doesn't contain it
+    pub fn get(&self, name: &str) -> Option<Result<Transform, WhereWasIParseError>> {
+        let mut contents = &self.map[self.index.get(name)?.clone()];
+        Some(
+            SaveFormat::detect_buffered(&mut contents)
+                .map_err(WhereWasIParseError::from)
+                .and_then(|format| format.deserialize(contents)),
+        )
+    }
+}
+
+/// Add the savefiles of tracked entities that haven't been loaded yet from the
+/// [`WhereWasIArchive`] to [`WhereWasISaves`], so [`load_state`](crate::load_state) restores them
+///
+/// Savefiles in the save directory take precedence over the archive.
+pub(crate) fn restore_from_archive(
+    tracked: Query<(Entity, &WhereWasI)>,
+    pending: Query<(), (With<WhereWasI>, Without<Initialized>)>,
+    archive: Res<WhereWasIArchive>,
+    mut saves: ResMut<WhereWasISaves>,
+    config: Res<WhereWasIConfig>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+
+    for (entity, name) in resolve_tracked_names(tracked.iter(), config.name_collisions) {
+        if !pending.contains(entity) || saves.contains(&name) || saves.failure(&name).is_some() {
+            continue;
+        }
+        let source = format!("{} in the archive", name);
+        match archive
+            .get(&name)
+            .map(|read| read.and_then(|transform| check_values(transform, &source, &config)))
+        {
+            Some(Ok(transform)) => saves.insert(
+                &name,
+                SaveRecord {
+                    transform,
+                    modified: None,
+                    trigger: None,
+                },
+            ),
+            Some(Err(err)) => error!("Could not read {}: {}", source, err),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use bevy_ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{WhereWasIStore, load_state, read_saves, read_state};
+
+    #[test]
+    fn test_restore_from_archive() {
+        let from = Path::new("assets/tests/mapped_from");
+        fs::create_dir_all(from.join("bookmarks/overview")).unwrap();
+        fs::copy("assets/tests/camera.state", from.join("camera.state")).unwrap();
+        fs::copy("assets/tests/identity.state", from.join("identity.state")).unwrap();
+        fs::copy(
+            "assets/tests/identity.state",
+            from.join("bookmarks/overview/camera.state"),
+        )
+        .unwrap();
+        let mut app = test_app(test_config("assets/tests/mapped_from"));
+        app.world_mut()
+            .run_system_once(|store: WhereWasIStore| {
+                store.export_archive("assets/tests/mapped.wwi").unwrap()
+            })
+            .unwrap();
+
+        let archive = WhereWasIArchive::open("assets/tests/mapped.wwi").unwrap();
+        assert_eq!(
+            archive.names().collect::<Vec<_>>(),
+            vec!["camera", "identity"]
+        );
+        let camera = read_state("assets/tests/camera.state").unwrap();
+        assert_eq!(archive.get("camera").unwrap().unwrap(), camera);
+        assert!(archive.get("missing").is_none());
+
+        let mut app = test_app(test_config("assets/tests/mapped_to"));
+        app.insert_resource(archive).add_systems(
+            Update,
+            (read_saves, restore_from_archive, load_state).chain(),
+        );
+        let entity = app.world_mut().spawn(WhereWasI::camera()).id();
+        app.update();
+
+        assert_eq!(*app.world().get::<Transform>(entity).unwrap(), camera);
+        // Only the savefile of the tracked entity was decoded
+        let saves = app.world().resource::<WhereWasISaves>();
+        assert!(saves.contains("camera"));
+        assert!(!saves.contains("identity"));
+
+        fs::remove_dir_all(from).unwrap();
+        fs::remove_file("assets/tests/mapped.wwi").unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::prelude::*;
+
+use crate::WhereWasI;
+
+/// What to do when several entities share the same [`WhereWasI`](crate::WhereWasI) name
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NameCollisionPolicy {
+    /// Log a warning, all entities share the same savefile
+    #[default]
+    Warn,
+    /// Log an error and neither load nor save any of the entities involved
+    Error,
+    /// Append `_1`, `_2`, ... to the names of all but the first entity, in spawn order
+    Suffix,
+    /// Intentionally share the savefile: all entities restore from it and the last one saved wins
+    Allow,
+}
+
+/// Characters that are not allowed in file names on at least one supported platform
+const FORBIDDEN_CHARACTERS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// File names that are reserved on Windows, regardless of extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// The error returned when a [`WhereWasI`](crate::WhereWasI) name can't be used as a file name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidNameError {
+    /// The name that was rejected
+    pub name: String,
+    /// Why the name was rejected
+    pub reason: &'static str,
+}
+
+impl fmt::Display for InvalidNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid name \"{}\": {}", self.name, self.reason)
+    }
+}
+
+impl Error for InvalidNameError {}
+
+/// Check that `name` can be used as a file name inside the save directory on all platforms
+///
+/// This rejects names that would escape the save directory, such as `../../oops`.
+pub(crate) fn validate_name(name: &str) -> Result<(), InvalidNameError> {
+    let reason = if name.is_empty() {
+        "name is empty"
+    } else if name == "." || name == ".." {
+        "name refers to a directory"
+    } else if name.contains(FORBIDDEN_CHARACTERS) {
+        "name contains one of / \\ : * ? \" < > |"
+    } else if name.contains(char::is_control) {
+        "name contains a control character"
+    } else if name.ends_with(['.', ' ']) || name.starts_with(' ') {
+        "name starts with a space or ends with a space or dot"
+    } else if RESERVED_NAMES.iter().any(|reserved| {
+        name.split('.')
+            .next()
+            .unwrap_or(name)
+            .eq_ignore_ascii_case(reserved)
+    }) {
+        "name is reserved on Windows"
+    } else {
+        return Ok(());
+    };
+
+    Err(InvalidNameError {
+        name: name.into(),
+        reason,
+    })
+}
+
+/// Resolve the name each entity is saved under, according to `policy`
+///
+/// Entities that should neither be loaded nor saved are omitted.
+pub(crate) fn resolve_names<'a>(
+    entities: impl IntoIterator<Item = (Entity, &'a str)>,
+    policy: NameCollisionPolicy,
+) -> Vec<(Entity, String)> {
+    let mut by_name: BTreeMap<&str, Vec<Entity>> = BTreeMap::new();
+    for (entity, name) in entities {
+        by_name.entry(name).or_default().push(entity);
+    }
+
+    let mut resolved = Vec::new();
+    for (name, mut entities) in by_name {
+        entities.sort_by_key(|entity| entity.index_u32());
+
+        if entities.len() > 1 {
+            match policy {
+                NameCollisionPolicy::Warn => {
+                    warn!("Entities {entities:?} share the name \"{name}\"");
+                }
+                NameCollisionPolicy::Error => {
+                    error!("Entities {entities:?} share the name \"{name}\", skipping them");
+                    continue;
+                }
+                NameCollisionPolicy::Suffix => {
+                    resolved.extend(entities.into_iter().enumerate().map(|(i, entity)| {
+                        let name = match i {
+                            0 => name.into(),
+                            i => format!("{name}_{i}"),
+                        };
+                        (entity, name)
+                    }));
+                    continue;
+                }
+                NameCollisionPolicy::Allow => {}
+            }
+        }
+
+        resolved.extend(entities.into_iter().map(|entity| (entity, name.into())));
+    }
+
+    resolved
+}
+
+/// Resolve the name each tracked entity is restored from, according to `policy`
+///
+/// Entities created with [`WhereWasI::follow`] keep the name they follow and are left out of
+/// collision handling, since they never write their savefile.
+pub(crate) fn resolve_tracked_names<'a>(
+    tracked: impl IntoIterator<Item = (Entity, &'a WhereWasI)>,
+    policy: NameCollisionPolicy,
+) -> Vec<(Entity, String)> {
+    let (followers, writers): (Vec<_>, Vec<_>) = tracked
+        .into_iter()
+        .partition(|(_, where_was_i)| where_was_i.follows);
+
+    let mut resolved = resolve_names(
+        writers
+            .into_iter()
+            .map(|(entity, where_was_i)| (entity, where_was_i.name.as_str())),
+        policy,
+    );
+    resolved.extend(
+        followers
+            .into_iter()
+            .map(|(entity, where_was_i)| (entity, where_was_i.name.clone())),
+    );
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw_u32(index).unwrap()
+    }
+
+    fn entities() -> Vec<(Entity, &'static str)> {
+        vec![
+            (entity(3), "camera"),
+            (entity(1), "camera"),
+            (entity(2), "light"),
+        ]
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name("camera"), Ok(()));
+        assert_eq!(validate_name("point light 2"), Ok(()));
+        assert_eq!(validate_name("camera.v2"), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_name_path_traversal() {
+        for name in [
+            "../../oops",
+            "..",
+            ".",
+            "/etc/passwd",
+            "saves\\..\\oops",
+            "C:oops",
+        ] {
+            assert!(validate_name(name).is_err(), "{name} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_validate_name_invalid() {
+        for name in [
+            "",
+            "a:b",
+            "what?",
+            "tab\t",
+            "trailing.",
+            " leading",
+            "con",
+            "NUL.txt",
+        ] {
+            assert!(validate_name(name).is_err(), "{name:?} should be rejected");
+        }
+        assert_eq!(
+            validate_name("../oops").unwrap_err().to_string(),
+            "Invalid name \"../oops\": name contains one of / \\ : * ? \" < > |"
+        );
+    }
+
+    #[test]
+    fn test_resolve_names_warn() {
+        assert_eq!(
+            resolve_names(entities(), NameCollisionPolicy::Warn),
+            vec![
+                (entity(1), "camera".into()),
+                (entity(3), "camera".into()),
+                (entity(2), "light".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_names_error() {
+        assert_eq!(
+            resolve_names(entities(), NameCollisionPolicy::Error),
+            vec![(entity(2), "light".into())]
+        );
+    }
+
+    #[test]
+    fn test_resolve_names_suffix() {
+        assert_eq!(
+            resolve_names(entities(), NameCollisionPolicy::Suffix),
+            vec![
+                (entity(1), "camera".into()),
+                (entity(3), "camera_1".into()),
+                (entity(2), "light".into()),
+            ]
+        );
+    }
+}
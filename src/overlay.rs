@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use crate::prelude::*;
+
+use crate::autosave::Autosave;
+use crate::{TransformDiff, WhereWasIStore};
+
+/// A plugin that shows an on-screen overlay with every tracked entity, whether a savefile exists
+/// for it and whether it moved since it was saved
+///
+/// Requires the `debug-ui` feature. This helps to diagnose why an entity wasn't restored, for
+/// example because its name was misspelled or its savefile was never written.
+///
+/// ```rust,ignore
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasIDebugOverlayPlugin, WhereWasIPlugin};
+///
+/// App::new().add_plugins((WhereWasIPlugin::default(), WhereWasIDebugOverlayPlugin));
+/// ```
+pub struct WhereWasIDebugOverlayPlugin;
+
+impl Plugin for WhereWasIDebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_overlay)
+            .add_systems(Update, update_overlay);
+    }
+}
+
+/// Marks the text entity of the overlay
+#[derive(Component)]
+struct DebugOverlay;
+
+/// Spawn the overlay in the top left corner, on top of everything else
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        DebugOverlay,
+        Text::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        GlobalZIndex(i32::MAX),
+    ));
+}
+
+/// The contents of the overlay for `diffs`, with the time until the next autosave if enabled
+fn overlay_text(diffs: &[TransformDiff], next_autosave: Option<Duration>) -> String {
+    let mut text = String::from("Where was I");
+
+    for diff in diffs {
+        let status = match diff.saved {
+            None => "no savefile",
+            Some(_) if diff.is_dirty() => "saved, moved since",
+            Some(_) => "saved, unchanged",
+        };
+        text.push_str(&format!("\n{}: {}", diff.name, status));
+    }
+
+    match next_autosave {
+        Some(remaining) => text.push_str(&format!(
+            "\nNext autosave in {}s",
+            remaining.as_secs_f32().ceil()
+        )),
+        None => text.push_str("\nSaves when the window closes"),
+    }
+    text
+}
+
+/// Refresh the contents of the overlay
+fn update_overlay(
+    store: WhereWasIStore,
+    autosave: Option<Res<Autosave>>,
+    mut overlays: Query<&mut Text, With<DebugOverlay>>,
+) {
+    let contents = overlay_text(&store.diff(), autosave.map(|autosave| autosave.remaining()));
+
+    for mut text in &mut overlays {
+        if text.0 != contents {
+            text.0.clone_from(&contents);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_text() {
+        let diff = |name: &str, saved| TransformDiff {
+            entity: Entity::PLACEHOLDER,
+            name: name.into(),
+            current: Transform::IDENTITY,
+            saved,
+            authored: None,
+        };
+
+        assert_eq!(
+            overlay_text(
+                &[
+                    diff("camera", Some(Transform::IDENTITY)),
+                    diff("light", Some(Transform::from_xyz(1.0, 0.0, 0.0))),
+                    diff("door", None),
+                ],
+                None
+            ),
+            "Where was I\ncamera: saved, unchanged\nlight: saved, moved since\ndoor: no savefile\n\
+             Saves when the window closes"
+        );
+        assert_eq!(
+            overlay_text(&[], Some(Duration::from_millis(12_300))),
+            "Where was I\nNext autosave in 13s"
+        );
+    }
+}
@@ -0,0 +1,964 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+use bevy_ecs::component::Mutable;
+
+use crate::names::{resolve_names, resolve_tracked_names, validate_name};
+use crate::throttle::SaveAdmitted;
+use crate::{Field, WhereWasI, WhereWasIConfig, WhereWasIParseError, WhereWasISaveFailed};
+
+/// A value that can be stored in a field of a [`Persist`] struct
+///
+/// This is implemented for floats, integers, `bool` and `String`.
+pub trait PersistValue: Sized {
+    /// The value as a single line of text
+    fn to_text(&self) -> String;
+
+    /// Parse a value written by [`to_text`](Self::to_text), or `None` if `text` isn't one
+    fn from_text(text: &str) -> Option<Self>;
+}
+
+macro_rules! impl_persist_value {
+    ($($ty:ty),*) => {
+        $(
+            impl PersistValue for $ty {
+                fn to_text(&self) -> String {
+                    self.to_string()
+                }
+
+                fn from_text(text: &str) -> Option<Self> {
+                    text.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_persist_value!(
+    f32, f64, bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl PersistValue for String {
+    /// Backslashes and line breaks are escaped, so every string fits on one line
+    fn to_text(&self) -> String {
+        self.replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    }
+
+    fn from_text(text: &str) -> Option<Self> {
+        let mut value = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(char) = chars.next() {
+            if char != '\\' {
+                value.push(char);
+                continue;
+            }
+            value.push(match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            });
+        }
+        Some(value)
+    }
+}
+
+/// The fields of a [`Persist`] struct, in the order they were written
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PersistedFields {
+    version: u32,
+    fields: Vec<(String, String)>,
+}
+
+impl PersistedFields {
+    /// The [`Persist::VERSION`] of the struct the fields were written by
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Store `value` as the field `name`, replacing a field with the same name
+    pub fn insert(&mut self, name: &str, value: &impl PersistValue) {
+        let value = value.to_text();
+        match self.fields.iter_mut().find(|(field, _)| field == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.fields.push((name.into(), value)),
+        }
+    }
+
+    /// The value of the field `name`, or `None` if there is no such field
+    ///
+    /// Fails with [`WhereWasIParseError::InvalidValue`] if the field can't be parsed as a `T`.
+    pub fn get<T: PersistValue>(&self, name: &str) -> Result<Option<T>, WhereWasIParseError> {
+        let Some((_, value)) = self.fields.iter().find(|(field, _)| field == name) else {
+            return Ok(None);
+        };
+        T::from_text(value)
+            .map(Some)
+            .ok_or_else(|| WhereWasIParseError::InvalidValue {
+                field: name.into(),
+                value: value.clone(),
+            })
+    }
+
+    /// Remove the field `name`, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.fields.len();
+        self.fields.retain(|(field, _)| field != name);
+        self.fields.len() != len
+    }
+
+    /// Rename the field `from` to `to`, replacing a field named `to`
+    pub fn rename(&mut self, from: &str, to: &str) {
+        let Some(index) = self.fields.iter().position(|(field, _)| field == from) else {
+            return;
+        };
+        let (_, value) = self.fields.remove(index);
+        self.fields.retain(|(field, _)| field != to);
+        self.fields
+            .insert(index.min(self.fields.len()), (to.into(), value));
+    }
+
+    /// The names of the fields
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(field, _)| field.as_str())
+    }
+
+    /// Read the fields from the file of a [`Persist`] struct at `path`, without migrating them
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, WhereWasIParseError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Serialize the fields, each as a `name:` line followed by the value
+    ///
+    /// A `version` line follows the format version, unless the version of the struct is 0.
+    fn serialize(&self) -> String {
+        let mut contents = String::from("v0\n");
+        if self.version != 0 {
+            contents.push_str(&format!("version {}\n", self.version));
+        }
+        for (name, value) in &self.fields {
+            contents.push_str(&format!("\n{name}:\n{value}\n"));
+        }
+        contents
+    }
+
+    /// Parse fields written by [`serialize`](Self::serialize)
+    ///
+    /// Values are taken verbatim, so strings keep their leading and trailing whitespace.
+    fn parse(contents: &str) -> Result<Self, WhereWasIParseError> {
+        let mut lines = contents
+            .lines()
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+            .peekable();
+
+        match lines.next() {
+            Some((_, "v0")) => {}
+            found => {
+                return Err(WhereWasIParseError::UnsupportedVersion {
+                    line: 1,
+                    found: found.map_or("", |(_, line)| line).into(),
+                });
+            }
+        }
+
+        let mut fields = Self::default();
+        if let Some((line, version)) = lines.next_if(|(_, line)| line.starts_with("version")) {
+            fields.version = version
+                .strip_prefix("version ")
+                .and_then(|version| version.parse().ok())
+                .ok_or_else(|| WhereWasIParseError::UnsupportedVersion {
+                    line,
+                    found: version.into(),
+                })?;
+        }
+        while let Some((line, key)) = lines.next() {
+            if key.trim().is_empty() {
+                continue;
+            }
+            let Some(name) = key.strip_suffix(':') else {
+                return Err(WhereWasIParseError::UnexpectedLine {
+                    line,
+                    expected: Field::PersistedField,
+                    found: key.into(),
+                });
+            };
+            let Some((_, value)) = lines.next() else {
+                return Err(WhereWasIParseError::MissingLine {
+                    line: line + 1,
+                    expected: Field::PersistedField,
+                });
+            };
+            fields.fields.push((name.into(), value.into()));
+        }
+        Ok(fields)
+    }
+}
+
+/// A struct that is saved and restored field by field, see
+/// [`WhereWasIAppExt::persist_resource`](crate::WhereWasIAppExt::persist_resource) and
+/// [`WhereWasIAppExt::persist_component`](crate::WhereWasIAppExt::persist_component)
+///
+/// With the `derive` feature, `#[derive(Persist)]` implements this for structs with named fields
+/// whose types implement [`PersistValue`]. Fields marked with `#[persist(skip)]` are left out and
+/// `#[persist(version = 2)]` on the struct sets its [`VERSION`](Self::VERSION).
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{Persist, PersistedFields, WhereWasIParseError};
+///
+/// #[derive(Resource)]
+/// struct Workspace {
+///     zoom: f32,
+///     show_grid: bool,
+/// }
+///
+/// impl Persist for Workspace {
+///     fn write_fields(&self, fields: &mut PersistedFields) {
+///         fields.insert("zoom", &self.zoom);
+///         fields.insert("show_grid", &self.show_grid);
+///     }
+///
+///     fn read_fields(&mut self, fields: &PersistedFields) -> Result<(), WhereWasIParseError> {
+///         if let Some(zoom) = fields.get("zoom")? {
+///             self.zoom = zoom;
+///         }
+///         if let Some(show_grid) = fields.get("show_grid")? {
+///             self.show_grid = show_grid;
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Persist {
+    /// The version of the fields, stored in the file
+    ///
+    /// Increase it when a field is renamed or changes meaning, and register a migration from the
+    /// old version with
+    /// [`WhereWasIAppExt::migrate_persisted`](crate::WhereWasIAppExt::migrate_persisted).
+    const VERSION: u32 = 0;
+
+    /// Store the fields to save in `fields`
+    fn write_fields(&self, fields: &mut PersistedFields);
+
+    /// Update `self` from `fields`, keeping the value of fields that weren't saved
+    fn read_fields(&mut self, fields: &PersistedFields) -> Result<(), WhereWasIParseError>;
+}
+
+/// A type that writes and reads its own savefile, see
+/// [`WhereWasIAppExt::persist_custom`](crate::WhereWasIAppExt::persist_custom)
+///
+/// Use this for types that can't implement [`Persist`], for example because they hold a collection.
+/// No version is stored for these types, so write one yourself if the format may change.
+///
+/// ```rust
+/// use std::io::{self, BufRead, Write};
+///
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{Persistable, WhereWasIParseError};
+///
+/// #[derive(Resource)]
+/// struct RecentFiles(Vec<String>);
+///
+/// impl Persistable for RecentFiles {
+///     fn write(&self, writer: &mut dyn Write) -> io::Result<()> {
+///         for file in &self.0 {
+///             writeln!(writer, "{file}")?;
+///         }
+///         Ok(())
+///     }
+///
+///     fn read(reader: &mut dyn BufRead) -> Result<Self, WhereWasIParseError> {
+///         let files = reader.lines().collect::<Result<_, _>>()?;
+///         Ok(Self(files))
+///     }
+/// }
+/// ```
+pub trait Persistable: Sized {
+    /// Write `self` to `writer`
+    fn write(&self, writer: &mut dyn Write) -> io::Result<()>;
+
+    /// Read a value written by [`write`](Self::write) from `reader`
+    fn read(reader: &mut dyn BufRead) -> Result<Self, WhereWasIParseError>;
+}
+
+/// The path of the persisted struct `name` in `directory`
+fn persisted_path(directory: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    Ok(directory.join("persisted").join(format!("{name}.persist")))
+}
+
+/// Write the file for `name` in `directory` with `write`
+fn write_file(
+    directory: &Path,
+    name: &str,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    let path = persisted_path(directory, name)?;
+    fs::create_dir_all(directory.join("persisted"))?;
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer)?;
+    writer.flush()
+}
+
+/// The contents of the file that the fields of `value` are written to
+fn persisted_contents<T: Persist>(value: &T) -> Vec<u8> {
+    let mut fields = PersistedFields {
+        version: T::VERSION,
+        ..default()
+    };
+    value.write_fields(&mut fields);
+    fields.serialize().into_bytes()
+}
+
+/// Write `contents` to the file for `name` in `directory`, remembering them in `written`
+///
+/// With [`WhereWasIConfig::skip_unchanged`], nothing is written if `written` holds the same
+/// contents, so an autosave doesn't rewrite every persisted file.
+fn write_changed(
+    config: &WhereWasIConfig,
+    directory: &Path,
+    name: &str,
+    contents: Vec<u8>,
+    written: &mut Option<Vec<u8>>,
+) -> io::Result<()> {
+    if config.skip_unchanged && written.as_ref() == Some(&contents) {
+        return Ok(());
+    }
+    write_file(directory, name, |writer| writer.write_all(&contents))?;
+    *written = Some(contents);
+    Ok(())
+}
+
+/// The migrations registered for the [`Persist`] type `T` with
+/// [`WhereWasIAppExt::migrate_persisted`](crate::WhereWasIAppExt::migrate_persisted), by the
+/// version they migrate from
+#[derive(Resource)]
+pub(crate) struct PersistMigrations<T> {
+    pub(crate) migrations: BTreeMap<u32, fn(&mut PersistedFields)>,
+    persisted: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for PersistMigrations<T> {
+    fn default() -> Self {
+        Self {
+            migrations: BTreeMap::new(),
+            persisted: PhantomData,
+        }
+    }
+}
+
+impl<T: Persist> PersistMigrations<T> {
+    /// Bring `fields` from the version they were written by up to [`Persist::VERSION`]
+    ///
+    /// Versions without a migration are skipped, as their fields are read as they are. Fields
+    /// written by a newer version of `T` are rejected, as their meaning is unknown.
+    fn migrate(
+        migrations: Option<&Self>,
+        fields: &mut PersistedFields,
+    ) -> Result<(), WhereWasIParseError> {
+        if fields.version > T::VERSION {
+            return Err(WhereWasIParseError::UnsupportedVersion {
+                line: 2,
+                found: format!("version {}", fields.version),
+            });
+        }
+        if let Some(migrations) = migrations {
+            for (_, migration) in migrations.migrations.range(fields.version..T::VERSION) {
+                migration(fields);
+            }
+        }
+        fields.version = T::VERSION;
+        Ok(())
+    }
+}
+
+/// Update `value` from the file for `name` in `directory`, migrating it from an older version
+///
+/// Returns `Ok(false)` if it was never saved.
+fn read_persisted<T: Persist>(
+    directory: &Path,
+    name: &str,
+    value: &mut T,
+    migrations: Option<&PersistMigrations<T>>,
+) -> Result<bool, WhereWasIParseError> {
+    let contents = match fs::read_to_string(persisted_path(directory, name)?) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    let mut fields = PersistedFields::parse(&contents)?;
+    PersistMigrations::migrate(migrations, &mut fields)?;
+    value.read_fields(&fields)?;
+    Ok(true)
+}
+
+/// Whether a save of all entities was admitted this frame, and isn't a dry run
+///
+/// Saves are only admitted when saving is possible and within the
+/// [`SaveRateLimit`](crate::SaveRateLimit).
+pub(crate) fn saving_all(
+    admitted: &mut MessageReader<SaveAdmitted>,
+    config: &WhereWasIConfig,
+) -> bool {
+    let save_all = admitted
+        .read()
+        .any(|SaveAdmitted(save)| save.windows.is_none());
+    save_all && !config.dry_run
+}
+
+/// The name registered for the resource `R` with
+/// [`WhereWasIAppExt::persist_resource`](crate::WhereWasIAppExt::persist_resource)
+#[derive(Resource)]
+pub(crate) struct PersistedResource<R> {
+    pub(crate) name: String,
+    resource: PhantomData<fn() -> R>,
+}
+
+impl<R> PersistedResource<R> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            resource: PhantomData,
+        }
+    }
+}
+
+/// Restore the resource `R` at startup, if it exists
+pub(crate) fn restore_resource<R: Resource<Mutability = Mutable> + Persist>(
+    persisted: Res<PersistedResource<R>>,
+    migrations: Option<Res<PersistMigrations<R>>>,
+    resource: Option<ResMut<R>>,
+    config: Res<WhereWasIConfig>,
+) {
+    let Some(mut resource) = resource else {
+        return;
+    };
+    if !config.restore_on_startup {
+        return;
+    }
+
+    match read_persisted(
+        &config.directory,
+        &persisted.name,
+        resource.as_mut(),
+        migrations.as_deref(),
+    ) {
+        Ok(true) => debug!("Restored {}", persisted.name),
+        Ok(false) => {}
+        Err(err) => error!("Could not read {}: {}", persisted.name, err),
+    }
+}
+
+/// Save the resource `R` when all entities are saved
+pub(crate) fn save_resource<R: Resource<Mutability = Mutable> + Persist>(
+    mut admitted: MessageReader<SaveAdmitted>,
+    mut failed: MessageWriter<WhereWasISaveFailed>,
+    mut written: Local<Option<Vec<u8>>>,
+    persisted: Res<PersistedResource<R>>,
+    resource: Option<Res<R>>,
+    config: Res<WhereWasIConfig>,
+) {
+    let Some(resource) = resource else {
+        return;
+    };
+    if !saving_all(&mut admitted, &config) {
+        return;
+    }
+
+    let contents = persisted_contents(resource.as_ref());
+    if let Err(error) = write_changed(
+        &config,
+        &config.directory,
+        &persisted.name,
+        contents,
+        &mut written,
+    ) {
+        error!("Could not save {}: {}", persisted.name, error);
+        failed.write(WhereWasISaveFailed {
+            name: persisted.name.clone(),
+            error,
+        });
+    }
+}
+
+/// The name registered for the resource `R` with
+/// [`WhereWasIAppExt::persist_custom`](crate::WhereWasIAppExt::persist_custom)
+#[derive(Resource)]
+pub(crate) struct PersistedCustom<R> {
+    pub(crate) name: String,
+    resource: PhantomData<fn() -> R>,
+}
+
+impl<R> PersistedCustom<R> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            resource: PhantomData,
+        }
+    }
+}
+
+/// Read the resource `R` at startup and insert it, replacing an existing one
+pub(crate) fn restore_custom<R: Resource + Persistable>(
+    mut commands: Commands,
+    persisted: Res<PersistedCustom<R>>,
+    config: Res<WhereWasIConfig>,
+) {
+    if !config.restore_on_startup {
+        return;
+    }
+
+    let read = || -> Result<Option<R>, WhereWasIParseError> {
+        let file = match File::open(persisted_path(&config.directory, &persisted.name)?) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        R::read(&mut BufReader::new(file)).map(Some)
+    };
+    match read() {
+        Ok(Some(resource)) => {
+            debug!("Restored {}", persisted.name);
+            commands.insert_resource(resource);
+        }
+        Ok(None) => {}
+        Err(err) => error!("Could not read {}: {}", persisted.name, err),
+    }
+}
+
+/// Save the resource `R` when all entities are saved
+pub(crate) fn save_custom<R: Resource + Persistable>(
+    mut admitted: MessageReader<SaveAdmitted>,
+    mut failed: MessageWriter<WhereWasISaveFailed>,
+    mut written: Local<Option<Vec<u8>>>,
+    persisted: Res<PersistedCustom<R>>,
+    resource: Option<Res<R>>,
+    config: Res<WhereWasIConfig>,
+) {
+    let Some(resource) = resource else {
+        return;
+    };
+    if !saving_all(&mut admitted, &config) {
+        return;
+    }
+
+    let mut contents = Vec::new();
+    if let Err(error) = resource.write(&mut contents).and_then(|_| {
+        write_changed(
+            &config,
+            &config.directory,
+            &persisted.name,
+            contents,
+            &mut written,
+        )
+    }) {
+        error!("Could not save {}: {}", persisted.name, error);
+        failed.write(WhereWasISaveFailed {
+            name: persisted.name.clone(),
+            error,
+        });
+    }
+}
+
+/// The name registered for the component `C` with
+/// [`WhereWasIAppExt::persist_component`](crate::WhereWasIAppExt::persist_component)
+#[derive(Resource)]
+pub(crate) struct PersistedComponent<C> {
+    pub(crate) name: String,
+    component: PhantomData<fn() -> C>,
+}
+
+impl<C> PersistedComponent<C> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            component: PhantomData,
+        }
+    }
+
+    /// The name of the file of the component on the entity saved under `entity`
+    fn file_name(&self, entity: &str) -> String {
+        format!("{}.{}", entity, self.name)
+    }
+}
+
+/// Restore the component `C` of tracked entities when it is added
+pub(crate) fn restore_component<C: Component<Mutability = Mutable> + Persist>(
+    persisted: Res<PersistedComponent<C>>,
+    migrations: Option<Res<PersistMigrations<C>>>,
+    tracked: Query<(Entity, &WhereWasI)>,
+    mut added: Query<&mut C, (Added<C>, With<WhereWasI>)>,
+    config: Res<WhereWasIConfig>,
+) {
+    if added.is_empty() || !config.restore_on_startup {
+        return;
+    }
+
+    for (entity, name) in resolve_tracked_names(tracked.iter(), config.name_collisions) {
+        let Ok(mut component) = added.get_mut(entity) else {
+            continue;
+        };
+        let Ok((_, where_was_i)) = tracked.get(entity) else {
+            continue;
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        let file_name = persisted.file_name(&name);
+        let migrations = migrations.as_deref();
+        if let Err(err) = read_persisted(directory, &file_name, component.as_mut(), migrations) {
+            error!("Could not read {}: {}", file_name, err);
+        }
+    }
+}
+
+/// Save the component `C` of tracked entities when all entities are saved
+pub(crate) fn save_component<C: Component + Persist>(
+    mut admitted: MessageReader<SaveAdmitted>,
+    mut failed: MessageWriter<WhereWasISaveFailed>,
+    mut written: Local<HashMap<String, Option<Vec<u8>>>>,
+    persisted: Res<PersistedComponent<C>>,
+    tracked: Query<(Entity, &WhereWasI, &C)>,
+    config: Res<WhereWasIConfig>,
+) {
+    if !saving_all(&mut admitted, &config) {
+        return;
+    }
+
+    let names = resolve_names(
+        tracked
+            .iter()
+            .filter(|(_, where_was_i, _)| !where_was_i.follows)
+            .map(|(entity, where_was_i, _)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        let Ok((_, where_was_i, component)) = tracked.get(entity) else {
+            continue;
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        let file_name = persisted.file_name(&name);
+        let written = written.entry(file_name.clone()).or_default();
+        let contents = persisted_contents(component);
+        if let Err(error) = write_changed(&config, directory, &file_name, contents, written) {
+            error!("Could not save {}: {}", file_name, error);
+            failed.write(WhereWasISaveFailed {
+                name: file_name,
+                error,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{close_window, test_app, test_config};
+    use crate::{
+        SaveRateLimit, SaveTrigger, SaveWhereWasI, WhereWasIAppExt, save_on_exit, save_state,
+    };
+
+    #[derive(Resource, Default, Debug, PartialEq)]
+    struct Workspace {
+        zoom: f32,
+        label: String,
+        show_grid: bool,
+    }
+
+    impl Persist for Workspace {
+        fn write_fields(&self, fields: &mut PersistedFields) {
+            fields.insert("zoom", &self.zoom);
+            fields.insert("label", &self.label);
+            fields.insert("show_grid", &self.show_grid);
+        }
+
+        fn read_fields(&mut self, fields: &PersistedFields) -> Result<(), WhereWasIParseError> {
+            if let Some(zoom) = fields.get("zoom")? {
+                self.zoom = zoom;
+            }
+            if let Some(label) = fields.get("label")? {
+                self.label = label;
+            }
+            if let Some(show_grid) = fields.get("show_grid")? {
+                self.show_grid = show_grid;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_persisted_fields() {
+        let workspace = Workspace {
+            zoom: 1.5,
+            label: " two\nlines \\ ".into(),
+            show_grid: true,
+        };
+        let mut fields = PersistedFields::default();
+        workspace.write_fields(&mut fields);
+        let contents = fields.serialize();
+        assert_eq!(
+            contents,
+            "v0\n\nzoom:\n1.5\n\nlabel:\n two\\nlines \\\\ \n\nshow_grid:\ntrue\n"
+        );
+
+        let mut restored = Workspace::default();
+        restored
+            .read_fields(&PersistedFields::parse(&contents).unwrap())
+            .unwrap();
+        assert_eq!(restored, workspace);
+
+        assert_eq!(
+            restored.read_fields(&PersistedFields::parse("v0\n\nzoom:\nwide\n").unwrap()),
+            Err(WhereWasIParseError::InvalidValue {
+                field: "zoom".into(),
+                value: "wide".into()
+            })
+        );
+        assert_eq!(
+            PersistedFields::parse("v0\n\nzoom\n"),
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 3,
+                expected: Field::PersistedField,
+                found: "zoom".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_persist_resource() {
+        let config = test_config("assets/tests/persist_resource");
+        let workspace = Workspace {
+            zoom: 2.0,
+            label: "level editor".into(),
+            show_grid: true,
+        };
+
+        let mut app = test_app(config.clone());
+        app.insert_resource(workspace)
+            .persist_resource::<Workspace>("workspace")
+            .add_systems(Update, save_state);
+        close_window(&mut app);
+        app.update();
+
+        let mut app = test_app(config);
+        app.init_resource::<Workspace>()
+            .persist_resource::<Workspace>("workspace");
+        app.update();
+        assert_eq!(
+            *app.world().resource::<Workspace>(),
+            Workspace {
+                zoom: 2.0,
+                label: "level editor".into(),
+                show_grid: true,
+            }
+        );
+
+        fs::remove_dir_all("assets/tests/persist_resource").unwrap();
+    }
+
+    #[test]
+    fn test_persist_resource_rate_limit() {
+        let path = Path::new("assets/tests/persist_rate_limit/persisted/workspace.persist");
+        let mut app = test_app(WhereWasIConfig {
+            rate_limit: Some(SaveRateLimit {
+                max_writes_per_second: Some(1),
+                max_bytes_per_minute: None,
+            }),
+            ..test_config("assets/tests/persist_rate_limit")
+        });
+        app.init_resource::<Workspace>()
+            .persist_resource::<Workspace>("workspace")
+            .add_systems(Update, save_state);
+        app.world_mut().spawn(WhereWasI::camera());
+        let save = SaveWhereWasI {
+            trigger: SaveTrigger::Manual,
+            window: None,
+        };
+
+        app.world_mut().write_message(save);
+        app.update();
+        assert!(fs::read_to_string(path).unwrap().contains("zoom:\n0\n"));
+
+        // The second save within a second is postponed, together with the resource
+        app.world_mut().resource_mut::<Workspace>().zoom = 2.0;
+        app.world_mut().write_message(save);
+        app.update();
+        assert!(fs::read_to_string(path).unwrap().contains("zoom:\n0\n"));
+
+        fs::remove_dir_all("assets/tests/persist_rate_limit").unwrap();
+    }
+
+    #[test]
+    fn test_persist_resource_on_exit() {
+        let directory = Path::new("assets/tests/persist_resource_on_exit");
+        let mut app = test_app(test_config("assets/tests/persist_resource_on_exit"));
+        app.add_message::<AppExit>()
+            .add_systems(Last, save_on_exit)
+            .init_resource::<Workspace>()
+            .persist_resource::<Workspace>("workspace");
+        app.update();
+        assert!(!fs::exists(directory.join("persisted/workspace.persist")).unwrap());
+
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+        assert!(fs::exists(directory.join("persisted/workspace.persist")).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[derive(Component, Default, Debug, PartialEq)]
+    struct Lens {
+        focal_length: f32,
+        aperture: f32,
+    }
+
+    impl Persist for Lens {
+        const VERSION: u32 = 2;
+
+        fn write_fields(&self, fields: &mut PersistedFields) {
+            fields.insert("focal_length", &self.focal_length);
+            fields.insert("aperture", &self.aperture);
+        }
+
+        fn read_fields(&mut self, fields: &PersistedFields) -> Result<(), WhereWasIParseError> {
+            if let Some(focal_length) = fields.get("focal_length")? {
+                self.focal_length = focal_length;
+            }
+            if let Some(aperture) = fields.get("aperture")? {
+                self.aperture = aperture;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_migrate_persisted() {
+        let config = test_config("assets/tests/persist_migrations");
+        fs::create_dir_all("assets/tests/persist_migrations/persisted").unwrap();
+        // Version 0 called the focal length `zoom`, version 1 stored the aperture as a fraction
+        fs::write(
+            "assets/tests/persist_migrations/persisted/camera.lens.persist",
+            "v0\n\nzoom:\n35\n\naperture:\n0.25\n",
+        )
+        .unwrap();
+
+        let mut app = test_app(config.clone());
+        app.persist_component::<Lens>("lens")
+            .migrate_persisted::<Lens>(0, |fields| fields.rename("zoom", "focal_length"))
+            .migrate_persisted::<Lens>(1, |fields| {
+                let fraction: f32 = fields.get("aperture").ok().flatten().unwrap_or(1.0);
+                fields.insert("aperture", &(1.0 / fraction));
+            });
+        app.add_systems(Update, save_state);
+        let camera = app
+            .world_mut()
+            .spawn((WhereWasI::camera(), Lens::default()))
+            .id();
+        app.update();
+        assert_eq!(
+            *app.world().get::<Lens>(camera).unwrap(),
+            Lens {
+                focal_length: 35.0,
+                aperture: 4.0,
+            }
+        );
+
+        close_window(&mut app);
+        app.update();
+        let contents =
+            fs::read_to_string("assets/tests/persist_migrations/persisted/camera.lens.persist")
+                .unwrap();
+        assert!(contents.starts_with("v0\nversion 2\n"));
+
+        let mut fields = PersistedFields::parse("v0\nversion 3\n").unwrap();
+        assert_eq!(fields.version(), 3);
+        assert_eq!(
+            PersistMigrations::<Lens>::migrate(None, &mut fields),
+            Err(WhereWasIParseError::UnsupportedVersion {
+                line: 2,
+                found: "version 3".into()
+            })
+        );
+
+        fs::remove_dir_all("assets/tests/persist_migrations").unwrap();
+    }
+
+    #[derive(Resource, Debug, PartialEq)]
+    struct RecentFiles(Vec<String>);
+
+    impl Persistable for RecentFiles {
+        fn write(&self, writer: &mut dyn Write) -> io::Result<()> {
+            for file in &self.0 {
+                writeln!(writer, "{file}")?;
+            }
+            Ok(())
+        }
+
+        fn read(reader: &mut dyn BufRead) -> Result<Self, WhereWasIParseError> {
+            let files = reader.lines().collect::<Result<_, _>>()?;
+            Ok(Self(files))
+        }
+    }
+
+    #[test]
+    fn test_persist_custom() {
+        let config = test_config("assets/tests/persist_custom");
+        let recent = || RecentFiles(vec!["level_1.scn".into(), "level_2.scn".into()]);
+
+        let mut app = test_app(config.clone());
+        app.insert_resource(recent())
+            .persist_custom::<RecentFiles>("recent_files")
+            .add_systems(Update, save_state);
+        close_window(&mut app);
+        app.update();
+
+        let mut app = test_app(config);
+        app.persist_custom::<RecentFiles>("recent_files");
+        app.update();
+        assert_eq!(*app.world().resource::<RecentFiles>(), recent());
+
+        fs::remove_dir_all("assets/tests/persist_custom").unwrap();
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_persist() {
+        #[derive(crate::Persist, Default, Debug, PartialEq)]
+        #[persist(version = 3)]
+        struct Orbit {
+            radius: f32,
+            label: String,
+            #[persist(skip)]
+            dragging: bool,
+        }
+
+        let orbit = Orbit {
+            radius: 12.5,
+            label: "main".into(),
+            dragging: true,
+        };
+        let mut fields = PersistedFields::default();
+        orbit.write_fields(&mut fields);
+        assert_eq!(fields.names().collect::<Vec<_>>(), vec!["radius", "label"]);
+        assert_eq!(Orbit::VERSION, 3);
+
+        let mut restored = Orbit::default();
+        restored.read_fields(&fields).unwrap();
+        assert_eq!(
+            restored,
+            Orbit {
+                radius: 12.5,
+                label: "main".into(),
+                dragging: false,
+            }
+        );
+    }
+}
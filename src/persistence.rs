@@ -0,0 +1,242 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+
+use crate::WhereWasIConfig;
+use crate::storage;
+
+/// The file that is written and removed again to check that the save directory is writable
+const PROBE_FILE: &str = ".where_was_i_probe";
+
+/// A [`Resource`] that is inserted at startup when neither the save directory nor
+/// [`WhereWasIPlugin::fallback_directory`](crate::WhereWasIPlugin::fallback_directory) can be
+/// created or written
+///
+/// This happens for example when the application is installed in a read-only location. Existing
+/// savefiles are still restored, but nothing is saved when the application closes. Check for this
+/// resource to tell the player that their progress won't be kept.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::PersistenceUnavailable;
+///
+/// fn warn_player(unavailable: Option<Res<PersistenceUnavailable>>) {
+///     if let Some(unavailable) = unavailable {
+///         warn!("Your camera position won't be saved: {}", unavailable.reason);
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct PersistenceUnavailable {
+    /// Why the save directory can't be used
+    pub reason: String,
+}
+
+/// A [`Resource`] with the result of checking that savefiles can be written, which happens when
+/// [`WhereWasIPlugin`](crate::WhereWasIPlugin) is added to the app
+///
+/// The check creates the save directory if needed, so a misconfigured directory is found at launch
+/// instead of when the application closes. It isn't inserted with
+/// [`WhereWasIPlugin::dry_run`](crate::WhereWasIPlugin::dry_run).
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasIDirectoryProbe;
+///
+/// fn show_save_directory(probe: Res<WhereWasIDirectoryProbe>) {
+///     match &probe.error {
+///         None => info!("Saving to {}", probe.directory.display()),
+///         Some(error) => warn!("Can't save to {}: {}", probe.directory.display(), error),
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct WhereWasIDirectoryProbe {
+    /// The directory that was checked last, which is the fallback directory if the plugin fell
+    /// back to it
+    pub directory: PathBuf,
+    /// Why [`directory`](Self::directory) can't be written, or `None` if it can
+    pub error: Option<String>,
+}
+
+impl WhereWasIDirectoryProbe {
+    /// Whether savefiles can be written
+    pub fn is_writable(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// A [`Message`] that is written at startup when the save directory can't be written and the
+/// plugin saves to [`WhereWasIPlugin::fallback_directory`](crate::WhereWasIPlugin::fallback_directory)
+/// instead
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct WhereWasIDirectoryFallback {
+    /// The directory that couldn't be written
+    pub from: PathBuf,
+    /// The directory that is used instead
+    pub to: PathBuf,
+    /// Why [`from`](Self::from) couldn't be written
+    pub reason: String,
+}
+
+/// Check that savefiles can be written to `directory`, creating it if needed
+fn check_writable(directory: &Path) -> io::Result<()> {
+    storage::create_dir_all(directory)?;
+
+    let probe = directory.join(PROBE_FILE);
+    storage::write(&probe, b"")?;
+    storage::remove_file(&probe)
+}
+
+/// Inserts [`WhereWasIDirectoryProbe`] and switches to the fallback directory if the save
+/// directory can't be written, or inserts [`PersistenceUnavailable`] if neither can be written
+pub(crate) fn check_persistence(
+    mut commands: Commands,
+    mut config: ResMut<WhereWasIConfig>,
+    mut fallbacks: MessageWriter<WhereWasIDirectoryFallback>,
+) {
+    if config.dry_run {
+        return;
+    }
+
+    let Err(err) = check_writable(&config.directory) else {
+        commands.insert_resource(WhereWasIDirectoryProbe {
+            directory: config.directory.clone(),
+            error: None,
+        });
+        return;
+    };
+
+    if let Some(fallback) = config
+        .fallback_directory
+        .clone()
+        .filter(|fallback| check_writable(fallback).is_ok())
+    {
+        warn!(
+            "Savefiles can't be written to {}, saving to {} instead: {}",
+            config.directory.display(),
+            fallback.display(),
+            err
+        );
+        fallbacks.write(WhereWasIDirectoryFallback {
+            from: config.directory.clone(),
+            to: fallback.clone(),
+            reason: err.to_string(),
+        });
+        commands.insert_resource(WhereWasIDirectoryProbe {
+            directory: fallback.clone(),
+            error: None,
+        });
+        config.fall_back_to(fallback);
+        return;
+    }
+
+    warn!(
+        "Savefiles can't be written to {}, state won't be saved: {}",
+        config.directory.display(),
+        err
+    );
+    commands.insert_resource(WhereWasIDirectoryProbe {
+        directory: config.directory.clone(),
+        error: Some(err.to_string()),
+    });
+    commands.insert_resource(PersistenceUnavailable {
+        reason: err.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::{close_window, test_app, test_config};
+    use crate::{DirectorySource, WhereWasI, WhereWasISaveFailed, save_state};
+
+    #[test]
+    fn test_persistence_unavailable() {
+        // A directory can't be created inside a regular file
+        let mut app = test_app(test_config("assets/tests/identity.state/saves"));
+        app.add_systems(PreStartup, check_persistence)
+            .add_systems(Update, save_state);
+        app.world_mut()
+            .spawn((WhereWasI::camera(), Transform::IDENTITY));
+
+        app.update();
+        assert!(app.world().contains_resource::<PersistenceUnavailable>());
+        let probe = app.world().resource::<WhereWasIDirectoryProbe>();
+        assert!(!probe.is_writable());
+        assert_eq!(
+            probe.directory,
+            Path::new("assets/tests/identity.state/saves")
+        );
+
+        close_window(&mut app);
+        app.update();
+
+        let failures = app.world().resource::<Messages<WhereWasISaveFailed>>();
+        assert_eq!(failures.iter_current_update_messages().count(), 0);
+    }
+
+    #[test]
+    fn test_fallback_directory() {
+        let mut app = test_app(WhereWasIConfig {
+            fallback_directory: Some("assets/tests/fallback".into()),
+            ..test_config("assets/tests/identity.state/saves")
+        });
+        app.add_systems(PreStartup, check_persistence)
+            .add_systems(Update, save_state);
+        app.world_mut()
+            .spawn((WhereWasI::camera(), Transform::IDENTITY));
+
+        app.update();
+        assert!(!app.world().contains_resource::<PersistenceUnavailable>());
+        let config = app.world().resource::<WhereWasIConfig>();
+        assert_eq!(config.directory(), Path::new("assets/tests/fallback"));
+        assert_eq!(config.directory_source(), &DirectorySource::Fallback);
+        let fallbacks = app
+            .world_mut()
+            .resource_mut::<Messages<WhereWasIDirectoryFallback>>()
+            .drain()
+            .map(|fallback| (fallback.from.clone(), fallback.to.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fallbacks,
+            vec![(
+                "assets/tests/identity.state/saves".into(),
+                "assets/tests/fallback".into()
+            )]
+        );
+
+        close_window(&mut app);
+        app.update();
+        assert!(fs::exists("assets/tests/fallback/camera.state").unwrap());
+
+        fs::remove_dir_all("assets/tests/fallback").unwrap();
+    }
+
+    #[test]
+    fn test_persistence_available() {
+        let mut app = test_app(test_config("assets/tests/persistence"));
+        app.add_systems(PreStartup, check_persistence);
+
+        app.update();
+
+        assert!(!app.world().contains_resource::<PersistenceUnavailable>());
+        assert_eq!(
+            app.world().resource::<WhereWasIDirectoryProbe>(),
+            &WhereWasIDirectoryProbe {
+                directory: "assets/tests/persistence".into(),
+                error: None,
+            }
+        );
+        assert!(
+            fs::read_dir("assets/tests/persistence")
+                .unwrap()
+                .next()
+                .is_none()
+        );
+        fs::remove_dir_all("assets/tests/persistence").unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::prelude::*;
+use bevy_platform::time::Instant;
+
+use crate::autosave::{SaveSink, save_entries, write_states};
+use crate::{PersistenceUnavailable, SaveTrigger, WhereWasI, WhereWasIConfig};
+
+/// When the savefile of an entity is written, in addition to the save when a window closes
+///
+/// Set it for a group with [`SaveGroup::policy`](crate::SaveGroup::policy) or for a single entity
+/// with [`WhereWasI::save_on_change`], [`WhereWasI::exit_only`] or [`WhereWasI::autosave_every`].
+/// The policy of an entity takes precedence over the one of its group.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SavePolicy {
+    /// Saved whenever the plugin saves, including
+    /// [`WhereWasIPlugin::autosave`](crate::WhereWasIPlugin::autosave)
+    #[default]
+    Inherit,
+    /// Also saved as soon as its [`Transform`] differs from the savefile
+    ///
+    /// Combine this with [`WhereWasIPlugin::rate_limit`](crate::WhereWasIPlugin::rate_limit) for
+    /// entities that move continuously.
+    OnChange,
+    /// Never autosaved, only saved when a window closes or a save is requested with
+    /// [`SaveWhereWasI`](crate::SaveWhereWasI)
+    ExitOnly,
+    /// Autosaved with this interval instead of the one of the plugin
+    Autosave(Duration),
+}
+
+impl WhereWasIConfig {
+    /// The policy of `where_was_i`, falling back to the one of its group
+    pub(crate) fn policy(&self, where_was_i: &WhereWasI) -> SavePolicy {
+        where_was_i
+            .policy
+            .or(self.group(where_was_i.group.as_deref()).policy)
+            .unwrap_or_default()
+    }
+}
+
+/// Write the savefiles of entities whose [`SavePolicy`] asks for a save right now
+///
+/// Entities with [`SavePolicy::OnChange`] are saved when they differ from their savefile, entities
+/// with [`SavePolicy::Autosave`] when their interval has passed. Saves that would exceed the
+/// [`SaveRateLimit`](crate::SaveRateLimit) are retried in a later frame, an interval that passed in
+/// the meantime stays due until its entities are written.
+pub(crate) fn save_by_policy(
+    time: Option<Res<Time>>,
+    mut timers: Local<HashMap<Duration, Timer>>,
+    mut due: Local<HashSet<Duration>>,
+    tracked: Query<(Entity, &WhereWasI, &Transform)>,
+    mut sink: SaveSink,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let delta = time.map_or(Duration::ZERO, |time| time.delta());
+    let mut on_change = false;
+    for (_, where_was_i, _) in &tracked {
+        match sink.config().policy(where_was_i) {
+            SavePolicy::Autosave(interval) if !timers.contains_key(&interval) => {
+                timers.insert(interval, Timer::new(interval, TimerMode::Repeating));
+            }
+            SavePolicy::OnChange => on_change = true,
+            _ => {}
+        }
+    }
+    for (interval, timer) in timers.iter_mut() {
+        if timer.tick(delta).just_finished() {
+            due.insert(*interval);
+        }
+    }
+    if (due.is_empty() && !on_change) || unavailable.is_some() || !sink.within_rate_limit() {
+        return;
+    }
+
+    // Select on the names the savefiles are written under, which differ from the names of the
+    // entities when they collide
+    let started = Instant::now();
+    let mut entries = save_entries(tracked.iter(), sink.config(), sink.saves());
+    entries.retain(|entry| {
+        let Ok((_, where_was_i, _)) = tracked.get(entry.entity) else {
+            return false;
+        };
+        match sink.config().policy(where_was_i) {
+            SavePolicy::OnChange => sink
+                .saves()
+                .get(&entry.name)
+                .is_none_or(|record| record.transform != entry.transform),
+            SavePolicy::Autosave(interval) => due.contains(&interval),
+            SavePolicy::Inherit | SavePolicy::ExitOnly => false,
+        }
+    });
+    let trigger = if due.is_empty() {
+        SaveTrigger::Changed
+    } else {
+        SaveTrigger::Autosave
+    };
+    due.clear();
+    if entries.is_empty() {
+        return;
+    }
+
+    let outcomes = write_states(sink.config(), entries);
+    sink.finish(outcomes, started.elapsed(), trigger);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    use super::*;
+    use crate::tests::test_app;
+    use crate::{
+        NameCollisionPolicy, SaveGroup, SaveRateLimit, WhereWasIGroup, WhereWasIPlugin,
+        WhereWasISaveCompleted, read_state,
+    };
+
+    #[test]
+    fn test_save_by_policy() {
+        let plugin = WhereWasIPlugin {
+            groups: BTreeMap::from([(
+                "editor_ui".into(),
+                SaveGroup {
+                    policy: Some(SavePolicy::Autosave(Duration::from_secs(10))),
+                    ..default()
+                },
+            )]),
+            ..WhereWasIPlugin::from_directory("assets/tests/policy")
+        };
+        let mut app = test_app(WhereWasIConfig::from(&plugin));
+        app.init_resource::<Time>()
+            .add_systems(Update, save_by_policy);
+
+        let follower = app
+            .world_mut()
+            .spawn(WhereWasI::from_name("follower").save_on_change())
+            .id();
+        app.world_mut().spawn((
+            WhereWasI::from_name("outliner"),
+            WhereWasIGroup::new("editor_ui"),
+        ));
+        app.world_mut().spawn((
+            WhereWasI::from_name("pinned").exit_only(),
+            WhereWasIGroup::new("editor_ui"),
+        ));
+        app.world_mut().spawn(WhereWasI::camera());
+
+        let advance = |app: &mut App, seconds| {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(seconds));
+            app.update();
+        };
+        let exists = |path: &str| fs::exists(format!("assets/tests/policy/{path}")).unwrap();
+
+        advance(&mut app, 1);
+        assert!(exists("follower.state"));
+        assert!(!exists("editor_ui/outliner.state"));
+
+        app.world_mut()
+            .get_mut::<Transform>(follower)
+            .unwrap()
+            .translation
+            .x = 1.0;
+        advance(&mut app, 9);
+        assert_eq!(
+            read_state("assets/tests/policy/follower.state").unwrap(),
+            Transform::from_xyz(1.0, 0.0, 0.0)
+        );
+        assert!(exists("editor_ui/outliner.state"));
+        assert!(!exists("editor_ui/pinned.state"));
+        assert!(!exists("camera.state"));
+
+        fs::remove_dir_all("assets/tests/policy").unwrap();
+    }
+
+    #[test]
+    fn test_save_by_policy_rate_limited() {
+        let plugin = WhereWasIPlugin {
+            rate_limit: Some(SaveRateLimit {
+                max_writes_per_second: Some(1),
+                max_bytes_per_minute: None,
+            }),
+            name_collisions: NameCollisionPolicy::Suffix,
+            ..WhereWasIPlugin::from_directory("assets/tests/policy_rate_limit")
+        };
+        let mut app = test_app(WhereWasIConfig::from(&plugin));
+        app.init_resource::<Time>()
+            .add_systems(Update, save_by_policy);
+
+        // The second marker is saved as marker_1 and compared with that savefile
+        app.world_mut()
+            .spawn(WhereWasI::from_name("marker").save_on_change());
+        app.world_mut().spawn((
+            WhereWasI::from_name("marker").save_on_change(),
+            Transform::from_xyz(1.0, 0.0, 0.0),
+        ));
+        app.world_mut()
+            .spawn(WhereWasI::from_name("outliner").autosave_every(Duration::from_secs(10)));
+
+        let advance = |app: &mut App, seconds| {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(seconds));
+            app.update();
+        };
+        let exists =
+            |path: &str| fs::exists(format!("assets/tests/policy_rate_limit/{path}")).unwrap();
+
+        advance(&mut app, 1);
+        assert!(exists("marker.state"));
+        assert!(exists("marker_1.state"));
+
+        // The interval passes while the rate limit is reached
+        advance(&mut app, 9);
+        assert!(!exists("outliner.state"));
+
+        // Once the rate limit allows it, the outliner is saved without another interval passing,
+        // and the unchanged markers aren't
+        app.world_mut().resource_mut::<WhereWasIConfig>().rate_limit = None;
+        advance(&mut app, 0);
+        assert!(exists("outliner.state"));
+        let completed = app.world().resource::<Messages<WhereWasISaveCompleted>>();
+        assert_eq!(
+            completed
+                .iter_current_update_messages()
+                .map(|completed| completed.saved.clone())
+                .collect::<Vec<_>>(),
+            vec![vec!["outliner".to_string()]]
+        );
+
+        fs::remove_dir_all("assets/tests/policy_rate_limit").unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::prelude::*;
+use bevy_math::{DVec3, I64Vec3};
+
+use crate::names::{resolve_names, resolve_tracked_names, validate_name};
+use crate::serialization::versioned_lines;
+use crate::{
+    Field, PersistenceUnavailable, SaveWhereWasI, WhereWasI, WhereWasIConfig, WhereWasIParseError,
+};
+
+/// A component with the position of an entity in a large world, saved at full precision next to
+/// its [`Transform`]
+///
+/// The translation of a [`Transform`] is an `f32`, which can't tell apart positions a few
+/// millimeters apart once they are a few kilometers from the origin. Keep this component up to
+/// date with the `f64` position of the entity, or with its grid cell and the translation within it
+/// for `big_space`-style worlds, and it is restored along with the [`Transform`]. Copying the
+/// restored position back into the world is up to the application.
+///
+/// ```rust
+/// use bevy::math::{DVec3, I64Vec3};
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{WhereWasI, WhereWasIPosition};
+///
+/// fn spawn_ship(mut commands: Commands) {
+///     commands.spawn((
+///         WhereWasI::from_name("ship"),
+///         WhereWasIPosition {
+///             translation: DVec3::new(1_500_000.25, 0.0, -30_000.5),
+///             cell: Some(I64Vec3::new(12, 0, -3)),
+///         },
+///     ));
+/// }
+/// ```
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Component)]
+pub struct WhereWasIPosition {
+    /// The translation at `f64` precision, relative to `cell` if it is set
+    pub translation: DVec3,
+    /// The integer grid cell the entity is in, for worlds that are split into cells
+    pub cell: Option<I64Vec3>,
+}
+
+/// The path of the position of the entity `name` in `directory`
+fn position_path(directory: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    Ok(directory.join("positions").join(format!("{name}.position")))
+}
+
+/// Write `position` for the entity `name` in `directory`
+///
+/// Values are written with the shortest representation that reads back to the same `f64`.
+pub(crate) fn write_position(
+    directory: &Path,
+    name: &str,
+    position: &WhereWasIPosition,
+) -> io::Result<()> {
+    let path = position_path(directory, name)?;
+    fs::create_dir_all(directory.join("positions"))?;
+
+    let translation = position.translation;
+    let mut contents = format!(
+        "v0\n\ntranslation:\n{} {} {}\n",
+        translation.x, translation.y, translation.z
+    );
+    if let Some(cell) = position.cell {
+        contents.push_str(&format!("\ncell:\n{} {} {}\n", cell.x, cell.y, cell.z));
+    }
+    fs::write(path, contents)
+}
+
+/// The three whitespace-separated values on the line after `key`, or `None` if `lines` has no
+/// `key`
+fn parse_triple<T: FromStr>(
+    lines: &[&str],
+    key: &str,
+    field: Field,
+) -> Result<Option<[T; 3]>, WhereWasIParseError> {
+    let Some(index) = lines.iter().position(|line| *line == key) else {
+        return Ok(None);
+    };
+    let line = lines.get(index + 1).copied().unwrap_or("");
+    let unexpected = || WhereWasIParseError::UnexpectedLine {
+        line: index + 2,
+        expected: field,
+        found: line.into(),
+    };
+
+    match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [x, y, z] => Ok(Some([
+            x.parse().map_err(|_| unexpected())?,
+            y.parse().map_err(|_| unexpected())?,
+            z.parse().map_err(|_| unexpected())?,
+        ])),
+        _ => Err(unexpected()),
+    }
+}
+
+/// Parse a [`WhereWasIPosition`] from the contents of its file
+fn parse_position(contents: &str) -> Result<WhereWasIPosition, WhereWasIParseError> {
+    let lines = versioned_lines(contents)?;
+
+    let translation = parse_triple(&lines, "translation:", Field::PreciseTranslation)?.ok_or(
+        WhereWasIParseError::MissingLine {
+            line: lines.len() + 1,
+            expected: Field::PreciseTranslation,
+        },
+    )?;
+    if translation.iter().any(|value: &f64| !value.is_finite()) {
+        return Err(WhereWasIParseError::NonFinite {
+            field: Field::PreciseTranslation,
+        });
+    }
+    let cell = parse_triple(&lines, "cell:", Field::GridCell)?;
+
+    Ok(WhereWasIPosition {
+        translation: DVec3::from_array(translation),
+        cell: cell.map(I64Vec3::from_array),
+    })
+}
+
+/// Read the position of the entity `name` in `directory`, or `None` if it was never saved
+pub(crate) fn read_position(
+    directory: &Path,
+    name: &str,
+) -> Result<Option<WhereWasIPosition>, WhereWasIParseError> {
+    let contents = match fs::read_to_string(position_path(directory, name)?) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    parse_position(&contents).map(Some)
+}
+
+/// Restore the [`WhereWasIPosition`] of tracked entities when it is added
+///
+/// Entities restored in [`PostStartup`] are picked up in the [`PreUpdate`] of the same frame.
+pub(crate) fn restore_positions(
+    tracked: Query<(Entity, &WhereWasI)>,
+    mut added: Query<&mut WhereWasIPosition, Added<WhereWasIPosition>>,
+    config: Res<WhereWasIConfig>,
+) {
+    if added.is_empty() || !config.restore_on_startup {
+        return;
+    }
+
+    let names = resolve_tracked_names(tracked.iter(), config.name_collisions);
+    for (entity, name) in names {
+        let Ok(mut position) = added.get_mut(entity) else {
+            continue;
+        };
+        let Ok((_, where_was_i)) = tracked.get(entity) else {
+            continue;
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        match read_position(directory, &name) {
+            Ok(Some(restored)) => *position = restored,
+            Ok(None) => {}
+            Err(err) => error!("Could not read position of {}: {}", name, err),
+        }
+    }
+}
+
+/// Save the [`WhereWasIPosition`] of tracked entities when all entities are saved
+pub(crate) fn save_positions(
+    mut requests: MessageReader<SaveWhereWasI>,
+    tracked: Query<(Entity, &WhereWasI, &WhereWasIPosition)>,
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let save_all = requests.read().any(|request| request.window.is_none());
+    if !save_all || unavailable.is_some() || config.dry_run {
+        return;
+    }
+
+    let names = resolve_names(
+        tracked
+            .iter()
+            .filter(|(_, where_was_i, _)| !where_was_i.follows)
+            .map(|(entity, where_was_i, _)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        let Ok((_, where_was_i, position)) = tracked.get(entity) else {
+            continue;
+        };
+        let directory = &config.group(where_was_i.group.as_deref()).directory;
+        if let Err(err) = write_position(directory, &name, position) {
+            error!("Could not save position of {}: {}", name, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SaveTrigger;
+    use crate::tests::{test_app, test_config};
+
+    #[test]
+    fn test_parse_position() {
+        assert_eq!(
+            parse_position("v0\n\ntranslation:\n1500000.25 0 -30000.5\n\ncell:\n12 0 -3\n"),
+            Ok(WhereWasIPosition {
+                translation: DVec3::new(1_500_000.25, 0.0, -30_000.5),
+                cell: Some(I64Vec3::new(12, 0, -3)),
+            })
+        );
+        assert_eq!(
+            parse_position("v0\n\ntranslation:\n1 2\n"),
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 4,
+                expected: Field::PreciseTranslation,
+                found: "1 2".into()
+            })
+        );
+        assert_eq!(
+            parse_position("v0\n\ntranslation:\n1 NaN 3\n"),
+            Err(WhereWasIParseError::NonFinite {
+                field: Field::PreciseTranslation
+            })
+        );
+    }
+
+    #[test]
+    fn test_save_and_restore_positions() {
+        let config = test_config("assets/tests/positions");
+        let position = WhereWasIPosition {
+            translation: DVec3::new(123_456_789.123_456_78, -0.1, 1e-9),
+            cell: None,
+        };
+
+        let mut app = test_app(config.clone());
+        app.add_systems(Update, save_positions);
+        app.world_mut()
+            .spawn((WhereWasI::from_name("ship"), position));
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI {
+                trigger: SaveTrigger::Manual,
+                window: None,
+            });
+        app.update();
+
+        let mut app = test_app(config);
+        app.add_systems(Update, restore_positions);
+        let ship = app
+            .world_mut()
+            .spawn((WhereWasI::from_name("ship"), WhereWasIPosition::default()))
+            .id();
+        app.update();
+        assert_eq!(
+            *app.world().get::<WhereWasIPosition>(ship).unwrap(),
+            position
+        );
+
+        fs::remove_dir_all("assets/tests/positions").unwrap();
+    }
+}
@@ -0,0 +1,18 @@
+//! The preludes of the Bevy crates this crate depends on, in place of `bevy::prelude`
+
+pub(crate) use bevy_app::prelude::*;
+#[cfg(any(feature = "viewports", feature = "camera-settings"))]
+pub(crate) use bevy_camera::prelude::*;
+#[cfg(feature = "debug-ui")]
+pub(crate) use bevy_color::prelude::*;
+pub(crate) use bevy_ecs::prelude::*;
+pub(crate) use bevy_log::prelude::*;
+pub(crate) use bevy_math::prelude::*;
+pub(crate) use bevy_reflect::prelude::*;
+pub(crate) use bevy_time::prelude::*;
+pub(crate) use bevy_transform::prelude::*;
+#[cfg(feature = "debug-ui")]
+pub(crate) use bevy_ui::prelude::*;
+pub(crate) use bevy_utils::prelude::*;
+#[cfg(feature = "window")]
+pub(crate) use bevy_window::prelude::*;
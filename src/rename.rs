@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use crate::prelude::*;
+
+use crate::{WhereWasI, WhereWasIConfig, WhereWasISaves, state_path};
+
+/// What happens to the savefile of an entity when the name of its [`WhereWasI`] changes at
+/// runtime, see [`WhereWasIPlugin::rename_policy`](crate::WhereWasIPlugin::rename_policy)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenamePolicy {
+    /// Move the savefile to the new name right away, replacing a savefile with that name
+    #[default]
+    Move,
+    /// Delete the savefile with the old name, the entity is saved under its new name when the
+    /// plugin saves next
+    Delete,
+    /// Leave the savefile with the old name, so it is restored again when an entity gets that name
+    Keep,
+}
+
+/// Move or delete the savefile of `old` after it was renamed to `new`, and update `saves`
+/// accordingly
+fn rename_savefile(
+    config: &WhereWasIConfig,
+    saves: &mut WhereWasISaves,
+    old: &str,
+    new: &str,
+) -> io::Result<()> {
+    let result = match config.rename_policy {
+        RenamePolicy::Keep => return Ok(()),
+        RenamePolicy::Move => {
+            let record = saves.remove(old);
+            let result = fs::rename(
+                state_path(&config.directory, old)?,
+                state_path(&config.directory, new)?,
+            );
+            if let Some(record) = record {
+                saves.insert(new, record);
+            }
+            result
+        }
+        RenamePolicy::Delete => {
+            saves.remove(old);
+            fs::remove_file(state_path(&config.directory, old)?)
+        }
+    };
+
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Apply the [`RenamePolicy`] to every tracked entity whose name changed since the last frame
+///
+/// Renames are detected by comparing the name of every changed [`WhereWasI`] to the one it had
+/// before, so this runs before the plugin saves. A change of [`WhereWasILevel`](crate::WhereWasILevel)
+/// isn't a rename.
+pub(crate) fn handle_renames(
+    mut known: Local<HashMap<Entity, (String, String)>>,
+    changed: Query<(Entity, &WhereWasI), Changed<WhereWasI>>,
+    mut removed: RemovedComponents<WhereWasI>,
+    mut saves: ResMut<WhereWasISaves>,
+    config: Res<WhereWasIConfig>,
+) {
+    for entity in removed.read() {
+        known.remove(&entity);
+    }
+
+    for (entity, where_was_i) in &changed {
+        if where_was_i.follows {
+            continue;
+        }
+        let Some((old_name, old)) = known.insert(
+            entity,
+            (where_was_i.name().into(), where_was_i.name.clone()),
+        ) else {
+            continue;
+        };
+        if old_name == where_was_i.name() || old == where_was_i.name {
+            continue;
+        }
+
+        let config = config.group(where_was_i.group.as_deref());
+        if config.dry_run {
+            info!(
+                "Dry run: would apply {:?} to the savefile of {} after renaming it to {}",
+                config.rename_policy, old, where_was_i.name
+            );
+            continue;
+        }
+        match rename_savefile(config, &mut saves, &old, &where_was_i.name) {
+            Ok(()) => debug!("Renamed {} to {}", old, where_was_i.name),
+            Err(err) => error!(
+                "Could not update the savefile of {} after renaming it to {}: {}",
+                old, where_was_i.name, err
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{SaveRecord, read_state, write_state};
+
+    fn renamed_app(directory: &str, rename_policy: RenamePolicy) -> (App, Entity) {
+        let config = WhereWasIConfig {
+            rename_policy,
+            ..test_config(directory)
+        };
+        write_state(&config, "old", &Transform::from_xyz(1.0, 2.0, 3.0)).unwrap();
+
+        let mut app = test_app(config);
+        app.add_systems(Update, handle_renames);
+        app.world_mut().resource_mut::<WhereWasISaves>().insert(
+            "old",
+            SaveRecord {
+                transform: Transform::from_xyz(1.0, 2.0, 3.0),
+                modified: None,
+                trigger: None,
+            },
+        );
+        let entity = app.world_mut().spawn(WhereWasI::from_name("old")).id();
+        app.update();
+
+        app.world_mut()
+            .get_mut::<WhereWasI>(entity)
+            .unwrap()
+            .set_name("new");
+        app.update();
+        (app, entity)
+    }
+
+    #[test]
+    fn test_rename_moves_savefile() {
+        let directory = "assets/tests/rename_move";
+        let (app, _) = renamed_app(directory, RenamePolicy::Move);
+
+        assert!(!fs::exists(format!("{directory}/old.state")).unwrap());
+        assert_eq!(
+            read_state(format!("{directory}/new.state")).unwrap(),
+            Transform::from_xyz(1.0, 2.0, 3.0)
+        );
+        let saves = app.world().resource::<WhereWasISaves>();
+        assert!(!saves.contains("old"));
+        assert!(saves.contains("new"));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_rename_deletes_savefile() {
+        let directory = "assets/tests/rename_delete";
+        let (app, _) = renamed_app(directory, RenamePolicy::Delete);
+
+        assert!(!fs::exists(format!("{directory}/old.state")).unwrap());
+        assert!(!fs::exists(format!("{directory}/new.state")).unwrap());
+        assert!(app.world().resource::<WhereWasISaves>().is_empty());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+use crate::names::resolve_names;
+use crate::timestamp::format_timestamp;
+use crate::{Field, PersistenceUnavailable, WhereWasI, WhereWasIConfig, WhereWasIParseError};
+
+/// A [`Message`] that re-drives the [`Transform`]s of tracked entities from a `.replay` file
+///
+/// Replays are recorded when [`WhereWasIPlugin::record_replay`](crate::WhereWasIPlugin::record_replay)
+/// is set. Playing a replay replaces one that is playing already.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::PlayWhereWasIReplay;
+///
+/// fn replay_last_session(mut replays: MessageWriter<PlayWhereWasIReplay>) {
+///     replays.write(PlayWhereWasIReplay::default());
+/// }
+/// ```
+#[derive(Message, Clone, Debug, Default)]
+pub struct PlayWhereWasIReplay {
+    /// The replay to play, or the most recent replay of a previous session if `None`
+    pub path: Option<PathBuf>,
+}
+
+/// The [`Transform`] of an entity at some point in a replay or journal
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ReplaySample {
+    /// The time since the recording started
+    pub(crate) at: Duration,
+    pub(crate) name: String,
+    pub(crate) transform: Transform,
+}
+
+/// Append a line with the sample of `transform` for `name`, `at` seconds after the recording
+/// started
+pub(crate) fn write_sample(
+    writer: &mut impl Write,
+    at: f32,
+    name: &str,
+    transform: &Transform,
+) -> io::Result<()> {
+    let Transform {
+        translation: t,
+        rotation: r,
+        scale: s,
+    } = transform;
+    writeln!(
+        writer,
+        "{at} {} {} {} {} {} {} {} {} {} {} {name}",
+        t.x, t.y, t.z, r.x, r.y, r.z, r.w, s.x, s.y, s.z
+    )
+}
+
+/// Appends samples of the [`Transform`]s of tracked entities to a `.replay` file
+///
+/// The file is created when the first sample is taken, in the `replays` subdirectory of the save
+/// directory. Only entities that moved since the previous sample are written.
+#[derive(Resource)]
+pub(crate) struct ReplayRecorder {
+    timer: Timer,
+    file: Option<(PathBuf, BufWriter<File>)>,
+    started: Duration,
+    last: HashMap<String, Transform>,
+}
+
+impl ReplayRecorder {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval, TimerMode::Repeating),
+            file: None,
+            started: Duration::ZERO,
+            last: HashMap::new(),
+        }
+    }
+
+    /// The path of the replay being recorded, if the first sample was taken
+    fn path(&self) -> Option<&Path> {
+        self.file.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    /// Append the samples of `transforms` taken at `now`
+    fn record(
+        &mut self,
+        directory: &Path,
+        now: Duration,
+        transforms: impl IntoIterator<Item = (String, Transform)>,
+    ) -> io::Result<usize> {
+        let (_, writer) = match self.file {
+            Some(ref mut file) => file,
+            None => {
+                let replays = directory.join("replays");
+                fs::create_dir_all(&replays)?;
+                let path = replays.join(format!("{}.replay", format_timestamp(SystemTime::now())));
+                let mut writer = BufWriter::new(File::create(&path)?);
+                writer.write_all(b"v0\n")?;
+
+                self.started = now;
+                self.file.insert((path, writer))
+            }
+        };
+
+        let at = now.saturating_sub(self.started).as_secs_f32();
+        let mut written = 0;
+        for (name, transform) in transforms {
+            if self.last.get(&name) == Some(&transform) {
+                continue;
+            }
+
+            write_sample(writer, at, &name, &transform)?;
+            self.last.insert(name, transform);
+            written += 1;
+        }
+        writer.flush()?;
+
+        Ok(written)
+    }
+}
+
+/// A replay that is being played
+#[derive(Resource)]
+pub(crate) struct ReplayPlayback {
+    samples: VecDeque<ReplaySample>,
+    elapsed: Duration,
+}
+
+/// Parse a sample from a line of a replay
+fn parse_sample(line: &str, number: usize) -> Result<ReplaySample, WhereWasIParseError> {
+    let mut tokens = line.splitn(12, ' ');
+    let mut values = [0.0; 11];
+    for value in &mut values {
+        let token = tokens.next().unwrap_or_default();
+        *value = token
+            .parse::<f32>()
+            .map_err(|_| WhereWasIParseError::InvalidFloat {
+                line: number,
+                field: Field::ReplaySample,
+                value: token.into(),
+            })?;
+    }
+    let name = tokens
+        .next()
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| WhereWasIParseError::UnexpectedLine {
+            line: number,
+            expected: Field::ReplaySample,
+            found: line.into(),
+        })?;
+    let at =
+        Duration::try_from_secs_f32(values[0]).map_err(|_| WhereWasIParseError::InvalidFloat {
+            line: number,
+            field: Field::ReplaySample,
+            value: values[0].to_string(),
+        })?;
+
+    let [_, tx, ty, tz, rx, ry, rz, rw, sx, sy, sz] = values;
+    Ok(ReplaySample {
+        at,
+        name: name.into(),
+        transform: Transform {
+            translation: Vec3::new(tx, ty, tz),
+            rotation: Quat::from_xyzw(rx, ry, rz, rw),
+            scale: Vec3::new(sx, sy, sz),
+        },
+    })
+}
+
+/// Read the samples of the replay or journal at `path`, in the order they were recorded
+pub(crate) fn read_samples(path: &Path) -> Result<Vec<ReplaySample>, WhereWasIParseError> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+
+    let version = lines.next().transpose()?.unwrap_or_default();
+    if version.trim() != "v0" {
+        return Err(WhereWasIParseError::UnsupportedVersion {
+            line: 1,
+            found: version,
+        });
+    }
+
+    let mut samples = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() {
+            samples.push(parse_sample(line, index + 2)?);
+        }
+    }
+    Ok(samples)
+}
+
+/// The most recent replay in `directory`, other than `current`
+///
+/// Replays are named after the time they were started, so the last one in alphabetical order is
+/// the most recent.
+fn latest_replay(directory: &Path, current: Option<&Path>) -> io::Result<Option<PathBuf>> {
+    let entries = match fs::read_dir(directory.join("replays")) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let mut latest = None;
+    for entry in entries {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "replay")
+            && Some(path.as_path()) != current
+            && latest.as_ref().is_none_or(|latest| path > *latest)
+        {
+            latest = Some(path);
+        }
+    }
+    Ok(latest)
+}
+
+/// Sample the [`Transform`]s of all tracked entities at the interval of the [`ReplayRecorder`]
+///
+/// Nothing is recorded while a replay is playing, or if [`PersistenceUnavailable`] was inserted at
+/// startup. The recording stops at the first error.
+pub(crate) fn record_replay(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut recorder: ResMut<ReplayRecorder>,
+    tracked: Query<(Entity, &WhereWasI, &Transform)>,
+    config: Res<WhereWasIConfig>,
+    playback: Option<Res<ReplayPlayback>>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    if !recorder.timer.tick(time.delta()).just_finished()
+        || playback.is_some()
+        || unavailable.is_some()
+        || config.dry_run
+    {
+        return;
+    }
+
+    let transforms: HashMap<Entity, Transform> = tracked
+        .iter()
+        .map(|(entity, _, transform)| (entity, *transform))
+        .collect();
+    let samples = resolve_names(
+        tracked
+            .iter()
+            .map(|(entity, where_was_i, _)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    )
+    .into_iter()
+    .map(|(entity, name)| (name, transforms[&entity]));
+
+    match recorder.record(&config.directory, time.elapsed(), samples) {
+        Ok(written) => debug!("Recorded {} replay sample(s)", written),
+        Err(err) => {
+            error!("Could not record replay, stopping the recording: {}", err);
+            commands.remove_resource::<ReplayRecorder>();
+        }
+    }
+}
+
+/// Handles [`PlayWhereWasIReplay`] messages by reading the replay and starting its playback
+pub(crate) fn start_replay(
+    mut commands: Commands,
+    mut requests: MessageReader<PlayWhereWasIReplay>,
+    recorder: Option<Res<ReplayRecorder>>,
+    config: Res<WhereWasIConfig>,
+) {
+    let Some(request) = requests.read().last() else {
+        return;
+    };
+
+    let path = match &request.path {
+        Some(path) => path.clone(),
+        None => {
+            let current = recorder.as_ref().and_then(|recorder| recorder.path());
+            match latest_replay(&config.directory, current) {
+                Ok(Some(path)) => path,
+                Ok(None) => {
+                    warn!("There is no replay of a previous session to play");
+                    return;
+                }
+                Err(err) => {
+                    error!("Could not list replays: {}", err);
+                    return;
+                }
+            }
+        }
+    };
+
+    match read_samples(&path) {
+        Ok(samples) => {
+            info!(
+                "Playing {} sample(s) from: {}",
+                samples.len(),
+                path.display()
+            );
+            commands.insert_resource(ReplayPlayback {
+                samples: samples.into(),
+                elapsed: Duration::ZERO,
+            });
+        }
+        Err(err) => error!("Could not read replay {}: {}", path.display(), err),
+    }
+}
+
+/// Apply the samples of the playing replay whose time has come
+pub(crate) fn play_replay(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut tracked: Query<(Entity, &WhereWasI, &mut Transform)>,
+    config: Res<WhereWasIConfig>,
+) {
+    playback.elapsed += time.delta();
+
+    let entities: HashMap<String, Entity> = resolve_names(
+        tracked
+            .iter()
+            .map(|(entity, where_was_i, _)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    )
+    .into_iter()
+    .map(|(entity, name)| (name, entity))
+    .collect();
+
+    while let Some(sample) = playback.samples.front()
+        && sample.at <= playback.elapsed
+    {
+        if let Some(&entity) = entities.get(&sample.name)
+            && let Ok((_, _, mut transform)) = tracked.get_mut(entity)
+        {
+            *transform = sample.transform;
+        }
+        playback.samples.pop_front();
+    }
+
+    if playback.samples.is_empty() {
+        info!("Finished playing replay");
+        commands.remove_resource::<ReplayPlayback>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_app, test_config};
+
+    fn advance(app: &mut App, seconds: u64) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(seconds));
+        app.update();
+    }
+
+    #[test]
+    fn test_parse_sample() {
+        assert_eq!(
+            parse_sample("1.5 1 2 3 0 0 0 1 1 1 1 main camera", 2),
+            Ok(ReplaySample {
+                at: Duration::from_secs_f32(1.5),
+                name: "main camera".into(),
+                transform: Transform::from_xyz(1.0, 2.0, 3.0),
+            })
+        );
+        assert_eq!(
+            parse_sample("1.5 1 2 3 0 0 x 1 1 1 1 camera", 3),
+            Err(WhereWasIParseError::InvalidFloat {
+                line: 3,
+                field: Field::ReplaySample,
+                value: "x".into()
+            })
+        );
+        assert_eq!(
+            parse_sample("-1 1 2 3 0 0 0 1 1 1 1 camera", 4),
+            Err(WhereWasIParseError::InvalidFloat {
+                line: 4,
+                field: Field::ReplaySample,
+                value: "-1".into()
+            })
+        );
+        assert_eq!(
+            parse_sample("1 1 2 3 0 0 0 1 1 1 1", 5),
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 5,
+                expected: Field::ReplaySample,
+                found: "1 1 2 3 0 0 0 1 1 1 1".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_and_play_replay() {
+        let config = test_config("assets/tests/replay");
+        let mut app = test_app(config.clone());
+        app.init_resource::<Time>()
+            .insert_resource(ReplayRecorder::new(Duration::from_secs(1)))
+            .add_systems(Update, record_replay);
+        let camera = app.world_mut().spawn(WhereWasI::camera()).id();
+
+        for x in [1.0, 1.0, 2.0] {
+            app.world_mut()
+                .get_mut::<Transform>(camera)
+                .unwrap()
+                .translation
+                .x = x;
+            advance(&mut app, 1);
+        }
+        let path = app
+            .world()
+            .resource::<ReplayRecorder>()
+            .path()
+            .unwrap()
+            .to_path_buf();
+        let samples = read_samples(&path).unwrap();
+        assert_eq!(
+            samples
+                .iter()
+                .map(|sample| (sample.at, sample.transform.translation.x))
+                .collect::<Vec<_>>(),
+            vec![(Duration::ZERO, 1.0), (Duration::from_secs(2), 2.0)]
+        );
+
+        let mut app = test_app(config);
+        app.init_resource::<Time>().add_systems(
+            Update,
+            (
+                start_replay,
+                play_replay.run_if(resource_exists::<ReplayPlayback>),
+            )
+                .chain(),
+        );
+        let camera = app.world_mut().spawn(WhereWasI::camera()).id();
+        app.world_mut()
+            .resource_mut::<Messages<PlayWhereWasIReplay>>()
+            .write(PlayWhereWasIReplay::default());
+        advance(&mut app, 0);
+
+        let x = |app: &App| app.world().get::<Transform>(camera).unwrap().translation.x;
+        advance(&mut app, 1);
+        assert_eq!(x(&app), 1.0);
+        advance(&mut app, 1);
+        assert_eq!(x(&app), 2.0);
+        assert!(!app.world().contains_resource::<ReplayPlayback>());
+
+        fs::remove_dir_all("assets/tests/replay").unwrap();
+    }
+}
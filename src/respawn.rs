@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+
+use crate::prelude::*;
+
+use crate::names::resolve_tracked_names;
+use crate::{WhereWasI, WhereWasIConfig, WhereWasISaves};
+
+/// A callback that re-creates a tracked entity from its savefile, see
+/// [`WhereWasIPlugin::respawn_entity`](crate::WhereWasIPlugin::respawn_entity)
+///
+/// It receives the name of the savefile and the saved [`Transform`], and returns the entity it
+/// spawned.
+pub type EntitySpawner = fn(&mut Commands, &str, &Transform) -> Entity;
+
+/// Hand every savefile without a matching tracked entity to
+/// [`WhereWasIConfig::respawn_entity`]
+///
+/// The spawned entities get a [`WhereWasI`] component with the name of the savefile, unless the
+/// callback added one itself. Savefiles named after a `WhereWasIId` also restore that ID.
+pub(crate) fn respawn_entities(
+    mut commands: Commands,
+    tracked: Query<(Entity, &WhereWasI)>,
+    saves: Res<WhereWasISaves>,
+    config: Res<WhereWasIConfig>,
+) {
+    let Some(spawn) = config.respawn_entity else {
+        return;
+    };
+
+    let names: HashSet<String> = resolve_tracked_names(tracked, config.name_collisions)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+
+    let mut respawned = 0;
+    for (name, record) in saves.iter() {
+        if names.contains(name) {
+            continue;
+        }
+
+        let entity = spawn(&mut commands, name, &record.transform);
+        let mut entity = commands.entity(entity);
+        entity.insert_if_new((WhereWasI::from_name(name), record.transform));
+        #[cfg(feature = "uuid")]
+        if let Some(id) = crate::WhereWasIId::from_name(name) {
+            entity.insert_if_new(id);
+        }
+        respawned += 1;
+    }
+
+    if respawned > 0 {
+        info!("Respawned {} entities from their savefiles", respawned);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SaveRecord;
+    use crate::tests::{test_app, test_config};
+
+    #[derive(Component)]
+    struct Marker;
+
+    #[test]
+    fn test_respawn_entities() {
+        let mut app = test_app(WhereWasIConfig {
+            respawn_entity: Some(|commands, _, transform| {
+                commands.spawn((Marker, *transform)).id()
+            }),
+            ..test_config("assets/tests/respawn_entities")
+        });
+        app.add_systems(Startup, respawn_entities);
+
+        let mut saves = WhereWasISaves::default();
+        for (name, x) in [("camera", 1.0), ("marker", 2.0)] {
+            saves.insert(
+                name,
+                SaveRecord {
+                    transform: Transform::from_xyz(x, 0.0, 0.0),
+                    modified: None,
+                    trigger: None,
+                },
+            );
+        }
+        app.insert_resource(saves);
+        app.world_mut().spawn(WhereWasI::camera());
+        app.update();
+
+        let respawned = app
+            .world_mut()
+            .query_filtered::<(&WhereWasI, &Transform), With<Marker>>()
+            .iter(app.world())
+            .map(|(where_was_i, transform)| (where_was_i.name.clone(), transform.translation.x))
+            .collect::<Vec<_>>();
+        assert_eq!(respawned, vec![("marker".to_string(), 2.0)]);
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use crate::prelude::*;
+use bevy_ecs::query::QueryFilter;
+
+use crate::WhereWasI;
+
+/// Excludes an entity from [`WhereWasIAppExt::persist_transform`](crate::WhereWasIAppExt::persist_transform),
+/// [`persist_marker`](crate::WhereWasIAppExt::persist_marker) and
+/// [`persist_matching`](crate::WhereWasIAppExt::persist_matching)
+///
+/// Use this for individual entities that match a broad rule, but whose [`Transform`] should never
+/// be saved or restored, such as a procedurally animated light. It has no effect on entities that
+/// were given a [`WhereWasI`] component directly.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasISkip;
+///
+/// fn spawn_flicker(mut commands: Commands) {
+///     commands.spawn((Name::new("flicker"), WhereWasISkip));
+/// }
+/// ```
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct WhereWasISkip;
+
+/// Marks entities that are tracked under their [`Name`] by a rule, so they follow when it changes
+#[derive(Component)]
+pub(crate) struct NamedByRule;
+
+/// Entities that aren't tracked yet and don't opt out of the rules with [`WhereWasISkip`]
+type Untracked = (Without<WhereWasI>, Without<WhereWasISkip>);
+
+/// The names registered with [`WhereWasIAppExt::persist_transform`](crate::WhereWasIAppExt::persist_transform)
+#[derive(Resource, Default)]
+pub(crate) struct PersistedNames(pub(crate) HashSet<String>);
+
+/// Add a [`WhereWasI`] component to entities whose [`Name`] is one of the [`PersistedNames`]
+///
+/// This runs right before the entities are restored, both at startup and for entities that are
+/// spawned or renamed later.
+pub(crate) fn track_named(
+    mut commands: Commands,
+    names: Res<PersistedNames>,
+    named: Query<(Entity, &Name), (Changed<Name>, Untracked)>,
+) {
+    for (entity, name) in &named {
+        if names.0.contains(name.as_str()) {
+            commands
+                .entity(entity)
+                .insert((WhereWasI::from_name(name.as_str()), NamedByRule));
+        }
+    }
+}
+
+/// The name registered for the marker component `M` with
+/// [`WhereWasIAppExt::persist_marker`](crate::WhereWasIAppExt::persist_marker)
+#[derive(Resource)]
+pub(crate) struct PersistedMarker<M> {
+    pub(crate) name: String,
+    marker: PhantomData<M>,
+}
+
+impl<M> PersistedMarker<M> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Add a [`WhereWasI`] component to entities with the marker component `M`
+pub(crate) fn track_marker<M: Component>(
+    mut commands: Commands,
+    marker: Res<PersistedMarker<M>>,
+    marked: Query<Entity, (Added<M>, Untracked)>,
+) {
+    for entity in &marked {
+        commands
+            .entity(entity)
+            .insert(WhereWasI::from_name(marker.name.as_str()));
+    }
+}
+
+/// Whether the filter `F` was registered with
+/// [`WhereWasIAppExt::persist_matching`](crate::WhereWasIAppExt::persist_matching)
+#[derive(Resource)]
+pub(crate) struct PersistedFilter<F>(pub(crate) PhantomData<fn() -> F>);
+
+/// Add a [`WhereWasI`] component named after the [`Name`] of every entity that matches `F`
+pub(crate) fn track_matching<F: QueryFilter>(
+    mut commands: Commands,
+    matching: Query<(Entity, &Name), (F, Untracked)>,
+) {
+    for (entity, name) in &matching {
+        commands
+            .entity(entity)
+            .insert((WhereWasI::from_name(name.as_str()), NamedByRule));
+    }
+}
+
+/// Rename the [`WhereWasI`] of entities that are tracked under their [`Name`] when it changes
+pub(crate) fn follow_names(mut named: Query<(&Name, &mut WhereWasI), With<NamedByRule>>) {
+    for (name, mut where_was_i) in &mut named {
+        if where_was_i.name() != name.as_str() {
+            where_was_i.set_name(name.as_str());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WhereWasIAppExt;
+    use crate::tests::{test_app, test_config};
+    use crate::{load_state, read_saves};
+
+    #[test]
+    fn test_persist_transform() {
+        let mut app = test_app(test_config("assets/tests"));
+        app.add_systems(PreStartup, read_saves)
+            .add_systems(PostStartup, load_state)
+            .add_systems(PreUpdate, load_state)
+            .persist_transform("camera");
+
+        let camera = app.world_mut().spawn(Name::new("camera")).id();
+        let other = app.world_mut().spawn(Name::new("other")).id();
+        app.update();
+
+        assert_eq!(app.world().get::<WhereWasI>(camera).unwrap().name, "camera");
+        assert_eq!(
+            app.world().get::<Transform>(camera).unwrap().translation,
+            Vec3::new(10.000002, 10.0, 10.0)
+        );
+        assert!(app.world().get::<WhereWasI>(other).is_none());
+
+        app.add_systems(Update, follow_names);
+        app.world_mut()
+            .entity_mut(camera)
+            .insert(Name::new("overview"));
+        app.update();
+        assert_eq!(
+            app.world().get::<WhereWasI>(camera).unwrap().name,
+            "overview"
+        );
+    }
+
+    #[derive(Component)]
+    struct MainCamera;
+
+    #[derive(Component)]
+    struct Lamp;
+
+    #[test]
+    fn test_persist_marker() {
+        let mut app = test_app(test_config("assets/tests"));
+        app.add_systems(PreStartup, read_saves)
+            .add_systems(PostStartup, load_state)
+            .add_systems(PreUpdate, load_state)
+            .persist_marker::<MainCamera>("camera");
+
+        app.update();
+        let camera = app.world_mut().spawn(MainCamera).id();
+        app.update();
+
+        assert_eq!(app.world().get::<WhereWasI>(camera).unwrap().name, "camera");
+        assert_eq!(
+            app.world().get::<Transform>(camera).unwrap().translation,
+            Vec3::new(10.000002, 10.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn test_persist_matching() {
+        let mut app = test_app(test_config("assets/tests"));
+        app.persist_matching::<With<Lamp>>();
+
+        let lamps = [
+            app.world_mut().spawn((Lamp, Name::new("desk_lamp"))).id(),
+            app.world_mut().spawn((Lamp, Name::new("ceiling"))).id(),
+        ];
+        let unnamed = app.world_mut().spawn(Lamp).id();
+        let skipped = app
+            .world_mut()
+            .spawn((Lamp, Name::new("flicker"), WhereWasISkip))
+            .id();
+        let other = app.world_mut().spawn(Name::new("chair")).id();
+        app.update();
+
+        let names = lamps.map(|lamp| app.world().get::<WhereWasI>(lamp).unwrap().name.clone());
+        assert_eq!(names, ["desk_lamp", "ceiling"]);
+        assert!(app.world().get::<WhereWasI>(unnamed).is_none());
+        assert!(app.world().get::<WhereWasI>(skipped).is_none());
+        assert!(app.world().get::<WhereWasI>(other).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+use crate::serialization::WhereWasIParseError;
+use crate::storage;
+use crate::validation::read_checked;
+use crate::{SaveTrigger, WhereWasIConfig};
+
+/// A savefile that was read from disk
+///
+/// With the `serde` feature, it implements `Serialize` and `Deserialize` so it can be embedded in
+/// a larger save game.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SaveRecord {
+    /// The [`Transform`] stored in the savefile
+    pub transform: Transform,
+    /// When the savefile was last written, as recorded in the session file, or as reported by the
+    /// platform for savefiles that aren't recorded there
+    pub modified: Option<SystemTime>,
+    /// What caused the savefile to be written, or `None` if that isn't known, for example for
+    /// savefiles written by older versions or edited by hand
+    pub trigger: Option<SaveTrigger>,
+}
+
+impl SaveRecord {
+    /// How long ago the savefile was written, or `None` if that isn't known
+    pub fn age(&self) -> Option<Duration> {
+        SystemTime::now().duration_since(self.modified?).ok()
+    }
+}
+
+/// A [`Resource`] with all savefiles found at startup, keyed by their name
+///
+/// This is populated in [`PreStartup`], so it can be inspected before (or without) the saved state
+/// being applied to entities, for example to decide whether to show a "continue" button. Changes
+/// made through [`WhereWasIStore`](crate::WhereWasIStore) and savefiles written when closing the
+/// application are reflected here as well.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasISaves;
+///
+/// fn has_camera_save(saves: Res<WhereWasISaves>) -> bool {
+///     saves.contains("camera")
+/// }
+/// ```
+#[derive(Resource, Default, Debug)]
+pub struct WhereWasISaves {
+    records: BTreeMap<String, SaveRecord>,
+    failures: BTreeMap<String, WhereWasIParseError>,
+}
+
+impl WhereWasISaves {
+    /// The record saved under `name`, if any
+    pub fn get(&self, name: &str) -> Option<&SaveRecord> {
+        self.records.get(name)
+    }
+
+    /// Whether a record is saved under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.records.contains_key(name)
+    }
+
+    /// The error that occurred while reading the savefile for `name`, if any
+    pub fn failure(&self, name: &str) -> Option<&WhereWasIParseError> {
+        self.failures.get(name)
+    }
+
+    /// Iterate over all records, sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SaveRecord)> {
+        self.records
+            .iter()
+            .map(|(name, record)| (name.as_str(), record))
+    }
+
+    /// The number of records
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether no records were found
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Insert or replace the record saved under `name`
+    pub(crate) fn insert(&mut self, name: &str, record: SaveRecord) {
+        self.records.insert(name.into(), record);
+    }
+
+    /// Add the records and failures of `other`, which replace those with the same name
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.records.extend(other.records);
+        self.failures.extend(other.failures);
+    }
+
+    /// Set the trigger of every record from the `triggers` recorded in the session file
+    pub(crate) fn set_triggers(&mut self, triggers: &BTreeMap<String, SaveTrigger>) {
+        for (name, trigger) in triggers {
+            if let Some(record) = self.records.get_mut(name) {
+                record.trigger = Some(*trigger);
+            }
+        }
+    }
+
+    /// The triggers of all records for which it is known, to record them in the session file
+    pub(crate) fn triggers(&self) -> BTreeMap<String, SaveTrigger> {
+        self.records
+            .iter()
+            .filter_map(|(name, record)| Some((name.clone(), record.trigger?)))
+            .collect()
+    }
+
+    /// Set when every record was written from the times recorded in the session file
+    ///
+    /// These are more reliable than the modification times of the files, which change when the
+    /// save directory is copied.
+    pub(crate) fn set_saved_at(&mut self, saved: &BTreeMap<String, SystemTime>) {
+        for (name, saved_at) in saved {
+            if let Some(record) = self.records.get_mut(name) {
+                record.modified = Some(*saved_at);
+            }
+        }
+    }
+
+    /// When every record was written, as far as it is known, to record it in the session file
+    pub(crate) fn saved_at(&self) -> BTreeMap<String, SystemTime> {
+        self.records
+            .iter()
+            .filter_map(|(name, record)| Some((name.clone(), record.modified?)))
+            .collect()
+    }
+
+    /// Remove the record saved under `name`
+    pub(crate) fn remove(&mut self, name: &str) -> Option<SaveRecord> {
+        self.records.remove(name)
+    }
+
+    /// Remove the records that were saved more than `max_age` ago
+    ///
+    /// Returns the names of the removed records. Records without a modification time are kept.
+    pub(crate) fn remove_expired(&mut self, max_age: Duration) -> Vec<String> {
+        let expired = self
+            .records
+            .iter()
+            .filter(|(_, record)| record.age().is_some_and(|age| age > max_age))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+
+        for name in &expired {
+            self.records.remove(name);
+        }
+        expired
+    }
+
+    /// Read all savefiles in the directory of `config`
+    ///
+    /// The directory is scanned once and only the `.state` files it contains are opened, so the
+    /// cost doesn't depend on the number of tracked entities. Files that can't be deserialized or
+    /// contain invalid values are logged and kept as failures. A missing directory results in an
+    /// empty map.
+    pub(crate) fn read_directory(config: &WhereWasIConfig) -> Self {
+        let mut saves = Self::default();
+
+        let Ok(entries) = storage::read_dir(&config.directory) else {
+            return saves;
+        };
+
+        for entry in entries {
+            let Some(name) = state_name(&entry.path) else {
+                continue;
+            };
+
+            match read_checked(&entry.path, config) {
+                Ok(transform) => {
+                    saves.records.insert(
+                        name,
+                        SaveRecord {
+                            transform,
+                            modified: entry.modified,
+                            trigger: None,
+                        },
+                    );
+                }
+                Err(err) => {
+                    error!("Could not deserialize transform: {}", err);
+                    saves.failures.insert(name, err);
+                }
+            }
+        }
+
+        debug!(
+            "Indexed {} savefile(s) in {}",
+            saves.records.len() + saves.failures.len(),
+            config.directory.display()
+        );
+        saves
+    }
+}
+
+/// The name of a savefile, or `None` if `path` isn't a `.state` file
+pub(crate) fn state_name(path: &Path) -> Option<String> {
+    if path.extension()? != "state" {
+        return None;
+    }
+    Some(path.file_stem()?.to_str()?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_config;
+
+    #[test]
+    fn test_read_directory() {
+        let saves = WhereWasISaves::read_directory(&test_config("assets/tests"));
+
+        assert_eq!(
+            saves.get("identity").map(|record| record.transform),
+            Some(Transform::IDENTITY)
+        );
+        assert!(saves.contains("camera"));
+        assert!(!saves.contains("invalid_file"));
+        assert!(!saves.contains("invalid_version"));
+        assert_eq!(
+            saves.failure("invalid_version"),
+            Some(&WhereWasIParseError::UnsupportedVersion {
+                line: 1,
+                found: "v1".into()
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let record = SaveRecord {
+            transform: Transform::from_xyz(1.0, 2.0, 3.0),
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_760_000_000)),
+            trigger: Some(SaveTrigger::Autosave),
+        };
+
+        let json = serde_json::to_string(&record).unwrap();
+
+        assert_eq!(serde_json::from_str::<SaveRecord>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn test_remove_expired() {
+        let mut saves = WhereWasISaves::default();
+        let old = SystemTime::now() - Duration::from_secs(3 * 24 * 60 * 60);
+        for (name, modified) in [
+            ("old", Some(old)),
+            ("new", Some(SystemTime::now())),
+            ("unknown", None),
+        ] {
+            saves.insert(
+                name,
+                SaveRecord {
+                    transform: Transform::IDENTITY,
+                    modified,
+                    trigger: None,
+                },
+            );
+        }
+
+        let expired = saves.remove_expired(Duration::from_secs(24 * 60 * 60));
+
+        assert_eq!(expired, vec!["old"]);
+        assert_eq!(
+            saves.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["new", "unknown"]
+        );
+    }
+
+    #[test]
+    fn test_read_missing_directory() {
+        assert!(
+            WhereWasISaves::read_directory(&test_config("assets/tests/does_not_exist")).is_empty()
+        );
+    }
+}
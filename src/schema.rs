@@ -0,0 +1,365 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::prelude::*;
+
+use crate::persist::saving_all;
+use crate::serialization::versioned_lines;
+use crate::storage;
+use crate::throttle::SaveAdmitted;
+use crate::{Field, WhereWasIConfig, WhereWasIParseError};
+
+/// The name of the file with the manifest of the [`WhereWasISchema`] in the save directory
+pub const SCHEMA_FILE: &str = "schema.manifest";
+
+/// How a type in the [`WhereWasISchema`] was registered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SchemaKind {
+    /// With [`WhereWasIAppExt::persist_resource`](crate::WhereWasIAppExt::persist_resource)
+    Resource,
+    /// With [`WhereWasIAppExt::persist_component`](crate::WhereWasIAppExt::persist_component)
+    Component,
+    /// With [`WhereWasIAppExt::persist_custom`](crate::WhereWasIAppExt::persist_custom)
+    Custom,
+}
+
+impl SchemaKind {
+    /// The name used in the manifest
+    fn name(self) -> &'static str {
+        match self {
+            Self::Resource => "resource",
+            Self::Component => "component",
+            Self::Custom => "custom",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "resource" => Some(Self::Resource),
+            "component" => Some(Self::Component),
+            "custom" => Some(Self::Custom),
+            _ => None,
+        }
+    }
+}
+
+/// A persisted type in the [`WhereWasISchema`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchemaEntry {
+    /// How the type was registered
+    pub kind: SchemaKind,
+    /// The name the type is saved under
+    pub name: String,
+    /// The [`Persist::VERSION`](crate::Persist::VERSION) of the type, or `None` for
+    /// [`Persistable`](crate::Persistable) types, which don't store one
+    pub version: Option<u32>,
+    /// The Rust type name of the type
+    pub type_name: String,
+}
+
+impl fmt::Display for SchemaEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({}", self.kind.name(), self.name, self.type_name)?;
+        match self.version {
+            Some(version) => write!(f, ", version {version})"),
+            None => f.write_str(")"),
+        }
+    }
+}
+
+/// A difference between the manifest in the save directory and the [`WhereWasISchema`] of the
+/// running application, see [`WhereWasISchema::changes`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// The type is persisted now, but wasn't when the manifest was written
+    Added(SchemaEntry),
+    /// The type was persisted when the manifest was written, but isn't anymore
+    Removed(SchemaEntry),
+    /// The type or version saved under a name changed
+    Changed {
+        /// The entry in the manifest
+        saved: SchemaEntry,
+        /// The entry of the running application
+        current: SchemaEntry,
+    },
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added(entry) => write!(f, "{entry} was added"),
+            Self::Removed(entry) => write!(f, "{entry} is no longer persisted"),
+            Self::Changed { saved, current } => write!(f, "{saved} changed to {current}"),
+        }
+    }
+}
+
+/// The types persisted by the application, with the names and versions they are saved under
+///
+/// Every call to [`WhereWasIAppExt::persist_resource`](crate::WhereWasIAppExt::persist_resource),
+/// [`persist_component`](crate::WhereWasIAppExt::persist_component) and
+/// [`persist_custom`](crate::WhereWasIAppExt::persist_custom) adds an entry. The manifest of the
+/// schema is written to [`SCHEMA_FILE`] in the save directory whenever all entities are saved,
+/// and compared to the schema at startup, which logs a warning for every [`SchemaChange`]. To
+/// check the schema in CI, write it with [`write_manifest`](Self::write_manifest) and compare it
+/// to a committed copy.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasISchema;
+///
+/// fn dump_schema(schema: Res<WhereWasISchema>) {
+///     schema
+///         .write_manifest("schema.manifest")
+///         .expect("Could not write schema");
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WhereWasISchema {
+    entries: Vec<SchemaEntry>,
+}
+
+impl WhereWasISchema {
+    /// The persisted types, ordered by kind and name
+    pub fn entries(&self) -> &[SchemaEntry] {
+        &self.entries
+    }
+
+    /// Add `entry`, replacing the entry for the same kind and type
+    pub(crate) fn register(&mut self, entry: SchemaEntry) {
+        self.entries.retain(|existing| {
+            existing.kind != entry.kind || existing.type_name != entry.type_name
+        });
+        self.entries.push(entry);
+        self.entries.sort();
+    }
+
+    /// The manifest of the schema, with one `kind name version type` line per entry
+    ///
+    /// The version is `-` for types without one.
+    pub fn manifest(&self) -> String {
+        let mut contents = String::from("v0\n\n");
+        for entry in &self.entries {
+            let version = entry
+                .version
+                .map_or_else(|| "-".into(), |version| version.to_string());
+            contents.push_str(&format!(
+                "{} {} {} {}\n",
+                entry.kind.name(),
+                entry.name,
+                version,
+                entry.type_name
+            ));
+        }
+        contents
+    }
+
+    /// Parse a manifest written by [`manifest`](Self::manifest)
+    pub fn parse_manifest(contents: &str) -> Result<Self, WhereWasIParseError> {
+        let lines = versioned_lines(contents)?;
+
+        let mut schema = Self::default();
+        for (index, line) in lines.iter().enumerate().skip(1) {
+            if line.is_empty() {
+                continue;
+            }
+            let unexpected = || WhereWasIParseError::UnexpectedLine {
+                line: index + 1,
+                expected: Field::SchemaEntry,
+                found: (*line).into(),
+            };
+
+            let mut parts = line.splitn(4, ' ');
+            let (Some(kind), Some(name), Some(version), Some(type_name)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return Err(unexpected());
+            };
+            let kind = SchemaKind::from_name(kind).ok_or_else(unexpected)?;
+            let version = match version {
+                "-" => None,
+                version => Some(version.parse().map_err(|_| unexpected())?),
+            };
+            schema.register(SchemaEntry {
+                kind,
+                name: name.into(),
+                version,
+                type_name: type_name.into(),
+            });
+        }
+        Ok(schema)
+    }
+
+    /// Write the manifest of the schema to `path`
+    pub fn write_manifest(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        storage::write(path.as_ref(), self.manifest().as_bytes())
+    }
+
+    /// Read a manifest from `path`, or `None` if there is no file at `path`
+    pub fn read_manifest(path: impl AsRef<Path>) -> Result<Option<Self>, WhereWasIParseError> {
+        match storage::read_to_string(path.as_ref()) {
+            Ok(contents) => Self::parse_manifest(&contents).map(Some),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// How this schema differs from the `saved` one, matching entries by kind and name
+    pub fn changes(&self, saved: &Self) -> Vec<SchemaChange> {
+        let find = |schema: &Self, entry: &SchemaEntry| {
+            schema
+                .entries
+                .iter()
+                .find(|other| other.kind == entry.kind && other.name == entry.name)
+                .cloned()
+        };
+
+        let mut changes = Vec::new();
+        for saved_entry in &saved.entries {
+            match find(self, saved_entry) {
+                None => changes.push(SchemaChange::Removed(saved_entry.clone())),
+                Some(current) if current != *saved_entry => changes.push(SchemaChange::Changed {
+                    saved: saved_entry.clone(),
+                    current,
+                }),
+                Some(_) => {}
+            }
+        }
+        for current in &self.entries {
+            if find(saved, current).is_none() {
+                changes.push(SchemaChange::Added(current.clone()));
+            }
+        }
+        changes
+    }
+}
+
+/// Warn about every difference between the manifest in the save directory and the schema
+pub(crate) fn check_schema(schema: Res<WhereWasISchema>, config: Res<WhereWasIConfig>) {
+    match WhereWasISchema::read_manifest(config.directory.join(SCHEMA_FILE)) {
+        Ok(Some(saved)) => {
+            for change in schema.changes(&saved) {
+                warn!(
+                    "The persisted types changed since the last save: {}",
+                    change
+                );
+            }
+        }
+        Ok(None) => {}
+        Err(err) => warn!("Could not read {}: {}", SCHEMA_FILE, err),
+    }
+}
+
+/// Write the manifest of the schema when all entities are saved
+///
+/// No manifest is written for applications that don't persist types of their own.
+pub(crate) fn save_schema(
+    mut admitted: MessageReader<SaveAdmitted>,
+    schema: Res<WhereWasISchema>,
+    config: Res<WhereWasIConfig>,
+) {
+    if !saving_all(&mut admitted, &config) {
+        return;
+    }
+
+    let path = config.directory.join(SCHEMA_FILE);
+    let result = if schema.entries.is_empty() {
+        storage::remove_file(&path).or_else(|err| match err.kind() {
+            io::ErrorKind::NotFound => Ok(()),
+            _ => Err(err),
+        })
+    } else {
+        storage::create_dir_all(&config.directory).and_then(|_| schema.write_manifest(&path))
+    };
+    if let Err(err) = result {
+        error!("Could not save {}: {}", SCHEMA_FILE, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::save_state;
+    use crate::tests::{close_window, test_app, test_config};
+
+    fn entry(kind: SchemaKind, name: &str, version: Option<u32>) -> SchemaEntry {
+        SchemaEntry {
+            kind,
+            name: name.into(),
+            version,
+            type_name: format!("game::{name}"),
+        }
+    }
+
+    #[test]
+    fn test_manifest() {
+        let mut schema = WhereWasISchema::default();
+        schema.register(entry(SchemaKind::Custom, "recent", None));
+        schema.register(entry(SchemaKind::Resource, "workspace", Some(2)));
+        schema.register(SchemaEntry {
+            type_name: "game::Pair<f32, f32>".into(),
+            ..entry(SchemaKind::Component, "pair", Some(0))
+        });
+
+        let manifest = schema.manifest();
+        assert_eq!(
+            manifest,
+            "v0\n\nresource workspace 2 game::workspace\ncomponent pair 0 game::Pair<f32, f32>\n\
+             custom recent - game::recent\n"
+        );
+        assert_eq!(WhereWasISchema::parse_manifest(&manifest), Ok(schema));
+        assert_eq!(
+            WhereWasISchema::parse_manifest("v0\n\nresource workspace x game::Workspace\n"),
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 3,
+                expected: Field::SchemaEntry,
+                found: "resource workspace x game::Workspace".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_schema_changes() {
+        let mut saved = WhereWasISchema::default();
+        saved.register(entry(SchemaKind::Resource, "workspace", Some(1)));
+        saved.register(entry(SchemaKind::Component, "lens", Some(0)));
+
+        let mut current = WhereWasISchema::default();
+        current.register(entry(SchemaKind::Resource, "workspace", Some(2)));
+        current.register(entry(SchemaKind::Custom, "recent", None));
+
+        assert_eq!(
+            current.changes(&saved),
+            vec![
+                SchemaChange::Changed {
+                    saved: entry(SchemaKind::Resource, "workspace", Some(1)),
+                    current: entry(SchemaKind::Resource, "workspace", Some(2)),
+                },
+                SchemaChange::Removed(entry(SchemaKind::Component, "lens", Some(0))),
+                SchemaChange::Added(entry(SchemaKind::Custom, "recent", None)),
+            ]
+        );
+        assert!(current.changes(&current).is_empty());
+    }
+
+    #[test]
+    fn test_save_schema() {
+        let mut app = test_app(test_config("assets/tests/schema"));
+        let mut schema = WhereWasISchema::default();
+        schema.register(entry(SchemaKind::Resource, "workspace", Some(1)));
+        app.insert_resource(schema.clone())
+            .add_systems(Update, (save_state, save_schema).chain());
+        close_window(&mut app);
+        app.update();
+
+        assert_eq!(
+            WhereWasISchema::read_manifest("assets/tests/schema/schema.manifest"),
+            Ok(Some(schema))
+        );
+
+        fs::remove_dir_all("assets/tests/schema").unwrap();
+    }
+}
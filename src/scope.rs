@@ -0,0 +1,181 @@
+use std::time::SystemTime;
+
+use crate::prelude::*;
+
+use crate::{
+    Initialized, PersistenceUnavailable, SaveRecord, SaveTrigger, WhereWasI, WhereWasIAuthored,
+    WhereWasIConfig, WhereWasISaves, write_state,
+};
+
+/// A [`Resource`] with the identifier of the current level or scene
+///
+/// Entities created with [`WhereWasI::scoped_by_level`] are saved under their name plus this
+/// identifier, for example `camera@forest`, so they remember a pose per level. Without this
+/// resource they are saved under their name alone.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasILevel;
+///
+/// fn enter_forest(mut commands: Commands) {
+///     commands.insert_resource(WhereWasILevel::new("forest"));
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct WhereWasILevel(pub String);
+
+impl WhereWasILevel {
+    /// Construct a [`WhereWasILevel`] with an identifier
+    pub fn new(level: impl Into<String>) -> Self {
+        Self(level.into())
+    }
+}
+
+/// The name and the level an entity created with [`WhereWasI::scoped_by_level`] is saved under
+#[derive(Reflect, Clone, Debug)]
+pub(crate) struct LevelScope {
+    pub(crate) name: String,
+    pub(crate) level: Option<String>,
+}
+
+impl LevelScope {
+    /// The name of the savefile
+    pub(crate) fn key(&self) -> String {
+        match &self.level {
+            Some(level) => format!("{}@{}", self.name, level),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Save entities created with [`WhereWasI::scoped_by_level`] under the level they were in, and
+/// restore them from the current [`WhereWasILevel`]
+///
+/// Entities that were already restored are saved under their previous level and reverted to their
+/// authored [`Transform`], after which [`load_state`](crate::load_state) restores them from the
+/// savefile of the new level, if there is one. This runs right before entities are restored.
+pub(crate) fn apply_level(
+    mut commands: Commands,
+    level: Option<Res<WhereWasILevel>>,
+    mut scoped: Query<(
+        Entity,
+        &mut WhereWasI,
+        &mut Transform,
+        Option<&WhereWasIAuthored>,
+    )>,
+    initialized: Query<(), With<Initialized>>,
+    mut saves: ResMut<WhereWasISaves>,
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let level = level.as_deref().map(|level| level.0.as_str());
+
+    for (entity, mut where_was_i, mut transform, authored) in &mut scoped {
+        let Some(scope) = &where_was_i.scope else {
+            continue;
+        };
+        if scope.level.as_deref() == level {
+            continue;
+        }
+
+        if initialized.contains(entity) {
+            let config = config.group(where_was_i.group.as_deref());
+            let name = &where_was_i.name;
+            if where_was_i.follows || config.dry_run || unavailable.is_some() {
+                debug!("Not saving {} before changing the level", name);
+            } else {
+                match write_state(config, name, &transform) {
+                    Ok(_) => saves.insert(
+                        name,
+                        SaveRecord {
+                            transform: *transform,
+                            modified: Some(SystemTime::now()),
+                            trigger: Some(SaveTrigger::Manual),
+                        },
+                    ),
+                    Err(err) => error!("Could not save {}: {}", name, err),
+                }
+            }
+
+            if let Some(authored) = authored {
+                *transform = authored.0;
+            }
+            commands.entity(entity).remove::<Initialized>();
+        }
+
+        let where_was_i = where_was_i.as_mut();
+        if let Some(scope) = &mut where_was_i.scope {
+            scope.level = level.map(Into::into);
+            where_was_i.name = scope.key();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{load_state, read_saves, read_state};
+
+    #[test]
+    fn test_scoped_by_level() {
+        let directory = "assets/tests/scoped_by_level";
+        let config = test_config(directory);
+        write_state(
+            &config,
+            "camera@forest",
+            &Transform::from_xyz(1.0, 2.0, 3.0),
+        )
+        .unwrap();
+
+        let mut app = test_app(config);
+        app.insert_resource(WhereWasILevel::new("forest"))
+            .add_systems(PostStartup, read_saves)
+            .add_systems(Update, (apply_level, load_state).chain());
+        let camera = app
+            .world_mut()
+            .spawn(WhereWasI::camera().scoped_by_level())
+            .id();
+        app.update();
+
+        let where_was_i = app.world().get::<WhereWasI>(camera).unwrap();
+        assert_eq!(where_was_i.name(), "camera");
+        assert_eq!(where_was_i.name, "camera@forest");
+        assert_eq!(
+            *app.world().get::<Transform>(camera).unwrap(),
+            Transform::from_xyz(1.0, 2.0, 3.0)
+        );
+
+        app.world_mut()
+            .get_mut::<Transform>(camera)
+            .unwrap()
+            .translation
+            .x = 5.0;
+        app.insert_resource(WhereWasILevel::new("desert"));
+        app.update();
+
+        assert_eq!(
+            read_state(format!("{directory}/camera@forest.state")).unwrap(),
+            Transform::from_xyz(5.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            app.world().get::<WhereWasI>(camera).unwrap().name,
+            "camera@desert"
+        );
+        assert_eq!(
+            *app.world().get::<Transform>(camera).unwrap(),
+            Transform::IDENTITY
+        );
+
+        app.insert_resource(WhereWasILevel::new("forest"));
+        app.update();
+        assert_eq!(
+            *app.world().get::<Transform>(camera).unwrap(),
+            Transform::from_xyz(5.0, 2.0, 3.0)
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,42 +1,395 @@
-use std::io::{self};
-use std::num::ParseFloatError;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+#[cfg(any(feature = "window", feature = "viewports"))]
+use std::str::FromStr;
 use std::{
     fs::File,
     io::{BufWriter, Write},
 };
 
-use bevy::prelude::*;
+use crate::prelude::*;
+
+use crate::InvalidNameError;
+
+/// A line of a savefile or session file, used by [`WhereWasIParseError`] to tell what was expected
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Field {
+    /// The version on the first line
+    Version,
+    /// The `translation:` header
+    TranslationHeader,
+    /// The x component of the translation
+    TranslationX,
+    /// The y component of the translation
+    TranslationY,
+    /// The z component of the translation
+    TranslationZ,
+    /// The `rotation:` header
+    RotationHeader,
+    /// The x component of the rotation quaternion
+    RotationX,
+    /// The y component of the rotation quaternion
+    RotationY,
+    /// The z component of the rotation quaternion
+    RotationZ,
+    /// The w component of the rotation quaternion
+    RotationW,
+    /// The `scale:` header
+    ScaleHeader,
+    /// The x component of the scale
+    ScaleX,
+    /// The y component of the scale
+    ScaleY,
+    /// The z component of the scale
+    ScaleZ,
+    /// The time a session was saved at, in a session file
+    SavedAt,
+    /// The number of saved entities, in a session file
+    Entities,
+    /// The position of a window, in a window file
+    WindowPosition,
+    /// The size of a window, in a window file
+    WindowSize,
+    /// The position of a camera viewport, in a viewport file
+    ViewportPosition,
+    /// The size of a camera viewport, in a viewport file
+    ViewportSize,
+    /// A sample of a transform, in a replay
+    ReplaySample,
+    /// The `f64` translation, in a position file
+    PreciseTranslation,
+    /// The grid cell, in a position file
+    GridCell,
+    /// The order of a camera, in a camera file
+    CameraOrder,
+    /// The render layers of a camera, in a camera file
+    RenderLayers,
+    /// A field of a [`Persist`](crate::Persist) struct
+    PersistedField,
+    /// An entry of a [`WhereWasISchema`](crate::WhereWasISchema) manifest
+    SchemaEntry,
+}
+
+impl Field {
+    /// The contents of the line for headers, which have to match exactly
+    fn header(self) -> Option<&'static str> {
+        match self {
+            Self::TranslationHeader => Some("translation:"),
+            Self::RotationHeader => Some("rotation:"),
+            Self::ScaleHeader => Some("scale:"),
+            _ => None,
+        }
+    }
+}
 
-/// Represents an error that occurred while parsing a savefile
-#[derive(Debug, PartialEq)]
-pub struct WhereWasIParseError {
-    pub message: String,
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Version => "a version",
+            Self::TranslationHeader => "\"translation:\"",
+            Self::RotationHeader => "\"rotation:\"",
+            Self::ScaleHeader => "\"scale:\"",
+            Self::TranslationX => "translation.x",
+            Self::TranslationY => "translation.y",
+            Self::TranslationZ => "translation.z",
+            Self::RotationX => "rotation.x",
+            Self::RotationY => "rotation.y",
+            Self::RotationZ => "rotation.z",
+            Self::RotationW => "rotation.w",
+            Self::ScaleX => "scale.x",
+            Self::ScaleY => "scale.y",
+            Self::ScaleZ => "scale.z",
+            Self::SavedAt => "saved_at",
+            Self::Entities => "entities",
+            Self::WindowPosition => "window position",
+            Self::WindowSize => "window size",
+            Self::ViewportPosition => "viewport position",
+            Self::ViewportSize => "viewport size",
+            Self::ReplaySample => "a replay sample",
+            Self::PreciseTranslation => "precise translation",
+            Self::GridCell => "grid cell",
+            Self::CameraOrder => "camera order",
+            Self::RenderLayers => "render layers",
+            Self::PersistedField => "a field",
+            Self::SchemaEntry => "a schema entry",
+        })
+    }
+}
+
+/// Represents an error that occurred while reading a savefile
+///
+/// Match on the variant to decide how to handle a savefile that can't be read, for example to
+/// fall back to a default for an [`UnsupportedVersion`](Self::UnsupportedVersion) but abort on
+/// an [`Io`](Self::Io) error.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WhereWasIParseError {
+    /// The file could not be read
+    Io {
+        /// The line that could not be read, if the file could be opened
+        line: Option<usize>,
+        /// The description of the underlying [`io::Error`]
+        message: String,
+    },
+    /// The file was written by an unsupported version of this crate
+    UnsupportedVersion {
+        /// The line with the version
+        line: usize,
+        /// The version found in the file
+        found: String,
+    },
+    /// The file ended before all fields were read
+    MissingLine {
+        /// The line where `expected` should have been
+        line: usize,
+        /// The field that is missing
+        expected: Field,
+    },
+    /// A line doesn't contain what was expected there
+    UnexpectedLine {
+        /// The offending line
+        line: usize,
+        /// What the line should have contained
+        expected: Field,
+        /// The contents of the line
+        found: String,
+    },
+    /// A line that should contain a number couldn't be parsed as one
+    InvalidFloat {
+        /// The offending line
+        line: usize,
+        /// The field the number is for
+        field: Field,
+        /// The contents of the line
+        value: String,
+    },
+    /// A value is NaN or infinite
+    NonFinite {
+        /// The field with the value
+        field: Field,
+    },
+    /// The rotation quaternion has (nearly) zero length, so it can't be normalized
+    ZeroRotation,
+    /// The length of the rotation quaternion deviates too much from 1, see
+    /// [`WhereWasIPlugin::strict_rotations`](crate::WhereWasIPlugin::strict_rotations)
+    NonUnitRotation {
+        /// The length of the rotation quaternion
+        length: f32,
+    },
+    /// A component of the scale is (nearly) zero
+    ZeroScale {
+        /// The component of the scale
+        field: Field,
+    },
+    /// The file isn't in [`SaveFormat::Binary`]
+    NotBinary,
+    /// A binary savefile has the wrong length
+    InvalidBinaryLength {
+        /// The number of bytes of values in the file
+        found: usize,
+    },
+    /// The name of the savefile can't be used as a file name, see [`InvalidNameError`]
+    InvalidName {
+        /// The name that was rejected
+        name: String,
+        /// Why the name was rejected
+        reason: String,
+    },
+    /// A field of a [`Persist`](crate::Persist) struct has a value of the wrong type
+    InvalidValue {
+        /// The name of the field
+        field: String,
+        /// The value that couldn't be parsed
+        value: String,
+    },
 }
 
-impl WhereWasIParseError {
-    pub fn expected_line() -> Self {
-        Self {
-            message: "Expected line to be there, but it wasn't there".into(),
+impl fmt::Display for WhereWasIParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io {
+                line: Some(line),
+                message,
+            } => write!(f, "Line {line}: {message}"),
+            Self::Io {
+                line: None,
+                message,
+            } => f.write_str(message),
+            Self::UnsupportedVersion { line, found } => {
+                write!(f, "Line {line}: unsupported version {found:?}")
+            }
+            Self::MissingLine { line, expected } => {
+                write!(f, "Line {line}: expected {expected}, found end of file")
+            }
+            Self::UnexpectedLine {
+                line,
+                expected,
+                found,
+            } => write!(f, "Line {line}: expected {expected}, found {found:?}"),
+            Self::InvalidFloat { line, field, value } => {
+                write!(
+                    f,
+                    "Line {line}: expected a number for {field}, found {value:?}"
+                )
+            }
+            Self::NonFinite { field } => write!(f, "{field} is not finite"),
+            Self::ZeroRotation => f.write_str("rotation has zero length"),
+            Self::NonUnitRotation { length } => {
+                write!(f, "rotation has length {length}, expected 1")
+            }
+            Self::ZeroScale { field } => write!(f, "{field} is zero"),
+            Self::NotBinary => f.write_str("Not a binary savefile"),
+            Self::InvalidBinaryLength { found } => {
+                write!(f, "Expected 40 bytes of values, found {found}")
+            }
+            Self::InvalidName { name, reason } => write!(f, "Invalid name \"{name}\": {reason}"),
+            Self::InvalidValue { field, value } => {
+                write!(f, "invalid value {value:?} for field {field}")
+            }
         }
     }
 }
 
+impl Error for WhereWasIParseError {}
+
 impl From<io::Error> for WhereWasIParseError {
     fn from(value: io::Error) -> Self {
-        Self {
+        Self::Io {
+            line: None,
             message: value.to_string(),
         }
     }
 }
-impl From<ParseFloatError> for WhereWasIParseError {
-    fn from(value: ParseFloatError) -> Self {
-        Self {
-            message: value.to_string(),
+
+impl From<InvalidNameError> for WhereWasIParseError {
+    fn from(value: InvalidNameError) -> Self {
+        Self::InvalidName {
+            name: value.name,
+            reason: value.reason.into(),
         }
     }
 }
 
-/// Serializes a [`Transform`] and writes it to the BufWriter
+/// The first bytes of a savefile in [`SaveFormat::Binary`]
+const BINARY_MAGIC: &[u8; 4] = b"wwi\0";
+
+/// The format savefiles are written in
+///
+/// Savefiles are always read in the format they were written in, so changing the format of an
+/// application only affects new saves. Use [`convert`](crate::convert) to migrate existing
+/// savefiles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SaveFormat {
+    /// A human-readable format with one value per line, which is easy to edit by hand
+    #[default]
+    Text,
+    /// A compact binary format with the values as little-endian 32-bit floats
+    Binary,
+}
+
+impl SaveFormat {
+    /// The format of the savefile at `path`
+    pub fn detect(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::detect_buffered(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// The format of the savefile read by `reader`, without consuming anything
+    ///
+    /// This lets a savefile be detected and deserialized with a single open.
+    pub(crate) fn detect_buffered(reader: &mut impl BufRead) -> io::Result<Self> {
+        Ok(if reader.fill_buf()?.starts_with(BINARY_MAGIC) {
+            Self::Binary
+        } else {
+            Self::Text
+        })
+    }
+
+    /// Serialize `transform` in this format
+    pub fn serialize(
+        self,
+        writer: &mut BufWriter<impl Write>,
+        transform: &Transform,
+    ) -> Result<(), io::Error> {
+        match self {
+            Self::Text => serialize_transform(writer, transform),
+            Self::Binary => serialize_binary(writer, transform),
+        }
+    }
+
+    /// Deserialize a [`Transform`] in this format
+    ///
+    /// ```rust
+    /// use std::io::BufWriter;
+    ///
+    /// use bevy::prelude::*;
+    /// use bevy_where_was_i::SaveFormat;
+    ///
+    /// let mut writer = BufWriter::new(Vec::new());
+    /// SaveFormat::Binary.serialize(&mut writer, &Transform::from_xyz(1.0, 2.0, 3.0))?;
+    /// let bytes = writer.into_inner()?;
+    /// assert_eq!(
+    ///     SaveFormat::Binary.deserialize(bytes.as_slice())?,
+    ///     Transform::from_xyz(1.0, 2.0, 3.0)
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn deserialize(self, reader: impl BufRead) -> Result<Transform, WhereWasIParseError> {
+        match self {
+            Self::Text => deserialize_transform(reader),
+            Self::Binary => deserialize_binary(reader),
+        }
+    }
+}
+
+/// Serializes a [`Transform`] in [`SaveFormat::Binary`] and writes it to the BufWriter
+fn serialize_binary(
+    writer: &mut BufWriter<impl Write>,
+    transform: &Transform,
+) -> Result<(), io::Error> {
+    writer.write_all(BINARY_MAGIC)?;
+
+    let values = transform
+        .translation
+        .to_array()
+        .into_iter()
+        .chain(transform.rotation.to_array())
+        .chain(transform.scale.to_array());
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Deserializes a [`Transform`] in [`SaveFormat::Binary`]
+pub(crate) fn deserialize_binary(reader: impl Read) -> Result<Transform, WhereWasIParseError> {
+    let mut bytes = Vec::new();
+    reader.take(64).read_to_end(&mut bytes)?;
+
+    let Some(values) = bytes.strip_prefix(BINARY_MAGIC.as_slice()) else {
+        return Err(WhereWasIParseError::NotBinary);
+    };
+    if values.len() != 10 * 4 {
+        return Err(WhereWasIParseError::InvalidBinaryLength {
+            found: values.len(),
+        });
+    }
+
+    let values = values
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect::<Vec<_>>();
+
+    Ok(Transform {
+        translation: Vec3::from_slice(&values[0..3]),
+        rotation: Quat::from_slice(&values[3..7]),
+        scale: Vec3::from_slice(&values[7..10]),
+    })
+}
+
+/// Serializes a [`Transform`] in [`SaveFormat::Text`] and writes it to the BufWriter
 ///
 /// Note: we could use serde using the `serialization` feature of Bevy. However, that requires
 /// external depedencies which we can avoid by doing the (de)serialization manually.
@@ -75,54 +428,120 @@ pub fn serialize_transform(
     Ok(())
 }
 
-/// Read the next line and parse it into an f32
-fn next_float(lines: &mut io::Lines<io::BufReader<File>>) -> Result<f32, WhereWasIParseError> {
-    Ok(lines
-        .next()
-        .ok_or(WhereWasIParseError::expected_line())??
-        .parse::<f32>()?)
+/// Reads the lines of a savefile in [`SaveFormat::Text`] one token at a time
+///
+/// Leading and trailing whitespace (including the `\r` of Windows line endings) is ignored and
+/// blank lines are skipped. The current line number is tracked so that errors point at the
+/// offending line.
+struct Tokenizer<I> {
+    lines: I,
+    line: usize,
+}
+
+impl<I: Iterator<Item = io::Result<String>>> Tokenizer<I> {
+    fn new(lines: I) -> Self {
+        Self { lines, line: 0 }
+    }
+
+    /// The next non-blank line, where `expected` should be
+    fn next_token(&mut self, expected: Field) -> Result<String, WhereWasIParseError> {
+        for line in self.lines.by_ref() {
+            self.line += 1;
+            let line = line.map_err(|err| WhereWasIParseError::Io {
+                line: Some(self.line),
+                message: err.to_string(),
+            })?;
+
+            let line = line.trim();
+            if !line.is_empty() {
+                return Ok(line.into());
+            }
+        }
+
+        Err(WhereWasIParseError::MissingLine {
+            line: self.line + 1,
+            expected,
+        })
+    }
+
+    /// Consume a line that must be exactly the `header`
+    fn header(&mut self, header: Field) -> Result<(), WhereWasIParseError> {
+        let token = self.next_token(header)?;
+        if Some(token.as_str()) != header.header() {
+            return Err(WhereWasIParseError::UnexpectedLine {
+                line: self.line,
+                expected: header,
+                found: token,
+            });
+        }
+        Ok(())
+    }
+
+    /// Consume a line with the value of `field`
+    fn float(&mut self, field: Field) -> Result<f32, WhereWasIParseError> {
+        let token = self.next_token(field)?;
+        parse_float(&token).ok_or(WhereWasIParseError::InvalidFloat {
+            line: self.line,
+            field,
+            value: token,
+        })
+    }
+}
+
+/// Parse `token` as a number, also accepting a comma as decimal separator
+///
+/// Every value is on its own line, so `1,5` can only mean 1.5. Tokens with both a comma and a dot
+/// are rejected, as it's unclear which one is the thousands separator.
+fn parse_float(token: &str) -> Option<f32> {
+    if let Ok(value) = token.parse() {
+        return Some(value);
+    }
+
+    if token.contains('.') || token.matches(',').count() != 1 {
+        return None;
+    }
+    token.replace(',', ".").parse().ok()
 }
 
-/// Deserializes lines into a [`Transform`]
+/// Deserializes a [`Transform`] in [`SaveFormat::Text`] from `reader`
+///
+/// `reader` can be a file, but also bytes in memory or received over the network. Never panics:
+/// malformed or truncated input results in an error with the line number and the token that was
+/// expected there.
 ///
 /// Note: we could use serde using the `serialization` feature of Bevy. However, that requires
 /// external depedencies which we can avoid by doing the (de)serialization manually.
-pub fn deserialize_transform(
-    mut lines: io::Lines<io::BufReader<File>>,
-) -> Result<Transform, WhereWasIParseError> {
-    let version = lines.next().ok_or(WhereWasIParseError::expected_line())??;
+pub fn deserialize_transform(reader: impl BufRead) -> Result<Transform, WhereWasIParseError> {
+    let mut tokens = Tokenizer::new(reader.lines());
+
+    let version = tokens.next_token(Field::Version)?;
     if version != "v0" {
-        return Err(WhereWasIParseError {
-            message: format!("Wrong version: {version}"),
+        return Err(WhereWasIParseError::UnsupportedVersion {
+            line: tokens.line,
+            found: version,
         });
     }
 
-    lines.next().ok_or(WhereWasIParseError::expected_line())??;
-    lines.next().ok_or(WhereWasIParseError::expected_line())??;
-
+    tokens.header(Field::TranslationHeader)?;
     let translation = Vec3::new(
-        next_float(&mut lines)?,
-        next_float(&mut lines)?,
-        next_float(&mut lines)?,
+        tokens.float(Field::TranslationX)?,
+        tokens.float(Field::TranslationY)?,
+        tokens.float(Field::TranslationZ)?,
     );
 
-    lines.next().ok_or(WhereWasIParseError::expected_line())??;
-    lines.next().ok_or(WhereWasIParseError::expected_line())??;
-
+    tokens.header(Field::RotationHeader)?;
     let rotation = Vec4::new(
-        next_float(&mut lines)?,
-        next_float(&mut lines)?,
-        next_float(&mut lines)?,
-        next_float(&mut lines)?,
+        tokens.float(Field::RotationX)?,
+        tokens.float(Field::RotationY)?,
+        tokens.float(Field::RotationZ)?,
+        tokens.float(Field::RotationW)?,
     );
 
-    lines.next().ok_or(WhereWasIParseError::expected_line())??;
-    lines.next().ok_or(WhereWasIParseError::expected_line())??;
-
+    tokens.header(Field::ScaleHeader)?;
     let scale = Vec3::new(
-        next_float(&mut lines)?,
-        next_float(&mut lines)?,
-        next_float(&mut lines)?,
+        tokens.float(Field::ScaleX)?,
+        tokens.float(Field::ScaleY)?,
+        tokens.float(Field::ScaleZ)?,
     );
 
     Ok(Transform {
@@ -132,12 +551,59 @@ pub fn deserialize_transform(
     })
 }
 
+/// The two whitespace-separated values on the line after `key`, or `None` if `lines` has no `key`
+#[cfg(any(feature = "window", feature = "viewports"))]
+pub(crate) fn parse_pair<T: FromStr>(
+    lines: &[&str],
+    key: &str,
+    field: Field,
+) -> Result<Option<(T, T)>, WhereWasIParseError> {
+    let Some(index) = lines.iter().position(|line| *line == key) else {
+        return Ok(None);
+    };
+    let line = lines.get(index + 1).copied().unwrap_or("");
+    let unexpected = || WhereWasIParseError::UnexpectedLine {
+        line: index + 2,
+        expected: field,
+        found: line.into(),
+    };
+
+    match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [x, y] => Ok(Some((
+            x.parse().map_err(|_| unexpected())?,
+            y.parse().map_err(|_| unexpected())?,
+        ))),
+        _ => Err(unexpected()),
+    }
+}
+
+/// The lines of a file with `key:` lines followed by values, such as a window or viewport file,
+/// after checking its version
+pub(crate) fn versioned_lines(contents: &str) -> Result<Vec<&str>, WhereWasIParseError> {
+    let lines = contents.lines().map(str::trim).collect::<Vec<_>>();
+
+    if lines.first() != Some(&"v0") {
+        return Err(WhereWasIParseError::UnsupportedVersion {
+            line: 1,
+            found: lines.first().unwrap_or(&"").to_string(),
+        });
+    }
+    Ok(lines)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::read_lines;
+    use proptest::prelude::*;
 
     use super::*;
 
+    /// Serialize `transform` in [`SaveFormat::Text`]
+    fn serialize_to_vec(transform: &Transform) -> Vec<u8> {
+        let mut buffer = BufWriter::new(Vec::new());
+        serialize_transform(&mut buffer, transform).expect("Expected serialization to succeed");
+        buffer.into_inner().unwrap()
+    }
+
     #[test]
     fn test_serialize_identity() {
         let mut buffer = BufWriter::new(Vec::new());
@@ -171,7 +637,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_identity() {
-        let buffer = read_lines("assets/tests/identity.state").expect("Could not read test file");
+        let buffer = &include_bytes!("../assets/tests/identity.state")[..];
         let transform = deserialize_transform(buffer).expect("Expected serialization to succeed");
 
         assert_eq!(transform, Transform::IDENTITY);
@@ -179,7 +645,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_camera() {
-        let buffer = read_lines("assets/tests/camera.state").expect("Could not read test file");
+        let buffer = &include_bytes!("../assets/tests/camera.state")[..];
         let transform = deserialize_transform(buffer).expect("Expected serialization to succeed");
 
         assert_eq!(
@@ -194,27 +660,173 @@ mod tests {
 
     #[test]
     fn test_deserialize_invalid_version() {
-        let buffer =
-            read_lines("assets/tests/invalid_version.state").expect("Could not read test file");
+        let buffer = &include_bytes!("../assets/tests/invalid_version.state")[..];
 
         assert_eq!(
             deserialize_transform(buffer),
-            Err(WhereWasIParseError {
-                message: "Wrong version: v1".into()
+            Err(WhereWasIParseError::UnsupportedVersion {
+                line: 1,
+                found: "v1".into()
             })
         );
     }
 
+    #[test]
+    fn test_binary_round_trip() {
+        let transform = Transform {
+            translation: Vec3::new(10.000002, 10.0, -10.0),
+            rotation: Quat::from_xyzw(-0.27984813, 0.36470526, 0.11591691, 0.88047624),
+            scale: Vec3::new(1.0, 2.0, 3.0),
+        };
+        let mut buffer = BufWriter::new(Vec::new());
+        SaveFormat::Binary
+            .serialize(&mut buffer, &transform)
+            .expect("Expected serialization to succeed");
+
+        assert_eq!(buffer.buffer().len(), 44);
+        assert_eq!(deserialize_binary(buffer.buffer()), Ok(transform));
+        assert!(deserialize_binary(&buffer.buffer()[..40]).is_err());
+        assert!(deserialize_binary(&b"v0\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            SaveFormat::detect("assets/tests/camera.state").unwrap(),
+            SaveFormat::Text
+        );
+        assert!(SaveFormat::detect("assets/tests/does_not_exist.state").is_err());
+
+        let mut reader = &b"wwi\0rest"[..];
+        assert_eq!(
+            SaveFormat::detect_buffered(&mut reader).unwrap(),
+            SaveFormat::Binary
+        );
+        assert_eq!(
+            reader, b"wwi\0rest",
+            "detecting should not consume the magic"
+        );
+    }
+
     #[test]
     fn test_deserialize_invalid_file() {
-        let buffer =
-            read_lines("assets/tests/invalid_file.state").expect("Could not read test file");
+        let buffer = &include_bytes!("../assets/tests/invalid_file.state")[..];
 
         assert_eq!(
             deserialize_transform(buffer),
-            Err(WhereWasIParseError {
-                message: "Expected line to be there, but it wasn't there".into()
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 3,
+                expected: Field::TranslationHeader,
+                found: "other text".into()
             })
         );
+        assert_eq!(
+            deserialize_transform(&include_bytes!("../assets/tests/invalid_file.state")[..])
+                .unwrap_err()
+                .to_string(),
+            "Line 3: expected \"translation:\", found \"other text\""
+        );
+    }
+
+    #[test]
+    fn test_deserialize_skips_blank_lines() {
+        let input: &[u8] =
+            b"v0\ntranslation:\n0\n0\n0\n\n\n\nrotation:\n0\n0\n0\n1\nscale:\n1\n1\n1";
+
+        assert_eq!(deserialize_transform(input), Ok(Transform::IDENTITY));
+    }
+
+    #[test]
+    fn test_deserialize_truncated() {
+        let input = &include_bytes!("../assets/tests/camera.state")[..27];
+
+        assert_eq!(
+            deserialize_transform(input),
+            Err(WhereWasIParseError::MissingLine {
+                line: 5,
+                expected: Field::TranslationY
+            })
+        );
+        assert_eq!(
+            deserialize_transform(input).unwrap_err().to_string(),
+            "Line 5: expected translation.y, found end of file"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid_float() {
+        let input: &[u8] = b"v0\n\ntranslation:\n1\ntwo\n3\n";
+
+        assert_eq!(
+            deserialize_transform(input),
+            Err(WhereWasIParseError::InvalidFloat {
+                line: 5,
+                field: Field::TranslationY,
+                value: "two".into()
+            })
+        );
+        assert_eq!(
+            deserialize_transform(input).unwrap_err().to_string(),
+            "Line 5: expected a number for translation.y, found \"two\""
+        );
+    }
+
+    #[test]
+    fn test_deserialize_crlf() {
+        let buffer = &include_bytes!("../assets/tests/crlf.state")[..];
+
+        assert_eq!(
+            deserialize_transform(buffer),
+            Ok(Transform::from_xyz(1.5, -2.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn test_parse_float() {
+        assert_eq!(parse_float("1.5"), Some(1.5));
+        assert_eq!(parse_float("-1,5"), Some(-1.5));
+        assert_eq!(parse_float("1e3"), Some(1000.0));
+        assert_eq!(parse_float("1,000.5"), None);
+        assert_eq!(parse_float("1,0,0"), None);
+        assert_eq!(parse_float("one"), None);
+    }
+
+    proptest! {
+        #[test]
+        fn test_deserialize_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+            if let Err(err) = deserialize_transform(&bytes[..]) {
+                prop_assert!(err.to_string().starts_with("Line "), "{}", err);
+            }
+        }
+
+        #[test]
+        fn test_deserialize_truncated_savefile(
+            x in -1e6f32..1e6,
+            angle in -10f32..10.0,
+            scale in 0.01f32..100.0,
+            cut in 0usize..256,
+        ) {
+            let transform = Transform::from_xyz(x, -x, 0.5)
+                .with_rotation(Quat::from_rotation_y(angle))
+                .with_scale(Vec3::splat(scale));
+            let bytes = serialize_to_vec(&transform);
+
+            if let Err(err) = deserialize_transform(&bytes[..cut.min(bytes.len())]) {
+                prop_assert!(err.to_string().starts_with("Line "), "{}", err);
+            }
+        }
+
+        #[test]
+        fn test_text_round_trip(
+            x in -1e6f32..1e6,
+            angle in -10f32..10.0,
+            scale in 0.01f32..100.0,
+        ) {
+            let transform = Transform::from_xyz(x, -x, 0.5)
+                .with_rotation(Quat::from_rotation_y(angle))
+                .with_scale(Vec3::splat(scale));
+
+            prop_assert_eq!(deserialize_transform(&serialize_to_vec(&transform)[..]), Ok(transform));
+        }
     }
 }
@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+use crate::storage;
+use crate::timestamp::{format_timestamp, parse_timestamp};
+use crate::{Field, WhereWasIParseError, WhereWasISaves};
+
+/// The name of the file with the [`SessionMetadata`] in the save directory
+pub(crate) const SESSION_FILE: &str = "session.meta";
+
+/// Information about the last time the application saved its state
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionMetadata {
+    /// When the savefiles were written
+    pub saved_at: SystemTime,
+    /// How many entities were saved
+    pub entities: usize,
+    /// The version of the application that wrote the savefiles, see
+    /// [`WhereWasIPlugin::app_version`](crate::WhereWasIPlugin::app_version)
+    pub app_version: Option<String>,
+    /// What caused the save, or `None` for session files written by older versions
+    pub trigger: Option<SaveTrigger>,
+    /// What caused the last save of every savefile, keyed by name, see
+    /// [`SaveRecord::trigger`](crate::SaveRecord::trigger)
+    pub triggers: BTreeMap<String, SaveTrigger>,
+    /// When every savefile was last written, keyed by name, see
+    /// [`SaveRecord::modified`](crate::SaveRecord::modified)
+    pub saved: BTreeMap<String, SystemTime>,
+    /// The [`WhereWasIContentHash`](crate::WhereWasIContentHash) when the savefiles were written,
+    /// if there was one
+    pub content_hash: Option<String>,
+}
+
+/// What caused a save, see [`SaveWhereWasI`](crate::SaveWhereWasI)
+///
+/// The variants are ordered by importance: when several saves are requested in the same frame,
+/// they are coalesced into a single save with the most important trigger.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SaveTrigger {
+    /// A periodic save, see [`WhereWasIPlugin::autosave`](crate::WhereWasIPlugin::autosave)
+    Autosave,
+    /// An entity changed, see [`SavePolicy::OnChange`](crate::SavePolicy::OnChange)
+    Changed,
+    /// A save requested by the application
+    #[default]
+    Manual,
+    /// A window was closed
+    WindowClosed,
+    /// The page was hidden or unloaded on the web, see the `web` feature
+    PageHidden,
+    /// The application is about to be suspended, for example because it was sent to the
+    /// background on a mobile platform
+    Suspended,
+    /// The application exited, see [`AppExit`]
+    Exit,
+}
+
+impl SaveTrigger {
+    /// The name used in the session file
+    fn name(self) -> &'static str {
+        match self {
+            Self::Autosave => "autosave",
+            Self::Changed => "changed",
+            Self::Manual => "manual",
+            Self::WindowClosed => "window_closed",
+            Self::PageHidden => "page_hidden",
+            Self::Suspended => "suspended",
+            Self::Exit => "exit",
+        }
+    }
+
+    /// The trigger with `name` in the session file
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Autosave,
+            Self::Changed,
+            Self::Manual,
+            Self::WindowClosed,
+            Self::PageHidden,
+            Self::Suspended,
+            Self::Exit,
+        ]
+        .into_iter()
+        .find(|trigger| trigger.name() == name)
+    }
+}
+
+impl SessionMetadata {
+    /// How long ago the savefiles were written
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.saved_at)
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+/// A [`Resource`] with the [`SessionMetadata`] of the previous session, read at startup
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasISession;
+///
+/// fn welcome(session: Res<WhereWasISession>) {
+///     if let Some(previous) = session.previous() {
+///         info!("Last session: {} hours ago", previous.age().as_secs() / 3600);
+///     }
+/// }
+/// ```
+#[derive(Resource, Default, Debug)]
+pub struct WhereWasISession {
+    pub(crate) previous: Option<SessionMetadata>,
+}
+
+impl WhereWasISession {
+    /// The metadata written by the previous session, or `None` if it never saved
+    pub fn previous(&self) -> Option<&SessionMetadata> {
+        self.previous.as_ref()
+    }
+}
+
+/// A [`Resource`] that tells whether this is the first run of the application, as far as the
+/// plugin can tell: no savefiles and no session file existed at startup
+///
+/// It is set in [`PreStartup`], so [`Startup`] systems can show onboarding or a default layout on
+/// a fresh install, and a "welcome back" flow otherwise. A [`WhereWasIStarted`] message is written
+/// at the same time.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasIFirstRun;
+///
+/// fn onboarding(first_run: Res<WhereWasIFirstRun>) {
+///     if first_run.0 {
+///         info!("Welcome!");
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WhereWasIFirstRun(pub bool);
+
+/// A [`Message`] that is written once the savefiles were read at startup
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WhereWasIStarted {
+    /// Whether this is the first run, see [`WhereWasIFirstRun`]
+    pub first_run: bool,
+}
+
+/// Set [`WhereWasIFirstRun`] from what was read at startup
+pub(crate) fn detect_first_run(
+    mut first_run: ResMut<WhereWasIFirstRun>,
+    mut started: MessageWriter<WhereWasIStarted>,
+    saves: Res<WhereWasISaves>,
+    session: Res<WhereWasISession>,
+) {
+    first_run.0 = saves.is_empty() && session.previous().is_none();
+    if first_run.0 {
+        info!("No savefiles found, this is the first run");
+    }
+    started.write(WhereWasIStarted {
+        first_run: first_run.0,
+    });
+}
+
+/// Write `metadata` to the session file in `directory`
+pub(crate) fn write_session(directory: &Path, metadata: &SessionMetadata) -> io::Result<()> {
+    let mut writer = Vec::new();
+
+    writer.write_all(b"v0\n\n")?;
+    writer.write_all(b"saved_at:\n")?;
+    writer.write_all(format_timestamp(metadata.saved_at).as_bytes())?;
+    writer.write_all(b"\n\nentities:\n")?;
+    writer.write_all(metadata.entities.to_string().as_bytes())?;
+    writer.write_all(b"\n\napp_version:\n")?;
+    writer.write_all(metadata.app_version.as_deref().unwrap_or("").as_bytes())?;
+    if let Some(trigger) = metadata.trigger {
+        writer.write_all(b"\n\ntrigger:\n")?;
+        writer.write_all(trigger.name().as_bytes())?;
+    }
+    if let Some(content_hash) = &metadata.content_hash {
+        writer.write_all(b"\n\ncontent_hash:\n")?;
+        writer.write_all(content_hash.as_bytes())?;
+    }
+    if !metadata.triggers.is_empty() {
+        writer.write_all(b"\n\ntriggers:")?;
+        for (name, trigger) in &metadata.triggers {
+            writer.write_all(b"\n")?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(b" ")?;
+            writer.write_all(trigger.name().as_bytes())?;
+        }
+    }
+    if !metadata.saved.is_empty() {
+        writer.write_all(b"\n\nsaved:")?;
+        for (name, saved_at) in &metadata.saved {
+            writer.write_all(b"\n")?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(b" ")?;
+            writer.write_all(format_timestamp(*saved_at).as_bytes())?;
+        }
+    }
+    writer.write_all(b"\n")?;
+
+    storage::write(&directory.join(SESSION_FILE), &writer)
+}
+
+/// Read the session file in `directory`
+///
+/// Returns `Ok(None)` if there is no session file.
+pub(crate) fn read_session(
+    directory: &Path,
+) -> Result<Option<SessionMetadata>, WhereWasIParseError> {
+    let contents = match storage::read_to_string(&directory.join(SESSION_FILE)) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let lines = contents.lines().collect::<Vec<_>>();
+    // The line number and contents of the value after `key`
+    let value = |key: &str| {
+        let index = lines.iter().position(|line| *line == key)?;
+        Some((index + 2, *lines.get(index + 1)?))
+    };
+    let missing = |expected| WhereWasIParseError::MissingLine {
+        line: lines.len() + 1,
+        expected,
+    };
+
+    if lines.first() != Some(&"v0") {
+        return Err(WhereWasIParseError::UnsupportedVersion {
+            line: 1,
+            found: lines.first().unwrap_or(&"").to_string(),
+        });
+    }
+
+    let (line, saved_at) = value("saved_at:").ok_or_else(|| missing(Field::SavedAt))?;
+    let saved_at =
+        parse_timestamp(saved_at).ok_or_else(|| WhereWasIParseError::UnexpectedLine {
+            line,
+            expected: Field::SavedAt,
+            found: saved_at.into(),
+        })?;
+    let (line, entities) = value("entities:").ok_or_else(|| missing(Field::Entities))?;
+    let entities = entities
+        .parse::<usize>()
+        .map_err(|_| WhereWasIParseError::UnexpectedLine {
+            line,
+            expected: Field::Entities,
+            found: entities.into(),
+        })?;
+    let app_version = value("app_version:")
+        .map(|(_, version)| version)
+        .filter(|version| !version.is_empty())
+        .map(Into::into);
+    // Unknown triggers from newer versions are ignored rather than failing the whole file
+    let trigger = value("trigger:").and_then(|(_, trigger)| SaveTrigger::from_name(trigger));
+    let content_hash = value("content_hash:").map(|(_, hash)| hash.into());
+    // One `<name> <value>` line per savefile, until the next blank line
+    let per_savefile = |key: &str| {
+        lines
+            .iter()
+            .position(|line| *line == key)
+            .map(|index| {
+                lines[index + 1..]
+                    .iter()
+                    .take_while(|line| !line.is_empty())
+                    .filter_map(|line| line.rsplit_once(' '))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    let triggers = per_savefile("triggers:")
+        .into_iter()
+        .filter_map(|(name, trigger)| Some((name.to_string(), SaveTrigger::from_name(trigger)?)))
+        .collect();
+    let saved = per_savefile("saved:")
+        .into_iter()
+        .filter_map(|(name, saved_at)| Some((name.to_string(), parse_timestamp(saved_at)?)))
+        .collect();
+
+    Ok(Some(SessionMetadata {
+        saved_at,
+        entities,
+        app_version,
+        trigger,
+        triggers,
+        saved,
+        content_hash,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let directory = Path::new("assets/tests/session");
+        fs::create_dir_all(directory).unwrap();
+
+        let metadata = SessionMetadata {
+            saved_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_760_000_000),
+            entities: 3,
+            app_version: Some("1.2.3".into()),
+            trigger: Some(SaveTrigger::WindowClosed),
+            triggers: BTreeMap::from([
+                ("camera".into(), SaveTrigger::WindowClosed),
+                ("minimap".into(), SaveTrigger::Autosave),
+            ]),
+            saved: BTreeMap::from([(
+                "camera".into(),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1_759_999_000),
+            )]),
+            content_hash: Some("9f2c".into()),
+        };
+        write_session(directory, &metadata).unwrap();
+
+        assert_eq!(read_session(directory), Ok(Some(metadata)));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_session_without_trigger() {
+        let directory = Path::new("assets/tests/session_without_trigger");
+        fs::create_dir_all(directory).unwrap();
+        fs::write(
+            directory.join(SESSION_FILE),
+            "v0\n\nsaved_at:\n20251009T085320Z\n\nentities:\n1\n\napp_version:\n\n",
+        )
+        .unwrap();
+
+        let metadata = read_session(directory).unwrap().unwrap();
+        assert_eq!(metadata.trigger, None);
+        assert!(metadata.triggers.is_empty());
+        assert!(metadata.saved.is_empty());
+        assert_eq!(metadata.content_hash, None);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_detect_first_run() {
+        let first_run = |directory: &str| {
+            let mut app = crate::tests::test_app(crate::tests::test_config(directory));
+            app.init_resource::<WhereWasIFirstRun>()
+                .add_message::<WhereWasIStarted>()
+                .add_systems(Update, (crate::read_saves, detect_first_run).chain());
+            app.update();
+
+            let started = app.world().resource::<Messages<WhereWasIStarted>>();
+            assert_eq!(started.len(), 1);
+            app.world().resource::<WhereWasIFirstRun>().0
+        };
+
+        assert!(first_run("assets/tests/does_not_exist"));
+        assert!(!first_run("assets/tests"));
+    }
+
+    #[test]
+    fn test_missing_session() {
+        assert_eq!(
+            read_session(Path::new("assets/tests/does_not_exist")),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+
+use crate::names::resolve_tracked_names;
+use crate::validation::read_checked;
+use crate::{WhereWasI, WhereWasIConfig, WhereWasIHistory, state_path};
+
+/// A [`Message`] that restores the [`Transform`] of an entity from an older snapshot
+///
+/// Snapshots are only kept if [`WhereWasIPlugin::history`](crate::WhereWasIPlugin::history) is
+/// larger than zero. Snapshot `1` is the state that was saved before the current savefile, usually
+/// the previous session. The savefiles on disk are left untouched.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::{RestoreWhereWasISnapshot, WhereWasI};
+///
+/// fn previous_view(
+///     mut restores: MessageWriter<RestoreWhereWasISnapshot>,
+///     camera: Single<Entity, With<WhereWasI>>,
+/// ) {
+///     restores.write(RestoreWhereWasISnapshot {
+///         entity: *camera,
+///         snapshot: 1,
+///     });
+/// }
+/// ```
+#[derive(Message, Clone, Copy, Debug)]
+pub struct RestoreWhereWasISnapshot {
+    /// The entity with a [`WhereWasI`] component to restore
+    pub entity: Entity,
+    /// Which snapshot to restore, `1` being the most recent one
+    pub snapshot: usize,
+}
+
+/// The path of snapshot `index` of the savefile for `name` in `directory`
+///
+/// Index `0` is the savefile itself.
+pub(crate) fn snapshot_path(directory: &Path, name: &str, index: usize) -> io::Result<PathBuf> {
+    let path = state_path(directory, name)?;
+    Ok(match index {
+        0 => path,
+        index => path.with_extension(format!("state.{index}")),
+    })
+}
+
+/// Shift the savefile for `name` and its snapshots one place back, keeping at most `history`
+/// snapshots
+///
+/// Afterwards, the savefile itself no longer exists and can be written again.
+pub(crate) fn rotate_snapshots(directory: &Path, name: &str, history: usize) -> io::Result<()> {
+    if history == 0 {
+        return Ok(());
+    }
+
+    for index in (0..history).rev() {
+        let from = snapshot_path(directory, name, index)?;
+        if fs::exists(&from)? {
+            fs::rename(from, snapshot_path(directory, name, index + 1)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles [`RestoreWhereWasISnapshot`] messages
+pub(crate) fn restore_snapshot(
+    mut restores: MessageReader<RestoreWhereWasISnapshot>,
+    mut to_restore: Query<(Entity, &WhereWasI, &mut Transform, &mut WhereWasIHistory)>,
+    config: Res<WhereWasIConfig>,
+) {
+    if restores.is_empty() {
+        return;
+    }
+
+    let names: HashMap<Entity, String> = resolve_tracked_names(
+        to_restore
+            .iter()
+            .map(|(entity, where_was_i, ..)| (entity, where_was_i)),
+        config.name_collisions,
+    )
+    .into_iter()
+    .collect();
+
+    for restore in restores.read() {
+        let Ok((_, where_was_i, mut transform, mut history)) = to_restore.get_mut(restore.entity)
+        else {
+            warn!("Cannot restore entity {} without WhereWasI", restore.entity);
+            continue;
+        };
+        let Some(name) = names.get(&restore.entity) else {
+            continue;
+        };
+
+        let snapshot = snapshot_path(&config.directory, name, restore.snapshot)
+            .map_err(Into::into)
+            .and_then(|path| read_checked(&path, &config));
+
+        match snapshot {
+            Ok(restored) => {
+                history.record(*transform);
+                *transform = where_was_i.clamp(restored);
+            }
+            Err(err) => error!(
+                "Could not restore snapshot {} of {}: {}",
+                restore.snapshot, name, err
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_app, test_config};
+    use crate::{read_state, write_state};
+
+    #[test]
+    fn test_rotate_snapshots() {
+        let directory = Path::new("assets/tests/snapshots");
+        let config = WhereWasIConfig {
+            history: 2,
+            ..test_config("assets/tests/snapshots")
+        };
+
+        for x in 0..4 {
+            write_state(
+                &config,
+                "rotate_test",
+                &Transform::from_xyz(x as f32, 0.0, 0.0),
+            )
+            .unwrap();
+        }
+
+        let snapshots = (0..3)
+            .map(|index| {
+                read_state(snapshot_path(directory, "rotate_test", index).unwrap())
+                    .unwrap()
+                    .translation
+                    .x
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(snapshots, vec![3.0, 2.0, 1.0]);
+        assert!(!fs::exists(snapshot_path(directory, "rotate_test", 3).unwrap()).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_restore_snapshot() {
+        let config = WhereWasIConfig {
+            history: 1,
+            ..test_config("assets/tests/restore_snapshot")
+        };
+        let previous = Transform::from_xyz(1.0, 2.0, 3.0);
+        write_state(&config, "camera", &previous).unwrap();
+        write_state(&config, "camera", &Transform::IDENTITY).unwrap();
+
+        let mut app = test_app(config);
+        let entity = app.world_mut().spawn(WhereWasI::camera()).id();
+        app.add_systems(Update, restore_snapshot);
+
+        app.world_mut()
+            .resource_mut::<Messages<RestoreWhereWasISnapshot>>()
+            .write(RestoreWhereWasISnapshot {
+                entity,
+                snapshot: 1,
+            });
+        app.update();
+
+        assert_eq!(*app.world().get::<Transform>(entity).unwrap(), previous);
+
+        fs::remove_dir_all("assets/tests/restore_snapshot").unwrap();
+    }
+}
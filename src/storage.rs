@@ -0,0 +1,179 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str;
+use std::time::SystemTime;
+
+#[cfg(all(feature = "web", target_arch = "wasm32", target_os = "unknown"))]
+use crate::web::local_storage;
+
+/// The prefix of save directories that are kept in the browser's `localStorage`, see
+/// [`PlatformDirectories::wasm`](crate::PlatformDirectories::wasm)
+pub(crate) const LOCAL_STORAGE: &str = "localStorage:";
+
+/// A file in a directory, as listed by [`read_dir`]
+pub(crate) struct DirEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) modified: Option<SystemTime>,
+}
+
+/// The `localStorage` key of `path`, or `None` if `path` is on the filesystem
+fn local_storage_key(path: &Path) -> Option<&str> {
+    path.to_str()?.strip_prefix(LOCAL_STORAGE)
+}
+
+/// Whether `directory` is kept in `localStorage` instead of on the filesystem
+pub(crate) fn is_local_storage(directory: &Path) -> bool {
+    local_storage_key(directory).is_some()
+}
+
+/// Fails in the browser, which has no filesystem
+fn filesystem() -> io::Result<()> {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("the browser has no filesystem, use a {LOCAL_STORAGE} save directory"),
+        ));
+    }
+    Ok(())
+}
+
+/// Read the file at `path`
+pub(crate) fn read(path: &Path) -> io::Result<Vec<u8>> {
+    match local_storage_key(path) {
+        Some(key) => local_storage::get(key)?
+            .map(String::into_bytes)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound)),
+        None => filesystem().and_then(|_| fs::read(path)),
+    }
+}
+
+/// Read the file at `path` as text
+pub(crate) fn read_to_string(path: &Path) -> io::Result<String> {
+    String::from_utf8(read(path)?).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Write `contents` to the file at `path`, replacing it if it exists
+///
+/// `localStorage` only stores text, so writing anything else there fails with
+/// [`io::ErrorKind::InvalidData`].
+pub(crate) fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    match local_storage_key(path) {
+        Some(key) => local_storage::set(
+            key,
+            str::from_utf8(contents)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+        ),
+        None => filesystem().and_then(|_| fs::write(path, contents)),
+    }
+}
+
+/// Remove the file at `path`
+///
+/// Fails with [`io::ErrorKind::NotFound`] if there is no file at `path`.
+pub(crate) fn remove_file(path: &Path) -> io::Result<()> {
+    match local_storage_key(path) {
+        Some(key) => match local_storage::get(key)? {
+            Some(_) => local_storage::remove(key),
+            None => Err(io::ErrorKind::NotFound.into()),
+        },
+        None => filesystem().and_then(|_| fs::remove_file(path)),
+    }
+}
+
+/// Create `directory` and its parents if they don't exist
+///
+/// `localStorage` has no directories, so this only checks that it can be used.
+pub(crate) fn create_dir_all(directory: &Path) -> io::Result<()> {
+    match local_storage_key(directory) {
+        Some(_) => local_storage::keys().map(|_| ()),
+        None => filesystem().and_then(|_| fs::create_dir_all(directory)),
+    }
+}
+
+/// The files in `directory`, without its subdirectories
+pub(crate) fn read_dir(directory: &Path) -> io::Result<Vec<DirEntry>> {
+    let Some(prefix) = local_storage_key(directory) else {
+        filesystem()?;
+        return Ok(fs::read_dir(directory)?
+            .flatten()
+            .filter(|entry| !entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .map(|entry| DirEntry {
+                path: entry.path(),
+                modified: entry.metadata().and_then(|meta| meta.modified()).ok(),
+            })
+            .collect());
+    };
+
+    let prefix = format!("{}/", prefix.trim_end_matches('/'));
+    Ok(local_storage::keys()?
+        .iter()
+        .filter_map(|key| key.strip_prefix(&prefix))
+        .filter(|file| !file.contains('/'))
+        .map(|file| DirEntry {
+            path: directory.join(file),
+            modified: None,
+        })
+        .collect())
+}
+
+/// `localStorage` outside of the browser, or without the `web` feature, where it can't be used
+#[cfg(not(all(feature = "web", target_arch = "wasm32", target_os = "unknown")))]
+mod local_storage {
+    use std::io;
+
+    use super::LOCAL_STORAGE;
+
+    fn unavailable<T>() -> io::Result<T> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{LOCAL_STORAGE} save directories need the `web` feature in the browser"),
+        ))
+    }
+
+    pub(super) fn get(_key: &str) -> io::Result<Option<String>> {
+        unavailable()
+    }
+
+    pub(super) fn set(_key: &str, _value: &str) -> io::Result<()> {
+        unavailable()
+    }
+
+    pub(super) fn remove(_key: &str) -> io::Result<()> {
+        unavailable()
+    }
+
+    pub(super) fn keys() -> io::Result<Vec<String>> {
+        unavailable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_storage_key() {
+        assert_eq!(
+            local_storage_key(Path::new("localStorage:my_app/camera.state")),
+            Some("my_app/camera.state")
+        );
+        assert_eq!(local_storage_key(Path::new("assets/saves")), None);
+    }
+
+    #[test]
+    fn test_local_storage_unavailable() {
+        let path = Path::new("localStorage:my_app/camera.state");
+
+        assert_eq!(
+            write(path, b"v0").unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+        assert_eq!(
+            read_dir(Path::new("localStorage:my_app"))
+                .err()
+                .map(|err| err.kind()),
+            Some(io::ErrorKind::Unsupported)
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::time::SystemTime;
+
+use crate::prelude::*;
+use bevy_ecs::system::SystemParam;
+
+use crate::history::apply_restore;
+use crate::names::resolve_tracked_names;
+use crate::storage;
+use crate::validation::read_checked;
+use crate::{
+    SaveRecord, SaveTrigger, WhereWasI, WhereWasIAuthored, WhereWasIConfig, WhereWasIParseError,
+    WhereWasISaves, state_path, write_state,
+};
+
+/// The error returned by [`WhereWasIStore::reload_entity`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum WhereWasIReloadError {
+    /// The entity has no [`WhereWasI`] component
+    NotTracked {
+        /// The entity that isn't tracked
+        entity: Entity,
+    },
+    /// The savefile of the entity couldn't be read
+    Parse(WhereWasIParseError),
+}
+
+impl fmt::Display for WhereWasIReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotTracked { entity } => write!(f, "{entity} is not tracked"),
+            Self::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for WhereWasIReloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::NotTracked { .. } => None,
+            Self::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<WhereWasIParseError> for WhereWasIReloadError {
+    fn from(value: WhereWasIParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// A [`SystemParam`] to manage savefiles from any system without crafting file paths by hand
+///
+/// Reads are served from [`WhereWasISaves`], writes go to disk and update [`WhereWasISaves`].
+///
+/// Note that this reads the [`Transform`] of all entities with a [`WhereWasI`] component, so it
+/// can't be combined with a query for `&mut Transform` on those entities in the same system.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasIStore;
+///
+/// fn forget_camera(mut store: WhereWasIStore) {
+///     if let Err(err) = store.delete("camera") {
+///         error!("Could not delete camera savefile: {err}");
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct WhereWasIStore<'w, 's> {
+    pub(crate) config: Res<'w, WhereWasIConfig>,
+    pub(crate) saves: ResMut<'w, WhereWasISaves>,
+    pub(crate) tracked: Query<'w, 's, (Entity, &'static WhereWasI, &'static Transform)>,
+    pub(crate) authored: Query<'w, 's, &'static WhereWasIAuthored>,
+    pub(crate) commands: Commands<'w, 's>,
+}
+
+impl WhereWasIStore<'_, '_> {
+    /// The record saved under `name`, if any
+    pub fn get(&self, name: &str) -> Option<&SaveRecord> {
+        self.saves.get(name)
+    }
+
+    /// Save `transform` under `name`, overwriting an existing savefile
+    pub fn set(&mut self, name: &str, transform: Transform) -> io::Result<()> {
+        write_state(&self.config, name, &transform)?;
+
+        self.saves.insert(
+            name,
+            SaveRecord {
+                transform,
+                modified: Some(SystemTime::now()),
+                trigger: Some(SaveTrigger::Manual),
+            },
+        );
+        Ok(())
+    }
+
+    /// Delete the savefile for `name`
+    ///
+    /// Returns whether a savefile existed.
+    pub fn delete(&mut self, name: &str) -> io::Result<bool> {
+        self.saves.remove(name);
+
+        match storage::remove_file(&state_path(&self.config.directory, name)?) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The names of all savefiles, sorted
+    pub fn list(&self) -> impl Iterator<Item = &str> {
+        self.saves.iter().map(|(name, _)| name)
+    }
+
+    /// The name every tracked entity is saved under, according to the
+    /// [`NameCollisionPolicy`](crate::NameCollisionPolicy)
+    pub(crate) fn tracked_names(&self) -> Vec<(Entity, String)> {
+        resolve_tracked_names(
+            self.tracked
+                .iter()
+                .map(|(entity, where_was_i, _)| (entity, where_was_i)),
+            self.config.name_collisions,
+        )
+    }
+
+    /// The name `entity` is saved under, if it is tracked
+    fn tracked_name(&self, entity: Entity) -> Option<String> {
+        self.tracked_names()
+            .into_iter()
+            .find_map(|(tracked, name)| (tracked == entity).then_some(name))
+    }
+
+    /// Whether the current [`Transform`] of a tracked `entity` differs from the one that was last
+    /// saved or loaded
+    ///
+    /// Unlike [`TransformDiff::is_dirty`](crate::TransformDiff::is_dirty), this compares the
+    /// transforms exactly. It is what decides whether a savefile is written with
+    /// [`WhereWasIPlugin::skip_unchanged`](crate::WhereWasIPlugin::skip_unchanged). Entities that
+    /// aren't tracked never need to be saved.
+    pub fn needs_save(&self, entity: Entity) -> bool {
+        let Some(name) = self.tracked_name(entity) else {
+            return false;
+        };
+        let Ok((_, _, transform)) = self.tracked.get(entity) else {
+            return false;
+        };
+
+        self.saves
+            .get(&name)
+            .is_none_or(|record| record.transform != *transform)
+    }
+
+    /// Save the current [`Transform`] of a tracked `entity`, overwriting its savefile
+    ///
+    /// Unlike [`set`](Self::set), this writes the savefile in the directory and format of the
+    /// [`WhereWasIGroup`](crate::WhereWasIGroup) of the entity.
+    pub fn save_entity(&mut self, entity: Entity) -> io::Result<()> {
+        let name = self.tracked_name(entity).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{entity} is not tracked"))
+        })?;
+        let (_, where_was_i, transform) = self.tracked.get(entity).map_err(io::Error::other)?;
+        write_state(
+            self.config.group(where_was_i.group.as_deref()),
+            &name,
+            transform,
+        )?;
+
+        self.saves.insert(
+            &name,
+            SaveRecord {
+                transform: *transform,
+                modified: Some(SystemTime::now()),
+                trigger: Some(SaveTrigger::Manual),
+            },
+        );
+        Ok(())
+    }
+
+    /// Read the savefile of a tracked `entity` from disk again and restore it
+    ///
+    /// This picks up changes made to the savefile since startup. The transform is applied when the
+    /// commands of the current system are applied.
+    pub fn reload_entity(&mut self, entity: Entity) -> Result<(), WhereWasIReloadError> {
+        let name = self
+            .tracked_name(entity)
+            .ok_or(WhereWasIReloadError::NotTracked { entity })?;
+        let config = match self.tracked.get(entity) {
+            Ok((_, where_was_i, _)) => self.config.group(where_was_i.group.as_deref()),
+            Err(_) => &self.config,
+        };
+        let path = state_path(&config.directory, &name).map_err(WhereWasIParseError::from)?;
+        let transform = read_checked(&path, config)?;
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        self.saves.insert(
+            &name,
+            SaveRecord {
+                transform,
+                modified,
+                trigger: None,
+            },
+        );
+        self.commands
+            .entity(entity)
+            .queue(move |entity: EntityWorldMut| apply_restore(entity, transform));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::tests::test_config;
+
+    #[test]
+    fn test_set_get_delete() {
+        let mut world = World::new();
+        world.insert_resource(test_config("assets/tests/store"));
+        world.init_resource::<WhereWasISaves>();
+
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0);
+
+        world
+            .run_system_once(move |mut store: WhereWasIStore| {
+                store.set("store_test", transform).unwrap();
+
+                assert_eq!(
+                    store.get("store_test").map(|record| record.transform),
+                    Some(transform)
+                );
+                assert_eq!(store.list().collect::<Vec<_>>(), vec!["store_test"]);
+
+                assert!(store.delete("store_test").unwrap());
+                assert!(!store.delete("store_test").unwrap());
+                assert_eq!(store.get("store_test"), None);
+            })
+            .unwrap();
+
+        assert!(!fs::exists("assets/tests/store/store_test.state").unwrap());
+    }
+
+    #[test]
+    fn test_save_reload_entity() {
+        let mut world = World::new();
+        world.insert_resource(test_config("assets/tests/store_entity"));
+        world.init_resource::<WhereWasISaves>();
+
+        let saved = Transform::from_xyz(1.0, 2.0, 3.0);
+        let entity = world.spawn((WhereWasI::from_name("camera"), saved)).id();
+        let untracked = world.spawn(Transform::IDENTITY).id();
+
+        world
+            .run_system_once(move |mut store: WhereWasIStore| {
+                assert!(store.needs_save(entity));
+                store.save_entity(entity).unwrap();
+                assert!(!store.needs_save(entity));
+                assert!(store.save_entity(untracked).is_err());
+                assert!(!store.needs_save(untracked));
+            })
+            .unwrap();
+
+        world.entity_mut(entity).insert(Transform::IDENTITY);
+        assert_eq!(
+            world
+                .run_system_once(move |mut store: WhereWasIStore| store.reload_entity(untracked))
+                .unwrap(),
+            Err(WhereWasIReloadError::NotTracked { entity: untracked })
+        );
+        world
+            .run_system_once(move |mut store: WhereWasIStore| store.reload_entity(entity))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(*world.entity(entity).get::<Transform>().unwrap(), saved);
+
+        fs::remove_dir_all("assets/tests/store_entity").unwrap();
+    }
+}
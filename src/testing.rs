@@ -0,0 +1,132 @@
+//! Helpers to write persistence regression tests for applications that use [`WhereWasIPlugin`]
+//!
+//! Requires the `testing` feature, usually enabled in `[dev-dependencies]`:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! bevy-where-was-i = { version = "*", features = ["testing"] }
+//! ```
+//!
+//! ```rust,ignore
+//! use bevy::prelude::*;
+//! use bevy_where_was_i::testing::assert_round_trip;
+//! use bevy_where_was_i::{WhereWasI, WhereWasIPlugin};
+//!
+//! #[test]
+//! fn camera_is_persisted() {
+//!     assert_round_trip(WhereWasIPlugin::from_directory("target/wwi-tests/camera"), |world| {
+//!         world.spawn((WhereWasI::camera(), Transform::from_xyz(1.0, 2.0, 3.0)));
+//!     });
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::prelude::*;
+use bevy_window::WindowClosing;
+
+use crate::{WhereWasI, WhereWasIPlugin};
+
+/// The [`Transform`] that tracked entities get before they are restored by
+/// [`assert_round_trip`], so a missing restore can't go unnoticed
+const UNRESTORED: Transform = Transform::from_translation(Vec3::splat(f32::MAX));
+
+/// A minimal [`App`] with `plugin` added, without a window or renderer
+///
+/// The first [`App::update`] reads the savefiles and restores tracked entities, like starting the
+/// application would.
+pub fn app(plugin: WhereWasIPlugin) -> App {
+    let mut app = App::new();
+    app.add_message::<WindowClosing>().add_plugins(plugin);
+    app
+}
+
+/// Send a [`WindowClosing`] message and run an update, which saves all tracked entities
+pub fn close_window(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<Messages<WindowClosing>>()
+        .write(WindowClosing {
+            window: Entity::PLACEHOLDER,
+        });
+    app.update();
+}
+
+/// The current [`Transform`] of every entity with a [`WhereWasI`] component, by name
+pub fn tracked_transforms(world: &mut World) -> BTreeMap<String, Transform> {
+    world
+        .query::<(&WhereWasI, &Transform)>()
+        .iter(world)
+        .map(|(where_was_i, transform)| (where_was_i.name.clone(), *transform))
+        .collect()
+}
+
+/// Assert that the entities spawned by `setup` are restored to the same [`Transform`] after a
+/// save and a restart
+///
+/// `setup` is run in two fresh [`App`]s built with [`app`]. The first one is started and closed,
+/// which saves the tracked entities. In the second one, the tracked entities are moved out of the
+/// way before startup, and are expected to be restored to exactly the saved [`Transform`].
+///
+/// The directory of `plugin` should be dedicated to the test, savefiles in it are overwritten.
+pub fn assert_round_trip(plugin: WhereWasIPlugin, setup: impl Fn(&mut World)) {
+    let mut first = app(plugin.clone());
+    setup(first.world_mut());
+    first.update();
+    let saved = tracked_transforms(first.world_mut());
+    close_window(&mut first);
+
+    let mut second = app(plugin);
+    setup(second.world_mut());
+    let world = second.world_mut();
+    for mut transform in world
+        .query_filtered::<&mut Transform, With<WhereWasI>>()
+        .iter_mut(world)
+    {
+        *transform = UNRESTORED;
+    }
+    second.update();
+    let restored = tracked_transforms(second.world_mut());
+
+    assert_eq!(
+        restored, saved,
+        "Tracked entities were not restored to their saved transforms"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_assert_round_trip() {
+        assert_round_trip(
+            WhereWasIPlugin::from_directory("assets/tests/round_trip"),
+            |world| {
+                world.spawn((
+                    WhereWasI::camera(),
+                    Transform::from_xyz(1.0, 2.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+                ));
+                world.spawn((
+                    WhereWasI::from_name("light"),
+                    Transform::from_scale(Vec3::splat(0.1)),
+                ));
+            },
+        );
+
+        fs::remove_dir_all("assets/tests/round_trip").unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Tracked entities were not restored")]
+    fn test_assert_round_trip_detects_unsaved() {
+        // An invalid name can't be saved, so nothing is restored
+        assert_round_trip(
+            WhereWasIPlugin::from_directory("assets/tests/round_trip_unsaved"),
+            |world| {
+                world.spawn(WhereWasI::from_name("../camera"));
+            },
+        );
+    }
+}
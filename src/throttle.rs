@@ -0,0 +1,285 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use crate::prelude::*;
+use bevy_platform::time::Instant;
+
+use crate::SaveTrigger;
+
+/// Limits on how much is written to disk, see
+/// [`WhereWasIPlugin::rate_limit`](crate::WhereWasIPlugin::rate_limit)
+///
+/// Saves that would exceed a limit are postponed until they fit, requests in the meantime are
+/// coalesced so only the latest [`Transform`]s are written. The save when a window closes, the page
+/// is hidden or the application is suspended or exits is never postponed.
+///
+/// ```rust
+/// use bevy_where_was_i::SaveRateLimit;
+///
+/// SaveRateLimit {
+///     max_writes_per_second: Some(20),
+///     max_bytes_per_minute: Some(1024 * 1024),
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SaveRateLimit {
+    /// The maximum number of savefiles written in any second
+    pub max_writes_per_second: Option<usize>,
+    /// The maximum number of bytes written in any minute
+    pub max_bytes_per_minute: Option<u64>,
+}
+
+/// A save that was written, as far as the rate limit is concerned
+struct PastWrite {
+    at: Instant,
+    files: usize,
+    bytes: u64,
+}
+
+/// A save that was requested in a frame, or several that were coalesced
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RequestedSave {
+    /// The most important trigger of the requests
+    pub(crate) trigger: SaveTrigger,
+    /// The windows to save the entities of, or `None` to save all entities
+    pub(crate) windows: Option<HashSet<Entity>>,
+}
+
+impl RequestedSave {
+    /// Coalesce with `other`, keeping the most important trigger and the entities of both
+    fn merge(self, other: Self) -> Self {
+        Self {
+            trigger: self.trigger.max(other.trigger),
+            windows: self
+                .windows
+                .zip(other.windows)
+                .map(|(windows, other)| windows.union(&other).copied().collect()),
+        }
+    }
+}
+
+/// A [`Message`] with the save that `save_state` admitted this frame
+///
+/// The systems that save next to the savefiles, such as those of persisted types, follow it
+/// instead of the [`SaveWhereWasI`](crate::SaveWhereWasI) requests, so they are held back by the
+/// [`SaveRateLimit`] as well.
+#[derive(Message, Clone, Debug)]
+pub(crate) struct SaveAdmitted(pub(crate) RequestedSave);
+
+/// The saves of the last minute and a save that was postponed by the [`SaveRateLimit`]
+#[derive(Resource, Default)]
+pub(crate) struct SaveThrottle {
+    writes: VecDeque<PastWrite>,
+    pending: Option<RequestedSave>,
+}
+
+impl SaveThrottle {
+    /// Record that `files` savefiles with a total of `bytes` were written at `at`
+    pub(crate) fn record(&mut self, at: Instant, files: usize, bytes: u64) {
+        self.writes.push_back(PastWrite { at, files, bytes });
+    }
+
+    /// Whether a save at `now` stays within `limit`
+    pub(crate) fn allows(&mut self, limit: &SaveRateLimit, now: Instant) -> bool {
+        let within = |write: &PastWrite, period| now.saturating_duration_since(write.at) < period;
+        let minute = Duration::from_secs(60);
+        while self
+            .writes
+            .front()
+            .is_some_and(|write| !within(write, minute))
+        {
+            self.writes.pop_front();
+        }
+
+        let files = self
+            .writes
+            .iter()
+            .filter(|write| within(write, Duration::from_secs(1)))
+            .map(|write| write.files)
+            .sum::<usize>();
+        let bytes = self.writes.iter().map(|write| write.bytes).sum::<u64>();
+
+        limit.max_writes_per_second.is_none_or(|max| files < max)
+            && limit.max_bytes_per_minute.is_none_or(|max| bytes < max)
+    }
+
+    /// The save to start at `now`, if any
+    ///
+    /// Combines `requested` with a save that was postponed earlier. If `limit` doesn't allow a
+    /// save right now, it is postponed and `None` is returned.
+    pub(crate) fn admit(
+        &mut self,
+        requested: Option<RequestedSave>,
+        limit: Option<&SaveRateLimit>,
+        now: Instant,
+    ) -> Option<RequestedSave> {
+        let save = match (requested, self.pending.take()) {
+            (Some(requested), Some(pending)) => requested.merge(pending),
+            (requested, pending) => requested.or(pending)?,
+        };
+
+        if !matches!(
+            save.trigger,
+            SaveTrigger::WindowClosed
+                | SaveTrigger::PageHidden
+                | SaveTrigger::Suspended
+                | SaveTrigger::Exit
+        ) && let Some(limit) = limit
+            && !self.allows(limit, now)
+        {
+            debug!("Postponing save, the rate limit was reached");
+            self.pending = Some(save);
+            return None;
+        }
+        Some(save)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A save of all entities
+    fn all(trigger: SaveTrigger) -> Option<RequestedSave> {
+        Some(RequestedSave {
+            trigger,
+            windows: None,
+        })
+    }
+
+    /// A save of the entities of `windows`
+    fn of_windows(trigger: SaveTrigger, windows: &[Entity]) -> Option<RequestedSave> {
+        Some(RequestedSave {
+            trigger,
+            windows: Some(windows.iter().copied().collect()),
+        })
+    }
+
+    #[test]
+    fn test_max_writes_per_second() {
+        let limit = SaveRateLimit {
+            max_writes_per_second: Some(5),
+            ..default()
+        };
+        let mut throttle = SaveThrottle::default();
+        let start = Instant::now();
+
+        assert_eq!(
+            throttle.admit(all(SaveTrigger::Manual), Some(&limit), start),
+            all(SaveTrigger::Manual)
+        );
+        throttle.record(start, 5, 100);
+
+        assert_eq!(
+            throttle.admit(all(SaveTrigger::Autosave), Some(&limit), start),
+            None
+        );
+        assert_eq!(
+            throttle.admit(None, Some(&limit), start + Duration::from_millis(500)),
+            None
+        );
+        assert_eq!(
+            throttle.admit(
+                all(SaveTrigger::WindowClosed),
+                Some(&limit),
+                start + Duration::from_millis(500)
+            ),
+            all(SaveTrigger::WindowClosed)
+        );
+
+        throttle.record(start + Duration::from_millis(500), 5, 100);
+        throttle.admit(all(SaveTrigger::Autosave), Some(&limit), start);
+        assert_eq!(
+            throttle.admit(None, Some(&limit), start + Duration::from_secs(2)),
+            all(SaveTrigger::Autosave)
+        );
+    }
+
+    #[test]
+    fn test_max_bytes_per_minute() {
+        let limit = SaveRateLimit {
+            max_bytes_per_minute: Some(1000),
+            ..default()
+        };
+        let mut throttle = SaveThrottle::default();
+        let start = Instant::now();
+        throttle.record(start, 1, 1000);
+
+        assert_eq!(
+            throttle.admit(
+                all(SaveTrigger::Manual),
+                Some(&limit),
+                start + Duration::from_secs(30)
+            ),
+            None
+        );
+        assert_eq!(
+            throttle.admit(None, Some(&limit), start + Duration::from_secs(61)),
+            all(SaveTrigger::Manual)
+        );
+        assert_eq!(
+            throttle.admit(all(SaveTrigger::Manual), None, start),
+            all(SaveTrigger::Manual)
+        );
+    }
+
+    #[test]
+    fn test_postponed_window_save() {
+        let limit = SaveRateLimit {
+            max_writes_per_second: Some(1),
+            ..default()
+        };
+        let mut throttle = SaveThrottle::default();
+        let start = Instant::now();
+        let (first, second) = (
+            Entity::from_raw_u32(1).unwrap(),
+            Entity::from_raw_u32(2).unwrap(),
+        );
+        throttle.record(start, 1, 100);
+
+        assert_eq!(
+            throttle.admit(
+                of_windows(SaveTrigger::Manual, &[first]),
+                Some(&limit),
+                start
+            ),
+            None
+        );
+        assert_eq!(
+            throttle.admit(None, Some(&limit), start + Duration::from_secs(2)),
+            of_windows(SaveTrigger::Manual, &[first])
+        );
+
+        throttle.record(start + Duration::from_secs(2), 1, 100);
+        throttle.admit(
+            of_windows(SaveTrigger::Autosave, &[first]),
+            Some(&limit),
+            start + Duration::from_secs(2),
+        );
+        throttle.admit(
+            of_windows(SaveTrigger::Manual, &[second]),
+            Some(&limit),
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(
+            throttle.admit(None, Some(&limit), start + Duration::from_secs(4)),
+            of_windows(SaveTrigger::Manual, &[first, second])
+        );
+
+        throttle.record(start + Duration::from_secs(4), 1, 100);
+        throttle.admit(
+            of_windows(SaveTrigger::Manual, &[first]),
+            Some(&limit),
+            start + Duration::from_secs(4),
+        );
+        throttle.admit(
+            all(SaveTrigger::Autosave),
+            Some(&limit),
+            start + Duration::from_secs(4),
+        );
+        assert_eq!(
+            throttle.admit(None, Some(&limit), start + Duration::from_secs(6)),
+            all(SaveTrigger::Manual)
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::prelude::*;
+
+use crate::{Field, WhereWasIConfig, WhereWasIParseError, read_state};
+
+/// How far the length of a rotation quaternion may deviate from 1 before it is reported
+///
+/// Savefiles written by this crate stay well within this, so larger deviations usually come from
+/// hand-edited savefiles.
+const ROTATION_TOLERANCE: f32 = 1e-3;
+
+/// What to do with savefiles that contain values which would break rendering or camera math, see
+/// [`WhereWasIPlugin::invalid_values`](crate::WhereWasIPlugin::invalid_values)
+///
+/// Values are invalid when they are not finite, when the rotation has (nearly) zero length or when
+/// a component of the scale is (nearly) zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidValuePolicy {
+    /// Fail to load the savefile, as if it couldn't be read
+    #[default]
+    Reject,
+    /// Replace invalid values with those of [`Transform::IDENTITY`] and log a warning
+    Sanitize,
+}
+
+/// Read the savefile at `path` and check its values according to [`WhereWasIConfig::invalid_values`]
+///
+/// Rotations that aren't normalized are normalized. A rotation whose length deviates more than [`ROTATION_TOLERANCE`]
+/// from 1 is logged, or rejected with [`WhereWasIConfig::strict_rotations`].
+pub(crate) fn read_checked(
+    path: &Path,
+    config: &WhereWasIConfig,
+) -> Result<Transform, WhereWasIParseError> {
+    check_values(read_state(path)?, path.display(), config)
+}
+
+/// Check the values of `transform`, which was read from `source`, like [`read_checked`]
+pub(crate) fn check_values(
+    mut transform: Transform,
+    source: impl fmt::Display,
+    config: &WhereWasIConfig,
+) -> Result<Transform, WhereWasIParseError> {
+    match (check_transform(&transform), config.invalid_values) {
+        (Ok(()), _) => {}
+        (Err(err), InvalidValuePolicy::Reject) => return Err(err),
+        (Err(err), InvalidValuePolicy::Sanitize) => {
+            warn!("Sanitizing {}: {}", source, err);
+            transform = sanitize_transform(transform);
+        }
+    }
+
+    let length = transform.rotation.length();
+    if (length - 1.0).abs() > ROTATION_TOLERANCE {
+        if config.strict_rotations {
+            return Err(WhereWasIParseError::NonUnitRotation { length });
+        }
+        warn!(
+            "Normalizing the rotation in {}, its length was {}",
+            source, length
+        );
+    }
+    if !transform.rotation.is_normalized() {
+        transform.rotation = transform.rotation.normalize();
+    }
+
+    Ok(transform)
+}
+
+/// Check that all values of `transform` can safely be used
+fn check_transform(transform: &Transform) -> Result<(), WhereWasIParseError> {
+    let [tx, ty, tz] = transform.translation.to_array();
+    let [rx, ry, rz, rw] = transform.rotation.to_array();
+    let [sx, sy, sz] = transform.scale.to_array();
+    let scale = [
+        (Field::ScaleX, sx),
+        (Field::ScaleY, sy),
+        (Field::ScaleZ, sz),
+    ];
+    let values = [
+        (Field::TranslationX, tx),
+        (Field::TranslationY, ty),
+        (Field::TranslationZ, tz),
+        (Field::RotationX, rx),
+        (Field::RotationY, ry),
+        (Field::RotationZ, rz),
+        (Field::RotationW, rw),
+    ]
+    .into_iter()
+    .chain(scale);
+
+    for (field, value) in values {
+        if !value.is_finite() {
+            return Err(WhereWasIParseError::NonFinite { field });
+        }
+    }
+    // `is_normal` also rejects subnormal values, which are just as useless as zero here
+    if !transform.rotation.length_squared().is_normal() {
+        return Err(WhereWasIParseError::ZeroRotation);
+    }
+    for (field, value) in scale {
+        if !value.is_normal() {
+            return Err(WhereWasIParseError::ZeroScale { field });
+        }
+    }
+    Ok(())
+}
+
+/// Replace the invalid values of `transform` with those of [`Transform::IDENTITY`]
+fn sanitize_transform(transform: Transform) -> Transform {
+    let rotation = transform.rotation;
+
+    Transform {
+        translation: transform
+            .translation
+            .to_array()
+            .map(|value| if value.is_finite() { value } else { 0.0 })
+            .into(),
+        rotation: if rotation.is_finite() && rotation.length_squared().is_normal() {
+            rotation
+        } else {
+            Quat::IDENTITY
+        },
+        scale: transform
+            .scale
+            .to_array()
+            .map(|value| if value.is_normal() { value } else { 1.0 })
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::test_config;
+    use crate::{SaveFormat, write_savefile};
+
+    #[test]
+    fn test_check_transform() {
+        assert_eq!(check_transform(&Transform::IDENTITY), Ok(()));
+        assert_eq!(
+            check_transform(&Transform::from_xyz(0.0, f32::NAN, 0.0)),
+            Err(WhereWasIParseError::NonFinite {
+                field: Field::TranslationY
+            })
+        );
+        assert_eq!(
+            check_transform(&Transform::from_rotation(Quat::from_xyzw(
+                0.0, 0.0, 0.0, 0.0
+            ))),
+            Err(WhereWasIParseError::ZeroRotation)
+        );
+        assert_eq!(
+            check_transform(&Transform::from_scale(Vec3::new(1.0, 1.0, 1e-40))),
+            Err(WhereWasIParseError::ZeroScale {
+                field: Field::ScaleZ
+            })
+        );
+    }
+
+    #[test]
+    fn test_sanitize_transform() {
+        let transform = Transform {
+            translation: Vec3::new(1.0, f32::INFINITY, 3.0),
+            rotation: Quat::from_xyzw(0.0, 0.0, 0.0, 0.0),
+            scale: Vec3::new(2.0, 0.0, f32::NAN),
+        };
+
+        assert_eq!(
+            sanitize_transform(transform),
+            Transform {
+                translation: Vec3::new(1.0, 0.0, 3.0),
+                rotation: Quat::IDENTITY,
+                scale: Vec3::new(2.0, 1.0, 1.0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_checked() {
+        let path = Path::new("assets/tests/invalid_values/camera.state");
+        write_savefile(
+            path,
+            &Transform::from_xyz(f32::NAN, 0.0, 0.0),
+            SaveFormat::Text,
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_checked(path, &test_config("assets/tests/invalid_values")),
+            Err(WhereWasIParseError::NonFinite {
+                field: Field::TranslationX
+            })
+        );
+        assert_eq!(
+            read_checked(
+                path,
+                &WhereWasIConfig {
+                    invalid_values: InvalidValuePolicy::Sanitize,
+                    ..test_config("assets/tests/invalid_values")
+                }
+            ),
+            Ok(Transform::IDENTITY)
+        );
+
+        fs::remove_dir_all("assets/tests/invalid_values").unwrap();
+    }
+
+    #[test]
+    fn test_read_checked_normalizes_rotation() {
+        let path = Path::new("assets/tests/non_unit_rotation/camera.state");
+        let rotation = Quat::from_rotation_y(1.0);
+        write_savefile(
+            path,
+            &Transform::from_rotation(rotation * 2.0),
+            SaveFormat::Text,
+        )
+        .unwrap();
+
+        let transform = read_checked(path, &test_config("assets/tests/non_unit_rotation")).unwrap();
+        assert!(transform.rotation.abs_diff_eq(rotation, 1e-6));
+        assert_eq!(
+            read_checked(
+                path,
+                &WhereWasIConfig {
+                    strict_rotations: true,
+                    ..test_config("assets/tests/non_unit_rotation")
+                }
+            ),
+            Err(WhereWasIParseError::NonUnitRotation { length: 2.0 })
+        );
+
+        fs::remove_dir_all("assets/tests/non_unit_rotation").unwrap();
+    }
+}
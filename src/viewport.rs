@@ -0,0 +1,209 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+use bevy_camera::Viewport;
+
+use crate::names::{resolve_names, validate_name};
+use crate::serialization::{parse_pair, versioned_lines};
+use crate::{
+    Field, PersistenceUnavailable, SaveWhereWasI, WhereWasI, WhereWasIConfig, WhereWasIParseError,
+};
+
+/// The path of the viewport of the camera `name` in `directory`
+fn viewport_path(directory: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    Ok(directory.join("viewports").join(format!("{name}.viewport")))
+}
+
+/// Write `viewport` for the camera `name` in `directory`
+fn write_viewport(directory: &Path, name: &str, viewport: &Viewport) -> io::Result<()> {
+    let path = viewport_path(directory, name)?;
+    fs::create_dir_all(directory.join("viewports"))?;
+
+    let position = viewport.physical_position;
+    let size = viewport.physical_size;
+    fs::write(
+        path,
+        format!(
+            "v0\n\nposition:\n{} {}\n\nsize:\n{} {}\n",
+            position.x, position.y, size.x, size.y
+        ),
+    )
+}
+
+/// Parse the position and size of a viewport from the contents of its file
+fn parse_viewport(contents: &str) -> Result<(UVec2, UVec2), WhereWasIParseError> {
+    let lines = versioned_lines(contents)?;
+    let missing = |expected| WhereWasIParseError::MissingLine {
+        line: lines.len() + 1,
+        expected,
+    };
+
+    let (x, y) = parse_pair(&lines, "position:", Field::ViewportPosition)?
+        .ok_or_else(|| missing(Field::ViewportPosition))?;
+    let (width, height): (u32, u32) = parse_pair(&lines, "size:", Field::ViewportSize)?
+        .ok_or_else(|| missing(Field::ViewportSize))?;
+
+    Ok((UVec2::new(x, y), UVec2::new(width.max(1), height.max(1))))
+}
+
+/// Restore the viewports of tracked cameras
+///
+/// The depth range of a camera that already has a viewport is kept.
+pub(crate) fn restore_viewports(
+    mut cameras: Query<(Entity, &WhereWasI, Option<&mut Camera>)>,
+    config: Res<WhereWasIConfig>,
+) {
+    if !config.restore_on_startup {
+        return;
+    }
+
+    let names = resolve_names(
+        cameras
+            .iter()
+            .map(|(entity, where_was_i, _)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        let Ok((_, _, Some(mut camera))) = cameras.get_mut(entity) else {
+            continue;
+        };
+        let Ok(path) = viewport_path(&config.directory, &name) else {
+            continue;
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                error!("Could not read viewport of {}: {}", name, err);
+                continue;
+            }
+        };
+        match parse_viewport(&contents) {
+            Ok((physical_position, physical_size)) => {
+                let depth = camera
+                    .viewport
+                    .as_ref()
+                    .map_or(0.0..1.0, |viewport| viewport.depth.clone());
+                camera.viewport = Some(Viewport {
+                    physical_position,
+                    physical_size,
+                    depth,
+                });
+            }
+            Err(err) => error!("Could not read viewport of {}: {}", name, err),
+        }
+    }
+}
+
+/// Save the viewports of tracked cameras when all entities are saved
+///
+/// The viewport file of a camera without a viewport is removed, so it renders to its whole target
+/// again after a restart.
+pub(crate) fn save_viewports(
+    mut requests: MessageReader<SaveWhereWasI>,
+    cameras: Query<(Entity, &WhereWasI, &Camera)>,
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let save_all = requests.read().any(|request| request.window.is_none());
+    if !save_all || unavailable.is_some() || config.dry_run {
+        return;
+    }
+
+    let names = resolve_names(
+        cameras
+            .iter()
+            .map(|(entity, where_was_i, _)| (entity, where_was_i.name.as_str())),
+        config.name_collisions,
+    );
+
+    for (entity, name) in names {
+        let Ok((_, _, camera)) = cameras.get(entity) else {
+            continue;
+        };
+
+        let result = match &camera.viewport {
+            Some(viewport) => write_viewport(&config.directory, &name, viewport),
+            None => match viewport_path(&config.directory, &name).and_then(fs::remove_file) {
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            },
+        };
+        if let Err(err) = result {
+            error!("Could not save viewport of {}: {}", name, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SaveTrigger;
+    use crate::tests::{test_app, test_config};
+
+    #[test]
+    fn test_parse_viewport() {
+        assert_eq!(
+            parse_viewport("v0\n\nposition:\n640 0\n\nsize:\n640 720\n"),
+            Ok((UVec2::new(640, 0), UVec2::new(640, 720)))
+        );
+        assert_eq!(
+            parse_viewport("v0\n\nposition:\n-1 0\n"),
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 4,
+                expected: Field::ViewportPosition,
+                found: "-1 0".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_save_and_restore_viewports() {
+        let config = test_config("assets/tests/viewports");
+        let viewport = |x| Viewport {
+            physical_position: UVec2::new(x, 0),
+            physical_size: UVec2::new(640, 720),
+            ..default()
+        };
+
+        let mut app = test_app(config.clone());
+        app.add_systems(Update, save_viewports);
+        for (name, x) in [("left", 0), ("right", 640)] {
+            app.world_mut().spawn((
+                WhereWasI::from_name(name),
+                Camera {
+                    viewport: Some(viewport(x)),
+                    ..default()
+                },
+            ));
+        }
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI {
+                trigger: SaveTrigger::Manual,
+                window: None,
+            });
+        app.update();
+
+        let mut app = test_app(config);
+        app.add_systems(Startup, restore_viewports);
+        let right = app
+            .world_mut()
+            .spawn((WhereWasI::from_name("right"), Camera::default()))
+            .id();
+        app.update();
+
+        let restored = app.world().get::<Camera>(right).unwrap().viewport.clone();
+        assert_eq!(
+            restored.map(|viewport| (viewport.physical_position, viewport.physical_size)),
+            Some((UVec2::new(640, 0), UVec2::new(640, 720)))
+        );
+
+        fs::remove_dir_all("assets/tests/viewports").unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::prelude::*;
+
+use crate::{SaveTrigger, SaveWhereWasI};
+
+/// A [`Resource`] with a flag that the browser sets when the page is hidden or unloaded
+///
+/// The flag is shared with the `visibilitychange` and `pagehide` listeners, which run outside of
+/// the schedule.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct PageHidden(Arc<AtomicBool>);
+
+impl PageHidden {
+    /// Whether the page was hidden since the last call
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Listen for the page becoming hidden, when the tab is backgrounded, and for the page being
+/// unloaded, when the tab is closed or navigates away
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn listen_for_page_hide(hidden: Res<PageHidden>) {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::closure::Closure;
+    use web_sys::VisibilityState;
+
+    let Some((window, document)) =
+        web_sys::window().and_then(|window| Some((window.clone(), window.document()?)))
+    else {
+        warn!("Not saving when the page is hidden: no document");
+        return;
+    };
+
+    let on_visibility_change = {
+        let hidden = hidden.clone();
+        let document = document.clone();
+        Closure::<dyn FnMut()>::new(move || {
+            if document.visibility_state() == VisibilityState::Hidden {
+                hidden.0.store(true, Ordering::Relaxed);
+            }
+        })
+    };
+    let on_page_hide = {
+        let hidden = hidden.clone();
+        Closure::<dyn FnMut()>::new(move || hidden.0.store(true, Ordering::Relaxed))
+    };
+
+    if let Err(err) = document.add_event_listener_with_callback(
+        "visibilitychange",
+        on_visibility_change.as_ref().unchecked_ref(),
+    ) {
+        error!("Could not listen for visibilitychange: {:?}", err);
+    }
+    if let Err(err) =
+        window.add_event_listener_with_callback("pagehide", on_page_hide.as_ref().unchecked_ref())
+    {
+        error!("Could not listen for pagehide: {:?}", err);
+    }
+
+    // The listeners live as long as the page
+    on_visibility_change.forget();
+    on_page_hide.forget();
+}
+
+/// The browser's `localStorage`, which keeps the files of `localStorage:` save directories under
+/// their path as key
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) mod local_storage {
+    use std::io;
+
+    use wasm_bindgen::JsValue;
+    use web_sys::Storage;
+
+    fn error(err: JsValue) -> io::Error {
+        io::Error::other(format!("localStorage: {err:?}"))
+    }
+
+    fn storage() -> io::Result<Storage> {
+        web_sys::window()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no window"))?
+            .local_storage()
+            .map_err(error)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no localStorage"))
+    }
+
+    pub(crate) fn get(key: &str) -> io::Result<Option<String>> {
+        storage()?.get_item(key).map_err(error)
+    }
+
+    /// Fails when the storage quota of the page is exceeded
+    pub(crate) fn set(key: &str, value: &str) -> io::Result<()> {
+        storage()?.set_item(key, value).map_err(error)
+    }
+
+    pub(crate) fn remove(key: &str) -> io::Result<()> {
+        storage()?.remove_item(key).map_err(error)
+    }
+
+    pub(crate) fn keys() -> io::Result<Vec<String>> {
+        let storage = storage()?;
+        let length = storage.length().map_err(error)?;
+        (0..length)
+            .filter_map(|index| storage.key(index).map_err(error).transpose())
+            .collect()
+    }
+}
+
+/// Request a save of all entities when the page was hidden or unloaded since the last frame
+pub(crate) fn request_save_on_page_hide(
+    hidden: Res<PageHidden>,
+    mut requests: MessageWriter<SaveWhereWasI>,
+) {
+    if hidden.take() {
+        requests.write(SaveWhereWasI {
+            trigger: SaveTrigger::PageHidden,
+            window: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use crate::session::read_session;
+    use crate::tests::{test_app, test_config};
+    use crate::{WhereWasI, WhereWasISaveCompleted, save_state};
+
+    #[test]
+    fn test_save_on_page_hide() {
+        let directory = Path::new("assets/tests/page_hide");
+        let mut app = test_app(test_config("assets/tests/page_hide"));
+        app.init_resource::<PageHidden>()
+            .add_systems(Update, (request_save_on_page_hide, save_state).chain());
+        app.world_mut()
+            .spawn((WhereWasI::camera(), Transform::from_xyz(1.0, 0.0, 0.0)));
+
+        app.world()
+            .resource::<PageHidden>()
+            .0
+            .store(true, Ordering::Relaxed);
+        app.update();
+        let session = read_session(directory).unwrap().unwrap();
+        assert_eq!(session.trigger, Some(SaveTrigger::PageHidden));
+
+        app.update();
+        let completed = app.world().resource::<Messages<WhereWasISaveCompleted>>();
+        assert_eq!(completed.iter_current_update_messages().count(), 0);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
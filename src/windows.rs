@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+use bevy_window::{Monitor, PrimaryMonitor, PrimaryWindow, WindowClosing, WindowPosition};
+
+use crate::names::validate_name;
+use crate::serialization::{parse_pair, versioned_lines};
+use crate::{Field, PersistenceUnavailable, SaveWhereWasI, WhereWasIConfig, WhereWasIParseError};
+
+/// A component that remembers whether a window was open, and where and how large it was
+///
+/// Windows are recorded under their name in the `windows` subdirectory of the save directory,
+/// every time all entities are saved. Closing a secondary window forgets it, so it isn't restored.
+/// At startup, the size and position of tracked windows that already exist are restored. Windows
+/// that were open but don't exist yet are re-created with
+/// [`WhereWasIPlugin::respawn_window`](crate::WhereWasIPlugin::respawn_window).
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_where_was_i::WhereWasIWindow;
+///
+/// fn open_inspector(mut commands: Commands) {
+///     commands.spawn((Window::default(), WhereWasIWindow::from_name("inspector")));
+/// }
+/// ```
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct WhereWasIWindow {
+    name: String,
+}
+
+impl WhereWasIWindow {
+    /// Track a window under `name`
+    pub fn from_name(name: &str) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// The name the window is recorded under
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A callback that re-creates the window named by the second argument and returns its entity, see
+/// [`WhereWasIPlugin::respawn_window`](crate::WhereWasIPlugin::respawn_window)
+pub type WindowSpawner = fn(&mut Commands, &str) -> Entity;
+
+/// How much of a window has to be on a monitor, in physical pixels, for its recorded position to
+/// be restored
+///
+/// This is roughly what is needed to grab the title bar and drag the window back.
+const MIN_VISIBLE: IVec2 = IVec2::new(64, 32);
+
+/// The recorded state of a window
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct WindowRecord {
+    /// The position in physical pixels, if it was placed explicitly
+    position: Option<IVec2>,
+    /// The size in physical pixels
+    size: UVec2,
+    /// The name of the monitor the window was on, if known
+    monitor: Option<String>,
+}
+
+/// The area covered by `monitor`, in physical pixels
+fn monitor_rect(monitor: &Monitor) -> IRect {
+    IRect::from_corners(
+        monitor.physical_position,
+        monitor.physical_position + monitor.physical_size().as_ivec2(),
+    )
+}
+
+impl WindowRecord {
+    /// Record `window`, which is on one of `monitors`
+    fn from_window(window: &Window, monitors: &[(&Monitor, bool)]) -> Self {
+        let position = match window.position {
+            WindowPosition::At(position) => Some(position),
+            _ => None,
+        };
+        let size = window.physical_size();
+        let monitor = position.and_then(|position| {
+            let center = position + size.as_ivec2() / 2;
+            monitors
+                .iter()
+                .find(|(monitor, _)| monitor_rect(monitor).contains(center))
+                .and_then(|(monitor, _)| monitor.name.clone())
+        });
+
+        Self {
+            position,
+            size,
+            monitor,
+        }
+    }
+
+    /// This record, moved onto one of `monitors` if too little of it would be visible otherwise
+    ///
+    /// The window is centered on the monitor it was recorded on if that one is still connected,
+    /// otherwise on the primary monitor, and shrunk to fit. Without any known monitors, the record
+    /// is left as is.
+    fn fit_to_monitors(mut self, monitors: &[(&Monitor, bool)]) -> Self {
+        let Some(position) = self.position else {
+            return self;
+        };
+        let window = IRect::from_corners(position, position + self.size.as_ivec2());
+        let visible = monitors.iter().any(|(monitor, _)| {
+            let overlap = window.intersect(monitor_rect(monitor));
+            !overlap.is_empty() && overlap.size().cmpge(MIN_VISIBLE).all()
+        });
+        if visible {
+            return self;
+        }
+
+        let target = monitors
+            .iter()
+            .find(|(monitor, _)| monitor.name.is_some() && monitor.name == self.monitor)
+            .or_else(|| monitors.iter().find(|(_, primary)| *primary))
+            .or_else(|| monitors.first());
+        let Some((monitor, _)) = target else {
+            return self;
+        };
+
+        info!(
+            "Window was outside of all monitors, moving it to {}",
+            monitor.name.as_deref().unwrap_or("the primary monitor")
+        );
+        self.size = self.size.min(monitor.physical_size());
+        self.position =
+            Some(monitor.physical_position + (monitor.physical_size() - self.size).as_ivec2() / 2);
+        self.monitor.clone_from(&monitor.name);
+        self
+    }
+
+    fn apply(&self, window: &mut Window) {
+        if let Some(position) = self.position {
+            window.position = WindowPosition::At(position);
+        }
+        window
+            .resolution
+            .set_physical_resolution(self.size.x, self.size.y);
+    }
+}
+
+/// The windows that were open when the previous session saved
+#[derive(Resource, Default, Debug)]
+pub(crate) struct SavedWindows(BTreeMap<String, WindowRecord>);
+
+/// The path of the record of the window `name` in `directory`
+fn window_path(directory: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_name(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    Ok(directory.join("windows").join(format!("{name}.window")))
+}
+
+/// Write `record` for the window `name` in `directory`
+fn write_window(directory: &Path, name: &str, record: &WindowRecord) -> io::Result<()> {
+    let path = window_path(directory, name)?;
+    fs::create_dir_all(directory.join("windows"))?;
+
+    let mut contents = String::from("v0\n");
+    if let Some(position) = record.position {
+        contents.push_str(&format!("\nposition:\n{} {}\n", position.x, position.y));
+    }
+    contents.push_str(&format!("\nsize:\n{} {}\n", record.size.x, record.size.y));
+    if let Some(monitor) = &record.monitor {
+        contents.push_str(&format!("\nmonitor:\n{monitor}\n"));
+    }
+    fs::write(path, contents)
+}
+
+/// Parse the record of a window from the contents of its file
+fn parse_window(contents: &str) -> Result<WindowRecord, WhereWasIParseError> {
+    let lines = versioned_lines(contents)?;
+    let pair = |key, field| parse_pair(&lines, key, field);
+
+    let position = pair("position:", Field::WindowPosition)?.map(|(x, y)| IVec2::new(x, y));
+    let size = pair("size:", Field::WindowSize)?
+        .map(|(x, y): (i32, i32)| UVec2::new(x.max(1) as u32, y.max(1) as u32))
+        .ok_or(WhereWasIParseError::MissingLine {
+            line: lines.len() + 1,
+            expected: Field::WindowSize,
+        })?;
+    let monitor = lines
+        .iter()
+        .position(|line| *line == "monitor:")
+        .and_then(|index| lines.get(index + 1))
+        .filter(|monitor| !monitor.is_empty())
+        .map(|monitor| monitor.to_string());
+
+    Ok(WindowRecord {
+        position,
+        size,
+        monitor,
+    })
+}
+
+/// Read the records of all windows in `directory`
+///
+/// Records that can't be read are logged and skipped.
+fn read_windows(directory: &Path) -> BTreeMap<String, WindowRecord> {
+    let Ok(entries) = fs::read_dir(directory.join("windows")) else {
+        return BTreeMap::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "window" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+
+            let record = fs::read_to_string(&path)
+                .map_err(WhereWasIParseError::from)
+                .and_then(|contents| parse_window(&contents));
+            match record {
+                Ok(record) => Some((name, record)),
+                Err(err) => {
+                    error!("Could not read window {}: {}", name, err);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Read the records of the windows that were open in the previous session
+pub(crate) fn read_saved_windows(mut saved: ResMut<SavedWindows>, config: Res<WhereWasIConfig>) {
+    saved.0 = read_windows(&config.directory);
+}
+
+/// Track the primary window under [`WhereWasIPlugin::primary_window`](crate::WhereWasIPlugin::primary_window),
+/// if it isn't tracked yet
+pub(crate) fn track_primary_window(
+    mut commands: Commands,
+    windows: Query<Entity, (With<PrimaryWindow>, Without<WhereWasIWindow>)>,
+    config: Res<WhereWasIConfig>,
+) {
+    let Some(name) = config.primary_window() else {
+        return;
+    };
+    for window in &windows {
+        commands
+            .entity(window)
+            .insert(WhereWasIWindow::from_name(name));
+    }
+}
+
+/// Restore the size and position of tracked windows, and re-create the ones that don't exist yet
+///
+/// Windows that would end up outside of all connected monitors, for example after undocking a
+/// laptop, are moved onto one of them.
+pub(crate) fn restore_windows(
+    mut commands: Commands,
+    mut windows: Query<(&WhereWasIWindow, &mut Window)>,
+    monitors: Query<(&Monitor, Has<PrimaryMonitor>)>,
+    saved: Res<SavedWindows>,
+    config: Res<WhereWasIConfig>,
+) {
+    let monitors = monitors.iter().collect::<Vec<_>>();
+    let mut missing = saved
+        .0
+        .iter()
+        .map(|(name, record)| (name.clone(), record.clone().fit_to_monitors(&monitors)))
+        .collect::<BTreeMap<_, _>>();
+
+    for (tracked, mut window) in &mut windows {
+        if let Some(record) = missing.remove(&tracked.name) {
+            record.apply(&mut window);
+        }
+    }
+
+    let Some(spawn) = config.respawn_window else {
+        return;
+    };
+    for (name, record) in missing {
+        let window = spawn(&mut commands, &name);
+        commands
+            .entity(window)
+            .insert(WhereWasIWindow::from_name(&name))
+            .queue(move |mut entity: EntityWorldMut| {
+                if let Some(mut window) = entity.get_mut::<Window>() {
+                    record.apply(&mut window);
+                }
+            });
+    }
+}
+
+/// Record all tracked windows when all entities are saved, and forget secondary windows that are
+/// closed while the primary window stays open
+pub(crate) fn track_windows(
+    mut closing: MessageReader<WindowClosing>,
+    mut requests: MessageReader<SaveWhereWasI>,
+    windows: Query<(Entity, &WhereWasIWindow, &Window, Has<PrimaryWindow>)>,
+    monitors: Query<(&Monitor, Has<PrimaryMonitor>)>,
+    config: Res<WhereWasIConfig>,
+    unavailable: Option<Res<PersistenceUnavailable>>,
+) {
+    let closed = closing
+        .read()
+        .map(|closing| closing.window)
+        .collect::<Vec<_>>();
+    let save_all = requests.read().any(|request| request.window.is_none());
+    if unavailable.is_some() || config.dry_run {
+        return;
+    }
+
+    if save_all {
+        let monitors = monitors.iter().collect::<Vec<_>>();
+        for (_, tracked, window, _) in &windows {
+            if let Err(err) = write_window(
+                &config.directory,
+                &tracked.name,
+                &WindowRecord::from_window(window, &monitors),
+            ) {
+                error!("Could not save window {}: {}", tracked.name, err);
+            }
+        }
+    }
+
+    let exiting = windows
+        .iter()
+        .any(|(entity, .., primary)| primary && closed.contains(&entity));
+    if exiting {
+        return;
+    }
+    for (entity, tracked, ..) in &windows {
+        if !closed.contains(&entity) {
+            continue;
+        }
+        if let Err(err) = window_path(&config.directory, &tracked.name).and_then(fs::remove_file)
+            && err.kind() != io::ErrorKind::NotFound
+        {
+            error!("Could not forget window {}: {}", tracked.name, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SaveTrigger;
+    use crate::tests::{test_app, test_config};
+
+    #[test]
+    fn test_window_round_trip() {
+        let directory = Path::new("assets/tests/windows");
+        let record = WindowRecord {
+            position: Some(IVec2::new(-100, 200)),
+            size: UVec2::new(800, 600),
+            monitor: Some("DP-1".into()),
+        };
+        write_window(directory, "inspector", &record).unwrap();
+        write_window(
+            directory,
+            "console",
+            &WindowRecord {
+                position: None,
+                monitor: None,
+                ..record.clone()
+            },
+        )
+        .unwrap();
+
+        let windows = read_windows(directory);
+        assert_eq!(windows.get("inspector"), Some(&record));
+        assert_eq!(windows.get("console").unwrap().position, None);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_fit_to_monitors() {
+        let monitor = |name: &str, x| Monitor {
+            name: Some(name.into()),
+            physical_height: 1080,
+            physical_width: 1920,
+            physical_position: IVec2::new(x, 0),
+            refresh_rate_millihertz: None,
+            scale_factor: 1.0,
+            video_modes: Vec::new(),
+        };
+        let laptop = monitor("eDP-1", 0);
+        let external = monitor("DP-1", 1920);
+        let record = WindowRecord {
+            position: Some(IVec2::new(2000, 100)),
+            size: UVec2::new(800, 600),
+            monitor: Some("DP-1".into()),
+        };
+
+        let docked = [(&laptop, true), (&external, false)];
+        assert_eq!(
+            WindowRecord::from_window(
+                &Window {
+                    position: WindowPosition::At(IVec2::new(2000, 100)),
+                    resolution: (800, 600).into(),
+                    ..default()
+                },
+                &docked
+            ),
+            record
+        );
+        assert_eq!(record.clone().fit_to_monitors(&docked), record);
+        assert_eq!(record.clone().fit_to_monitors(&[]), record);
+
+        let undocked = record.fit_to_monitors(&[(&laptop, true)]);
+        assert_eq!(undocked.position, Some(IVec2::new(560, 240)));
+        assert_eq!(undocked.size, UVec2::new(800, 600));
+        assert_eq!(undocked.monitor.as_deref(), Some("eDP-1"));
+    }
+
+    #[test]
+    fn test_parse_invalid_window() {
+        assert_eq!(
+            parse_window("v0\n\nsize:\n800 wide\n"),
+            Err(WhereWasIParseError::UnexpectedLine {
+                line: 4,
+                expected: Field::WindowSize,
+                found: "800 wide".into()
+            })
+        );
+        assert!(matches!(
+            parse_window("v0\n"),
+            Err(WhereWasIParseError::MissingLine {
+                expected: Field::WindowSize,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_track_and_respawn_windows() {
+        let config = test_config("assets/tests/track_windows");
+        let mut app = test_app(config.clone());
+        app.add_message::<WindowClosing>()
+            .add_systems(Update, track_windows);
+        let primary = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+        let mut inspector = Window {
+            position: WindowPosition::At(IVec2::new(10, 20)),
+            ..default()
+        };
+        inspector.resolution.set_physical_resolution(640, 480);
+        app.world_mut()
+            .spawn((inspector, WhereWasIWindow::from_name("inspector")));
+        let console = app
+            .world_mut()
+            .spawn((Window::default(), WhereWasIWindow::from_name("console")))
+            .id();
+
+        app.world_mut()
+            .resource_mut::<Messages<SaveWhereWasI>>()
+            .write(SaveWhereWasI {
+                trigger: SaveTrigger::Manual,
+                window: None,
+            });
+        app.update();
+        assert!(fs::exists("assets/tests/track_windows/windows/console.window").unwrap());
+
+        app.world_mut()
+            .resource_mut::<Messages<WindowClosing>>()
+            .write(WindowClosing { window: console });
+        app.update();
+        assert!(!fs::exists("assets/tests/track_windows/windows/console.window").unwrap());
+
+        app.world_mut()
+            .resource_mut::<Messages<WindowClosing>>()
+            .write(WindowClosing { window: primary });
+        app.update();
+        assert!(fs::exists("assets/tests/track_windows/windows/inspector.window").unwrap());
+
+        let mut app = test_app(WhereWasIConfig {
+            respawn_window: Some(|commands, _name| commands.spawn(Window::default()).id()),
+            ..config
+        });
+        app.init_resource::<SavedWindows>()
+            .add_systems(Startup, (read_saved_windows, restore_windows).chain());
+        app.update();
+
+        let mut windows = app.world_mut().query::<(&WhereWasIWindow, &Window)>();
+        let windows = windows.iter(app.world()).collect::<Vec<_>>();
+        assert_eq!(windows.len(), 1);
+        let (tracked, window) = windows[0];
+        assert_eq!(tracked.name(), "inspector");
+        assert_eq!(window.position, WindowPosition::At(IVec2::new(10, 20)));
+        assert_eq!(window.physical_size(), UVec2::new(640, 480));
+
+        fs::remove_dir_all("assets/tests/track_windows").unwrap();
+    }
+
+    #[test]
+    fn test_track_primary_window() {
+        let directory = Path::new("assets/tests/primary_window");
+        let record = WindowRecord {
+            position: Some(IVec2::new(30, 40)),
+            size: UVec2::new(1024, 768),
+            monitor: None,
+        };
+        write_window(directory, "main", &record).unwrap();
+
+        let mut app = test_app(WhereWasIConfig {
+            primary_window: Some("main".into()),
+            ..test_config("assets/tests/primary_window")
+        });
+        let primary = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+        let secondary = app.world_mut().spawn(Window::default()).id();
+        app.init_resource::<SavedWindows>()
+            .add_systems(PreStartup, (read_saved_windows, track_primary_window))
+            .add_systems(PostStartup, restore_windows);
+        app.update();
+
+        let tracked = app.world().get::<WhereWasIWindow>(primary).unwrap();
+        assert_eq!(tracked.name(), "main");
+        assert!(app.world().get::<WhereWasIWindow>(secondary).is_none());
+        let window = app.world().get::<Window>(primary).unwrap();
+        assert_eq!(window.position, WindowPosition::At(IVec2::new(30, 40)));
+        assert_eq!(window.physical_size(), record.size);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}